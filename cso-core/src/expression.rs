@@ -3,6 +3,7 @@ use crate::ColumnRefSet;
 use dyn_clonable::clonable;
 use std::fmt::Debug;
 
+#[typetag::serde(tag = "type")]
 #[clonable]
 pub trait ScalarExpression: AsAny + Debug + Clone {
     fn is_boolean_expression(&self) -> bool {
@@ -11,11 +11,12 @@ pub mod metadata;
 pub mod operator;
 pub mod property;
 pub mod rule;
+pub mod validator;
 
 mod task;
 
 use crate::memo::{GroupPlanRef, Memo};
-use crate::metadata::MdAccessor;
+use crate::metadata::{MdAccessor, MdCache, Stats};
 use crate::operator::{LogicalOperator, Operator, PhysicalOperator};
 use crate::property::{LogicalProperties, PhysicalProperties};
 use crate::rule::{RuleId, RuleSet};
@@ -24,15 +25,23 @@ use bit_set::BitSet;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 use std::hash::Hash;
-use std::marker::PhantomData;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 pub trait OptimizerType: 'static + PartialEq + Eq + Hash + Clone {
     type RuleId: RuleId;
     type OperatorId: PartialEq + Debug;
     type MdId: PartialEq + Eq + Clone + Hash + Debug + Serialize + for<'a> Deserialize<'a>;
+
+    /// Writes the minidump of an optimization to `path`, see [`Options::with_minidump_path`].
+    /// Optimizers whose plans can't be serialized don't support minidumps.
+    fn write_minidump(_capture: &Capture<Self>, _path: &Path) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "minidumps are not supported"))
+    }
 }
 
+#[derive(Clone, Debug)]
 pub struct LogicalPlan<T: OptimizerType> {
     op: Rc<dyn LogicalOperator<T>>,
     inputs: Vec<LogicalPlan<T>>,
@@ -53,20 +62,75 @@ impl<T: OptimizerType> LogicalPlan<T> {
         }
     }
 
+    pub fn operator(&self) -> &Rc<dyn LogicalOperator<T>> {
+        &self.op
+    }
+
+    pub fn inputs(&self) -> &[LogicalPlan<T>] {
+        &self.inputs
+    }
+
     pub fn required_properties(&self) -> &[PhysicalProperties<T>] {
         &self.required_properties
     }
+
+    /// Returns the plan with the operators standing for other plans, like views, replaced by them.
+    pub fn expand(self, md_accessor: &MdAccessor<T>, next_column_id: &mut u32) -> LogicalPlan<T> {
+        if let Some(plan) = self.op.expand(md_accessor, next_column_id) {
+            // the plan may stand on other views in turn
+            return plan.expand(md_accessor, next_column_id);
+        }
+        let inputs = self
+            .inputs
+            .into_iter()
+            .map(|input| input.expand(md_accessor, next_column_id))
+            .collect();
+        LogicalPlan { inputs, ..self }
+    }
+
+    /// Returns the ids of the columns produced anywhere in the plan.
+    pub fn derive_column_ids(&self) -> ColumnRefSet {
+        let mut column_set = ColumnRefSet::new();
+        self.derive_plan_columns(&mut column_set);
+        column_set
+    }
+
+    fn derive_plan_columns(&self, column_set: &mut ColumnRefSet) -> Plan<T> {
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|input| input.derive_plan_columns(column_set))
+            .collect();
+        let plan = Plan::new(Operator::Logical(self.op.clone()), inputs, None);
+        plan.derive_output_columns(column_set);
+        plan
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct PhysicalPlan<T: OptimizerType> {
     op: Rc<dyn PhysicalOperator<T>>,
     inputs: Vec<PhysicalPlan<T>>,
+    statistics: Option<Rc<dyn Stats>>,
 }
 
 impl<T: OptimizerType> PhysicalPlan<T> {
     pub const fn new(op: Rc<dyn PhysicalOperator<T>>, inputs: Vec<PhysicalPlan<T>>) -> Self {
-        PhysicalPlan { op, inputs }
+        PhysicalPlan {
+            op,
+            inputs,
+            statistics: None,
+        }
+    }
+
+    /// Attaches the statistics the plan was costed with, which explain shows.
+    pub fn with_statistics(mut self, statistics: Rc<dyn Stats>) -> Self {
+        self.statistics = Some(statistics);
+        self
+    }
+
+    pub fn statistics(&self) -> Option<&Rc<dyn Stats>> {
+        self.statistics.as_ref()
     }
 
     pub fn operator(&self) -> &Rc<dyn PhysicalOperator<T>> {
@@ -76,6 +140,35 @@ impl<T: OptimizerType> PhysicalPlan<T> {
     pub fn inputs(&self) -> &[PhysicalPlan<T>] {
         &self.inputs
     }
+
+    /// Returns the physical properties delivered by the plan, derived bottom-up from its inputs.
+    pub fn derive_output_properties(&self) -> Rc<PhysicalProperties<T>> {
+        let input_props = self
+            .inputs
+            .iter()
+            .map(|input| input.derive_output_properties())
+            .collect::<Vec<_>>();
+        self.op.derive_output_properties(&input_props)
+    }
+
+    /// Returns the plan as an indented operator tree, one operator per line followed by its
+    /// statistics if known.
+    pub fn explain(&self) -> String {
+        let mut output = String::new();
+        self.explain_with_indent(0, &mut output);
+        output
+    }
+
+    fn explain_with_indent(&self, indent: usize, output: &mut String) {
+        output.push_str(&format!("{:indent$}{:?}", "", self.op, indent = indent * 2));
+        if let Some(statistics) = &self.statistics {
+            output.push_str(&format!(" [{}]", statistics.explain()));
+        }
+        output.push('\n');
+        for input in &self.inputs {
+            input.explain_with_indent(indent + 1, output);
+        }
+    }
 }
 
 impl<T: OptimizerType> PartialEq<Self> for PhysicalPlan<T> {
@@ -122,22 +215,99 @@ impl<T: OptimizerType> Plan<T> {
     }
 }
 
-#[derive(Default)]
-pub struct Options {}
+/// Options of an optimization.
+///
+/// Estimated row counts can be overridden to explore plan choices under other data sizes: those
+/// of relations, which the statistics derived from them scale to, and those of operators of the
+/// input plan, addressed by the positions of the inputs leading to them from the root. An
+/// operator standing for another plan, like a view, is addressed as the root of that plan.
+///
+/// Optimizations may also write a minidump, holding everything needed to reproduce them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Options<T: OptimizerType> {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    relation_row_counts: Vec<(T::MdId, u64)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    operator_row_counts: Vec<(Vec<usize>, u64)>,
+    /// not part of minidumps, so replaying one doesn't overwrite it
+    #[serde(skip)]
+    minidump_path: Option<PathBuf>,
+}
+
+impl<T: OptimizerType> Default for Options<T> {
+    fn default() -> Self {
+        Options {
+            relation_row_counts: Vec::new(),
+            operator_row_counts: Vec::new(),
+            minidump_path: None,
+        }
+    }
+}
+
+impl<T: OptimizerType> Options<T> {
+    /// Assumes the relation `md_id` has `rows` rows.
+    pub fn with_relation_row_count(mut self, md_id: T::MdId, rows: u64) -> Self {
+        self.relation_row_counts.retain(|(id, _)| *id != md_id);
+        self.relation_row_counts.push((md_id, rows));
+        self
+    }
+
+    /// Assumes the operator of the input plan at `path` returns `rows` rows, e.g. `[0]` for the
+    /// input of the root.
+    pub fn with_operator_row_count(mut self, path: Vec<usize>, rows: u64) -> Self {
+        self.operator_row_counts.retain(|(other, _)| *other != path);
+        self.operator_row_counts.push((path, rows));
+        self
+    }
+
+    /// Writes the minidump of every optimization to `path`, replacing the previous one.
+    pub fn with_minidump_path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.minidump_path = Some(path.into());
+        self
+    }
+
+    pub fn relation_row_counts(&self) -> &[(T::MdId, u64)] {
+        &self.relation_row_counts
+    }
+
+    pub fn operator_row_counts(&self) -> &[(Vec<usize>, u64)] {
+        &self.operator_row_counts
+    }
+
+    pub fn minidump_path(&self) -> Option<&Path> {
+        self.minidump_path.as_deref()
+    }
+}
+
+/// What an optimization depended on and produced: the metadata is the metadata it fetched.
+pub struct Capture<'a, T: OptimizerType> {
+    pub options: &'a Options<T>,
+    pub plan: &'a LogicalPlan<T>,
+    pub required_properties: &'a Rc<PhysicalProperties<T>>,
+    pub rule_set: &'a RuleSet<T>,
+    pub metadata: &'a MdCache<T>,
+    pub result: &'a PhysicalPlan<T>,
+}
 
 pub struct Optimizer<T: OptimizerType> {
-    _options: Options,
-    _mark: PhantomData<T>,
+    options: Options<T>,
+    minidump_error: Option<io::Error>,
 }
 
 impl<T: OptimizerType> Optimizer<T> {
-    pub fn new(_options: Options) -> Optimizer<T> {
+    pub fn new(options: Options<T>) -> Optimizer<T> {
         Optimizer {
-            _options,
-            _mark: PhantomData,
+            options,
+            minidump_error: None,
         }
     }
 
+    /// Returns why the minidump of the last optimization couldn't be written, if it couldn't.
+    pub fn minidump_error(&self) -> Option<&io::Error> {
+        self.minidump_error.as_ref()
+    }
+
     pub fn optimize(
         &mut self,
         plan: LogicalPlan<T>,
@@ -145,14 +315,54 @@ impl<T: OptimizerType> Optimizer<T> {
         md_accessor: MdAccessor<T>,
         rule_set: RuleSet<T>,
     ) -> PhysicalPlan<T> {
-        let mut optimizer_ctx = OptimizerContext::new(md_accessor, rule_set);
-        optimizer_ctx.memo_mut().init(plan);
+        let Some(path) = self.options.minidump_path.clone() else {
+            return self.optimize_and_capture(plan, required_properties, md_accessor, rule_set, |capture| {
+                capture.result.clone()
+            });
+        };
+        let (plan, result) = self.optimize_and_capture(plan, required_properties, md_accessor, rule_set, |capture| {
+            (capture.result.clone(), T::write_minidump(capture, &path))
+        });
+        self.minidump_error = result.err();
+        plan
+    }
+
+    /// Optimizes the plan, and returns what `capture` makes of the optimization.
+    pub fn optimize_and_capture<R>(
+        &mut self,
+        plan: LogicalPlan<T>,
+        required_properties: Rc<PhysicalProperties<T>>,
+        md_accessor: MdAccessor<T>,
+        rule_set: RuleSet<T>,
+        capture: impl FnOnce(&Capture<T>) -> R,
+    ) -> R {
+        self.minidump_error = None;
+        let relation_row_counts = self.options.relation_row_counts.iter().cloned().collect();
+        let md_accessor = md_accessor.with_relation_row_counts(relation_row_counts);
+        let mut memo = Memo::new();
+        memo.init(plan.clone(), &md_accessor, &self.options.operator_row_counts);
+        let mut optimizer_ctx = OptimizerContext::new(memo, md_accessor, rule_set);
         let mut task_runner = TaskRunner::new();
         let initial_task =
             OptimizeGroupTask::new(optimizer_ctx.memo().root_group().clone(), required_properties.clone());
         task_runner.push_task(initial_task);
         task_runner.run(&mut optimizer_ctx);
-        optimizer_ctx.memo().extract_best_plan(&required_properties)
+        let result = optimizer_ctx.memo().extract_best_plan(&required_properties);
+
+        if cfg!(debug_assertions) {
+            if let Err(err) = validator::validate_plan(&result, &required_properties) {
+                panic!("invalid physical plan: {}\n{}", err, result.explain());
+            }
+        }
+
+        capture(&Capture {
+            options: &self.options,
+            plan: &plan,
+            required_properties: &required_properties,
+            rule_set: optimizer_ctx.rule_set(),
+            metadata: &optimizer_ctx.md_accessor().accessed_metadata(),
+            result: &result,
+        })
     }
 }
 
@@ -163,9 +373,9 @@ pub struct OptimizerContext<T: OptimizerType> {
 }
 
 impl<T: OptimizerType> OptimizerContext<T> {
-    fn new(md_accessor: MdAccessor<T>, rule_set: RuleSet<T>) -> Self {
+    fn new(memo: Memo<T>, md_accessor: MdAccessor<T>, rule_set: RuleSet<T>) -> Self {
         OptimizerContext {
-            memo: Memo::new(),
+            memo,
             md_accessor,
             rule_set,
         }
@@ -235,4 +445,8 @@ impl ColumnRefSet {
     pub fn len(&self) -> usize {
         self.bit_set.len()
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.bit_set.iter().map(|id| id as u32)
+    }
 }
@@ -1,5 +1,5 @@
 use crate::cost::Cost;
-use crate::metadata::Stats;
+use crate::metadata::{MdAccessor, Stats};
 use crate::operator::Operator;
 use crate::property::PhysicalProperties;
 use crate::rule::{Rule, RuleId};
@@ -118,6 +118,7 @@ pub struct Group<T: OptimizerType> {
     physical_plans: Vec<GroupPlanRef<T>>,
     is_explored: bool,
     statistics: Option<Rc<dyn Stats>>,
+    row_count_override: Option<u64>,
     lowest_cost_plans: LowestCostPlans<T>,
     child_required_properties: ChildRequiredPropertiesMap<T>,
 }
@@ -133,6 +134,7 @@ impl<T: OptimizerType> Group<T> {
             physical_plans: Vec::new(),
             is_explored: false,
             statistics: None,
+            row_count_override: None,
             lowest_cost_plans: HashMap::new(),
             child_required_properties: HashMap::new(),
         }
@@ -193,6 +195,11 @@ impl<T: OptimizerType> Group<T> {
         &self.statistics
     }
 
+    /// Returns the row count the options assume for the group, if any.
+    pub fn row_count_override(&self) -> Option<u64> {
+        self.row_count_override
+    }
+
     pub fn lowest_cost_plans(&self) -> &HashMap<Rc<PhysicalProperties<T>>, (Cost, GroupPlanRef<T>)> {
         &self.lowest_cost_plans
     }
@@ -251,20 +258,29 @@ impl<T: OptimizerType> Group<T> {
         let operator = plan.borrow().operator().physical_op().clone();
 
         let mut inputs = Vec::new();
-        if plan.borrow().inputs().is_empty() {
-            return PhysicalPlan::new(operator, inputs);
+        if !plan.borrow().inputs().is_empty() {
+            let (_, child_reqd_props) = self.child_required_props(required_properties).unwrap();
+            for (group, child_reqd_prop) in plan.borrow().inputs().iter().zip(child_reqd_props) {
+                let child_plan = group.borrow().extract_best_plan(child_reqd_prop);
+                inputs.push(child_plan);
+            }
         }
 
-        let (_, child_reqd_props) = self.child_required_props(required_properties).unwrap();
-        for (group, child_reqd_prop) in plan.borrow().inputs().iter().zip(child_reqd_props) {
-            let child_plan = group.borrow().extract_best_plan(child_reqd_prop);
-            inputs.push(child_plan);
+        let plan = PhysicalPlan::new(operator, inputs);
+        match &self.statistics {
+            Some(statistics) => plan.with_statistics(statistics.clone()),
+            None => plan,
         }
-
-        PhysicalPlan::new(operator, inputs)
     }
 }
 
+/// The state of copying the input plan into the memo.
+struct InitContext<'a> {
+    /// The positions of the inputs leading from the root to the operator copied
+    path: Vec<usize>,
+    operator_row_counts: &'a [(Vec<usize>, u64)],
+}
+
 pub struct Memo<T: OptimizerType> {
     groups: Vec<GroupRef<T>>,
     root_group: Option<GroupRef<T>>,
@@ -281,8 +297,21 @@ impl<T: OptimizerType> Memo<T> {
         }
     }
 
-    pub fn init(&mut self, plan: LogicalPlan<T>) {
-        let root_group = self.copy_in(None, plan);
+    /// Copies the plan into the memo, expanding the operators standing for other plans first.
+    /// The groups of the operators at the paths of `operator_row_counts` get those row counts.
+    pub fn init(
+        &mut self,
+        plan: LogicalPlan<T>,
+        md_accessor: &MdAccessor<T>,
+        operator_row_counts: &[(Vec<usize>, u64)],
+    ) {
+        let mut next_column_id = plan.derive_column_ids().iter().last().map_or(0, |id| id + 1);
+        let plan = plan.expand(md_accessor, &mut next_column_id);
+        let mut init = InitContext {
+            path: Vec::new(),
+            operator_row_counts,
+        };
+        let root_group = self.copy_in(plan, &mut init);
         self.root_group = Some(root_group);
     }
 
@@ -301,16 +330,27 @@ impl<T: OptimizerType> Memo<T> {
         self.insert_group_plan(group_plan, target_group)
     }
 
-    fn copy_in(&mut self, target_group: Option<GroupRef<T>>, plan: LogicalPlan<T>) -> GroupRef<T> {
+    fn copy_in(&mut self, plan: LogicalPlan<T>, init: &mut InitContext) -> GroupRef<T> {
         let mut inputs = Vec::new();
-        for input in plan.inputs {
-            let group = self.copy_in(None, input);
+        for (i, input) in plan.inputs.into_iter().enumerate() {
+            init.path.push(i);
+            let group = self.copy_in(input, init);
+            init.path.pop();
             inputs.push(group);
         }
 
         let group_plan = GroupPlan::new(Operator::Logical(plan.op), inputs);
-        let plan_ref = self.insert_group_plan(group_plan, target_group);
+        let plan_ref = self.insert_group_plan(group_plan, None);
         let group = plan_ref.borrow().group();
+        let row_count = init
+            .operator_row_counts
+            .iter()
+            .find(|(path, _)| *path == init.path)
+            .map(|(_, rows)| *rows);
+        // the group may hold an operator copied in before, at another path
+        if row_count.is_some() {
+            group.borrow_mut().row_count_override = row_count;
+        }
         group
     }
 
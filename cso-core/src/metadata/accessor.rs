@@ -1,12 +1,16 @@
 use crate::metadata::provider::MdProvider;
-use crate::metadata::{MdCache, Metadata};
+use crate::metadata::{MdCache, MdError, Metadata};
 use crate::OptimizerType;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
+/// Metadata access of one optimization. Metadata is fetched from the provider once and kept,
+/// so the optimization sees the same metadata throughout even if the provider changes.
 pub struct MdAccessor<T: OptimizerType> {
     md_cache: RefCell<MdCache<T>>,
     md_provider: Rc<dyn MdProvider<T>>,
+    relation_row_counts: HashMap<T::MdId, u64>,
 }
 
 impl<T: OptimizerType> MdAccessor<T> {
@@ -14,20 +18,77 @@ impl<T: OptimizerType> MdAccessor<T> {
         Self {
             md_cache: RefCell::new(MdCache::new()),
             md_provider,
+            relation_row_counts: HashMap::new(),
         }
     }
 
-    pub fn retrieve_metadata(&self, md_id: &T::MdId) -> Option<Box<dyn Metadata>> {
+    /// Overrides the row counts of relations, by mdid, for the statistics derived from them.
+    pub fn with_relation_row_counts(mut self, relation_row_counts: HashMap<T::MdId, u64>) -> Self {
+        self.relation_row_counts = relation_row_counts;
+        self
+    }
+
+    /// Returns the row count the relation is assumed to have instead of its statistics, if any.
+    pub fn relation_row_count(&self, md_id: &T::MdId) -> Option<u64> {
+        self.relation_row_counts.get(md_id).copied()
+    }
+
+    pub fn retrieve_metadata(&self, md_id: &T::MdId) -> Result<Box<dyn Metadata>, MdError> {
         let mut md_cache = self.md_cache.borrow_mut();
         match md_cache.get(md_id) {
-            Some(md) => Some(md.clone()),
-            None => match self.md_provider.retrieve_metadata(md_id) {
-                Some(md) => {
-                    md_cache.insert(md_id.clone(), md.clone());
-                    Some(md)
-                }
-                None => None,
-            },
+            Some(md) => Ok(md.clone()),
+            None => {
+                let md = self.md_provider.retrieve_metadata(md_id)?;
+                md_cache.insert(md_id.clone(), md.clone());
+                Ok(md)
+            }
         }
     }
+
+    /// Returns the metadata retrieved so far.
+    pub fn accessed_metadata(&self) -> MdCache<T> {
+        self.md_cache.borrow().clone()
+    }
+
+    /// Retrieves the metadata as the concrete type `M`.
+    pub fn retrieve_metadata_as<M: Metadata + Clone>(&self, md_id: &T::MdId) -> Result<M, MdError> {
+        let md = self.retrieve_metadata(md_id)?;
+        match md.downcast_ref::<M>() {
+            Some(md) => Ok(md.clone()),
+            None => Err(MdError::UnexpectedType {
+                md_id: format!("{:?}", md_id),
+                actual: md.typetag_name(),
+                expected: type_name::<M>(),
+            }),
+        }
+    }
+
+    /// Finds metadata of type `M` matching the filter.
+    pub fn find_metadata_as<M: Metadata + Clone>(
+        &self,
+        filter: impl Fn(&M) -> bool,
+    ) -> Result<Option<(T::MdId, M)>, MdError> {
+        let filter = |md: &dyn Metadata| md.downcast_ref::<M>().is_some_and(&filter);
+        let Some((md_id, md)) = self.md_provider.find_metadata(&filter)? else {
+            return Ok(None);
+        };
+
+        // prefer the metadata this optimization has already seen
+        let md = self
+            .md_cache
+            .borrow_mut()
+            .entry(md_id.clone())
+            .or_insert(md)
+            .clone();
+        match md.downcast_ref::<M>() {
+            Some(md) => Ok(Some((md_id, md.clone()))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Returns the name of the type without its module path.
+pub(crate) fn type_name<M>() -> &'static str {
+    let name = std::any::type_name::<M>();
+    name.rsplit("::").next().unwrap_or(name)
 }
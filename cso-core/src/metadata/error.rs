@@ -0,0 +1,75 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// Errors of retrieving metadata. The mdid is kept in its debug representation.
+#[derive(Debug)]
+pub enum MdError {
+    /// No metadata exists for the mdid.
+    NotFound { md_id: String },
+    /// No metadata of the expected type has the name.
+    NameNotFound { name: String, expected: &'static str },
+    /// The metadata is not of the expected type.
+    UnexpectedType {
+        md_id: String,
+        actual: &'static str,
+        expected: &'static str,
+    },
+    /// The metadata exists but could not be read.
+    Io {
+        md_id: String,
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The metadata could not be deserialized.
+    Malformed {
+        md_id: String,
+        path: PathBuf,
+        message: String,
+    },
+    /// The metadata could not be kept in a cache shared between threads.
+    Uncacheable { md_id: String, message: String },
+}
+
+impl MdError {
+    /// Returns the mdid of the failed retrieval, or the name for lookups by name.
+    pub fn md_id(&self) -> &str {
+        match self {
+            MdError::NotFound { md_id }
+            | MdError::UnexpectedType { md_id, .. }
+            | MdError::Io { md_id, .. }
+            | MdError::Malformed { md_id, .. }
+            | MdError::Uncacheable { md_id, .. } => md_id,
+            MdError::NameNotFound { name, .. } => name,
+        }
+    }
+}
+
+impl Display for MdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MdError::NotFound { md_id } => write!(f, "metadata {md_id} not found"),
+            MdError::NameNotFound { name, expected } => write!(f, "{expected} named {name:?} not found"),
+            MdError::UnexpectedType {
+                md_id,
+                actual,
+                expected,
+            } => write!(f, "metadata {md_id} is {actual}, expected {expected}"),
+            MdError::Io { md_id, path, source } => {
+                write!(f, "failed to read metadata {md_id} from {}: {source}", path.display())
+            }
+            MdError::Malformed { md_id, path, message } => {
+                write!(f, "malformed metadata {md_id} in {}: {message}", path.display())
+            }
+            MdError::Uncacheable { md_id, message } => write!(f, "failed to cache metadata {md_id}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for MdError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MdError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -1,15 +1,20 @@
 mod accessor;
+mod error;
 mod provider;
+mod shared_cache;
 mod statistics;
 
 pub use self::accessor::MdAccessor;
-pub use self::provider::{CachedMdProvider, MdProvider};
+pub use self::error::MdError;
+pub use self::provider::{CachedMdProvider, JsonDirMdProvider, MdEntry, MdFilter, MdProvider};
+pub use self::shared_cache::{SharedMdCache, SharedMdSnapshot};
 pub use self::statistics::Stats;
 
 use crate::any::AsAny;
 use crate::OptimizerType;
 use dyn_clonable::clonable;
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::fmt::Debug;
 
@@ -24,7 +29,7 @@ impl dyn Metadata {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct MdCache<T: OptimizerType> {
     cache: HashMap<T::MdId, Box<dyn Metadata>>,
 }
@@ -41,4 +46,12 @@ impl<T: OptimizerType> MdCache<T> {
     pub fn insert(&mut self, key: T::MdId, val: Box<dyn Metadata>) -> Option<Box<dyn Metadata>> {
         self.cache.insert(key, val)
     }
+
+    pub fn entry(&mut self, key: T::MdId) -> Entry<'_, T::MdId, Box<dyn Metadata>> {
+        self.cache.entry(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&T::MdId, &Box<dyn Metadata>)> {
+        self.cache.iter()
+    }
 }
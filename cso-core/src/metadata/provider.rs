@@ -1,8 +1,27 @@
-use crate::metadata::{MdCache, Metadata};
+use crate::metadata::{MdCache, MdError, Metadata};
 use crate::OptimizerType;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Mutex, MutexGuard};
+
+/// Finds metadata matching a predicate.
+pub type MdFilter<'a> = &'a dyn Fn(&dyn Metadata) -> bool;
+
+/// Metadata found by a provider, with its mdid.
+pub type MdEntry<T> = (<T as OptimizerType>::MdId, Box<dyn Metadata>);
 
 pub trait MdProvider<T: OptimizerType> {
-    fn retrieve_metadata(&self, md_id: &T::MdId) -> Option<Box<dyn Metadata>>;
+    fn retrieve_metadata(&self, md_id: &T::MdId) -> Result<Box<dyn Metadata>, MdError>;
+
+    /// Returns any metadata matching the filter. Providers which can't enumerate their
+    /// metadata find nothing.
+    fn find_metadata(&self, _filter: MdFilter) -> Result<Option<MdEntry<T>>, MdError> {
+        Ok(None)
+    }
 }
 
 pub struct CachedMdProvider<T: OptimizerType> {
@@ -16,10 +35,130 @@ impl<T: OptimizerType> CachedMdProvider<T> {
 }
 
 impl<T: OptimizerType> MdProvider<T> for CachedMdProvider<T> {
-    fn retrieve_metadata(&self, md_id: &T::MdId) -> Option<Box<dyn Metadata>> {
+    fn retrieve_metadata(&self, md_id: &T::MdId) -> Result<Box<dyn Metadata>, MdError> {
         match self.md_cache.get(md_id) {
-            Some(md) => Some(md.clone()),
-            None => None,
+            Some(md) => Ok(md.clone()),
+            None => Err(MdError::NotFound {
+                md_id: format!("{:?}", md_id),
+            }),
+        }
+    }
+
+    fn find_metadata(&self, filter: MdFilter) -> Result<Option<MdEntry<T>>, MdError> {
+        Ok(self
+            .md_cache
+            .iter()
+            .find(|(_, md)| filter(md.as_ref()))
+            .map(|(md_id, md)| (md_id.clone(), md.clone())))
+    }
+}
+
+/// Loads metadata from a directory holding one `<mdid>.json` file per metadata, in the
+/// serialized form of [`Metadata`]. Files are read on first access and cached afterwards, as
+/// read, so the provider can be shared between threads.
+pub struct JsonDirMdProvider<T: OptimizerType> {
+    dir: PathBuf,
+    files: Mutex<HashMap<T::MdId, String>>,
+}
+
+impl<T: OptimizerType> JsonDirMdProvider<T>
+where
+    T::MdId: Display,
+{
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {
+            dir: dir.into(),
+            files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(dir: &Path, md_id: &T::MdId) -> PathBuf {
+        dir.join(format!("{}.json", md_id))
+    }
+
+    /// Writes every metadata in the cache to `dir`, in the layout read by the provider.
+    pub fn write_md_cache<P: AsRef<Path>>(dir: P, md_cache: &MdCache<T>) -> io::Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        for (md_id, md) in md_cache.iter() {
+            let json = serde_json::to_string_pretty(md).map_err(io::Error::other)?;
+            fs::write(Self::path(dir, md_id), json + "\n")?;
+        }
+        Ok(())
+    }
+
+    fn load(&self, md_id: &T::MdId) -> Result<Box<dyn Metadata>, MdError> {
+        let path = Self::path(&self.dir, md_id);
+        let cached = self.files().get(md_id).cloned();
+        let json = match cached {
+            Some(json) => json,
+            None => {
+                let json = Self::read(&path, md_id)?;
+                self.files().insert(md_id.clone(), json.clone());
+                json
+            }
+        };
+        serde_json::from_str(&json).map_err(|err| MdError::Malformed {
+            md_id: format!("{:?}", md_id),
+            path,
+            message: err.to_string(),
+        })
+    }
+
+    fn files(&self) -> MutexGuard<'_, HashMap<T::MdId, String>> {
+        self.files.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn read(path: &Path, md_id: &T::MdId) -> Result<String, MdError> {
+        fs::read_to_string(path).map_err(|source| match source.kind() {
+            io::ErrorKind::NotFound => MdError::NotFound {
+                md_id: format!("{:?}", md_id),
+            },
+            _ => MdError::Io {
+                md_id: format!("{:?}", md_id),
+                path: path.to_path_buf(),
+                source,
+            },
+        })
+    }
+}
+
+impl<T: OptimizerType> MdProvider<T> for JsonDirMdProvider<T>
+where
+    T::MdId: Display + FromStr,
+{
+    fn retrieve_metadata(&self, md_id: &T::MdId) -> Result<Box<dyn Metadata>, MdError> {
+        self.load(md_id)
+    }
+
+    /// Loads the files of the directory in file name order until one matches.
+    fn find_metadata(&self, filter: MdFilter) -> Result<Option<MdEntry<T>>, MdError> {
+        let entries = fs::read_dir(&self.dir).map_err(|source| MdError::Io {
+            md_id: "*".to_string(),
+            path: self.dir.clone(),
+            source,
+        })?;
+        let mut md_ids = entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "json" {
+                    return None;
+                }
+                path.file_stem()?.to_str()?.parse::<T::MdId>().ok()
+            })
+            .collect::<Vec<_>>();
+        md_ids.sort_by_key(|md_id| md_id.to_string());
+
+        for md_id in md_ids {
+            let md = self.retrieve_metadata(&md_id)?;
+            if filter(md.as_ref()) {
+                return Ok(Some((md_id, md)));
+            }
         }
+        Ok(None)
     }
 }
@@ -0,0 +1,405 @@
+use crate::metadata::{MdEntry, MdError, MdFilter, MdProvider, Metadata};
+use crate::OptimizerType;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Debug, Display};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+/// Cached metadata, serialized as metadata may hold `Rc`s which can't be shared between threads.
+/// Each thread deserializes it once, see [`DECODED`].
+struct Version {
+    md: Arc<str>,
+    version: u64,
+    /// The generation of the last invalidation of the mdid before the metadata was fetched.
+    valid_from: u64,
+}
+
+struct Entry {
+    current: Version,
+    last_used: u64,
+}
+
+struct Inner<T: OptimizerType> {
+    entries: HashMap<T::MdId, Entry>,
+    /// versions invalidated while some snapshot may still see them, with the generation of their
+    /// invalidation
+    superseded: HashMap<T::MdId, Vec<(Version, u64)>>,
+    /// last used tick -> mdid, the first entry is the least recently used one
+    lru: BTreeMap<u64, T::MdId>,
+    tick: u64,
+    next_version: u64,
+    /// bumped by every invalidation
+    generation: u64,
+    /// the generation of the last invalidation of each mdid, and of all of them, which tells
+    /// whether an mdid was invalidated while fetched
+    invalidated: HashMap<T::MdId, u64>,
+    all_invalidated: u64,
+    /// the latest generation of the invalidations pruned from `invalidated`, which stands for
+    /// the last invalidation of the mdids no longer in it
+    pruned_invalidation: u64,
+    /// generation -> number of snapshots seeing it
+    pins: BTreeMap<u64, usize>,
+}
+
+impl<T: OptimizerType> Inner<T> {
+    fn last_invalidation(&self, md_id: &T::MdId) -> u64 {
+        let invalidated = self.invalidated.get(md_id).copied().unwrap_or(self.pruned_invalidation);
+        invalidated.max(self.all_invalidated)
+    }
+
+    fn next_version(&mut self) -> u64 {
+        let version = self.next_version;
+        self.next_version += 1;
+        version
+    }
+
+    /// Returns the version of the metadata seen at the generation, if cached.
+    fn lookup(&mut self, md_id: &T::MdId, generation: u64) -> Option<(Arc<str>, u64)> {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some(entry) = self.entries.get_mut(md_id) {
+            if entry.current.valid_from <= generation {
+                let last_used = std::mem::replace(&mut entry.last_used, tick);
+                let result = (entry.current.md.clone(), entry.current.version);
+                self.lru.remove(&last_used);
+                self.lru.insert(tick, md_id.clone());
+                return Some(result);
+            }
+        }
+        self.superseded
+            .get(md_id)?
+            .iter()
+            .find(|(version, invalidated)| version.valid_from <= generation && generation < *invalidated)
+            .map(|(version, _)| (version.md.clone(), version.version))
+    }
+
+    fn insert(&mut self, md_id: &T::MdId, md: Arc<str>, valid_from: u64, capacity: usize) -> u64 {
+        let version = self.next_version();
+        if self.entries.len() >= capacity {
+            if let Some((_, evicted)) = self.lru.pop_first() {
+                self.entries.remove(&evicted);
+            }
+        }
+        self.tick += 1;
+        let current = Version {
+            md,
+            version,
+            valid_from,
+        };
+        self.entries.insert(
+            md_id.clone(),
+            Entry {
+                current,
+                last_used: self.tick,
+            },
+        );
+        self.lru.insert(self.tick, md_id.clone());
+        version
+    }
+
+    fn supersede(&mut self, md_id: &T::MdId) {
+        if let Some(entry) = self.entries.remove(md_id) {
+            self.lru.remove(&entry.last_used);
+            let superseded = self.superseded.entry(md_id.clone()).or_default();
+            superseded.push((entry.current, self.generation));
+        }
+    }
+
+    /// Drops what no snapshot can see anymore.
+    fn prune(&mut self) {
+        // every snapshot, current or future, sees this generation or a later one
+        let oldest = self.pins.keys().next().copied().unwrap_or(self.generation);
+        self.superseded.retain(|_, superseded| {
+            superseded.retain(|(_, invalidated)| oldest < *invalidated);
+            !superseded.is_empty()
+        });
+
+        // every snapshot sees these invalidations, so metadata fetched afterwards only needs to
+        // be valid from the latest of them. A fetch racing with the pruning is taken as
+        // invalidated, and just not cached.
+        let mut pruned_invalidation = self.pruned_invalidation;
+        self.invalidated.retain(|_, invalidated| {
+            let pruned = *invalidated <= oldest;
+            if pruned {
+                pruned_invalidation = pruned_invalidation.max(*invalidated);
+            }
+            !pruned
+        });
+        self.pruned_invalidation = pruned_invalidation;
+    }
+
+    /// Returns the versions some snapshot may still see.
+    fn live_versions(&self) -> HashSet<u64> {
+        let current = self.entries.values().map(|entry| entry.current.version);
+        let superseded = self
+            .superseded
+            .values()
+            .flat_map(|superseded| superseded.iter().map(|(version, _)| version.version));
+        current.chain(superseded).collect()
+    }
+}
+
+fn uncacheable(md_id: &impl Debug, err: impl Display) -> MdError {
+    MdError::Uncacheable {
+        md_id: format!("{md_id:?}"),
+        message: err.to_string(),
+    }
+}
+
+/// The versions of metadata a thread deserialized from a cache.
+struct Decoded {
+    /// gone once the cache is dropped
+    cache: Weak<()>,
+    versions: HashMap<u64, Box<dyn Metadata>>,
+}
+
+thread_local! {
+    /// The metadata deserialized on this thread by cache id, so cache hits don't deserialize it
+    /// again.
+    static DECODED: RefCell<HashMap<u64, Decoded>> = RefCell::new(HashMap::new());
+}
+
+static NEXT_CACHE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A metadata cache shared by many optimizations, possibly on different threads, in front of
+/// another provider.
+///
+/// Every cached entry carries a version, which changes whenever the entry is fetched again
+/// after an invalidation. Once the cache holds `capacity` entries, the least recently used
+/// one is evicted. The entries are kept serialized between threads, and deserialized once by
+/// every thread retrieving them.
+///
+/// Optimizations retrieve metadata through a [`SharedMdSnapshot`], which sees the cache as of
+/// the last invalidation before it was taken: metadata invalidated afterwards is kept for it
+/// until it is dropped. Metadata it retrieves for the first time after being invalidated, and
+/// which wasn't cached before, is fetched anew. Pass the snapshot as the provider of the
+/// [`MdAccessor`] of the optimization.
+///
+/// The provider isn't called under the lock of the cache, so it may retrieve metadata through
+/// the cache itself.
+///
+/// [`MdAccessor`]: crate::metadata::MdAccessor
+pub struct SharedMdCache<T: OptimizerType> {
+    md_provider: Arc<dyn MdProvider<T> + Send + Sync>,
+    capacity: usize,
+    inner: Mutex<Inner<T>>,
+    /// identifies the metadata deserialized from the cache on every thread
+    id: u64,
+    alive: Arc<()>,
+}
+
+impl<T: OptimizerType> SharedMdCache<T> {
+    pub fn new(md_provider: Arc<dyn MdProvider<T> + Send + Sync>, capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity of metadata cache must be positive");
+        Self {
+            md_provider,
+            capacity,
+            inner: Mutex::new(Inner {
+                entries: HashMap::new(),
+                superseded: HashMap::new(),
+                lru: BTreeMap::new(),
+                tick: 0,
+                next_version: 1,
+                generation: 0,
+                invalidated: HashMap::new(),
+                all_invalidated: 0,
+                pruned_invalidation: 0,
+                pins: BTreeMap::new(),
+            }),
+            id: NEXT_CACHE_ID.fetch_add(1, Ordering::Relaxed),
+            alive: Arc::new(()),
+        }
+    }
+
+    fn inner(&self) -> MutexGuard<'_, Inner<T>> {
+        // the cache is consistent between statements, even if a panic poisoned the lock
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, md_id: &T::MdId) -> bool {
+        self.inner().entries.contains_key(md_id)
+    }
+
+    /// Returns the version of the cached entry.
+    pub fn version(&self, md_id: &T::MdId) -> Option<u64> {
+        self.inner().entries.get(md_id).map(|entry| entry.current.version)
+    }
+
+    /// Drops the cached entry, the next retrieval fetches it from the provider again. Snapshots
+    /// taken before keep seeing the dropped entry.
+    pub fn invalidate(&self, md_id: &T::MdId) -> bool {
+        let mut inner = self.inner();
+        inner.generation += 1;
+        let generation = inner.generation;
+        inner.invalidated.insert(md_id.clone(), generation);
+        let cached = inner.entries.contains_key(md_id);
+        inner.supersede(md_id);
+        inner.prune();
+        cached
+    }
+
+    pub fn invalidate_all(&self) {
+        let mut inner = self.inner();
+        inner.generation += 1;
+        inner.all_invalidated = inner.generation;
+        let md_ids = inner.entries.keys().cloned().collect::<Vec<_>>();
+        for md_id in md_ids {
+            inner.supersede(&md_id);
+        }
+        inner.prune();
+    }
+
+    /// Takes a snapshot of the cache, seeing the metadata as of now.
+    pub fn snapshot(self: &Arc<Self>) -> SharedMdSnapshot<T> {
+        let mut inner = self.inner();
+        let generation = inner.generation;
+        *inner.pins.entry(generation).or_default() += 1;
+        SharedMdSnapshot {
+            shared_cache: self.clone(),
+            generation,
+        }
+    }
+
+    /// Returns the latest metadata with its version.
+    pub fn retrieve_versioned(&self, md_id: &T::MdId) -> Result<(Box<dyn Metadata>, u64), MdError> {
+        let generation = self.inner().generation;
+        self.retrieve_at(md_id, generation)
+    }
+
+    /// Returns the latest metadata.
+    pub fn retrieve_metadata(&self, md_id: &T::MdId) -> Result<Box<dyn Metadata>, MdError> {
+        self.retrieve_versioned(md_id).map(|(md, _)| md)
+    }
+
+    /// Returns the metadata seen at the generation with its version.
+    fn retrieve_at(&self, md_id: &T::MdId, generation: u64) -> Result<(Box<dyn Metadata>, u64), MdError> {
+        let (cached, valid_from) = {
+            let mut inner = self.inner();
+            (inner.lookup(md_id, generation), inner.last_invalidation(md_id))
+        };
+        if let Some((md, version)) = cached {
+            return Ok((self.decode(md_id, &md, version)?, version));
+        }
+
+        let md = self.md_provider.retrieve_metadata(md_id)?;
+        let serialized = serde_json::to_string(md.as_ref()).map_err(|err| uncacheable(md_id, err))?;
+
+        let mut inner = self.inner();
+        if inner.last_invalidation(md_id) != valid_from {
+            // invalidated while fetched, the metadata may be stale already
+            return Ok((md, inner.next_version()));
+        }
+        if let Some(entry) = inner.entries.get(md_id) {
+            // fetched by another retrieval meanwhile
+            let (cached, version) = (entry.current.md.clone(), entry.current.version);
+            drop(inner);
+            return Ok((self.decode(md_id, &cached, version)?, version));
+        }
+        let version = inner.insert(md_id, serialized.into(), valid_from, self.capacity);
+        drop(inner);
+        self.remember(version, md.clone());
+        Ok((md, version))
+    }
+
+    /// Returns the version of the metadata, deserialized once per thread.
+    fn decode(&self, md_id: &T::MdId, md: &str, version: u64) -> Result<Box<dyn Metadata>, MdError> {
+        let decoded = DECODED.with(|decoded| {
+            let decoded = decoded.borrow();
+            decoded
+                .get(&self.id)
+                .and_then(|decoded| decoded.versions.get(&version))
+                .cloned()
+        });
+        if let Some(md) = decoded {
+            return Ok(md);
+        }
+        let md: Box<dyn Metadata> = serde_json::from_str(md).map_err(|err| uncacheable(md_id, err))?;
+        self.remember(version, md.clone());
+        Ok(md)
+    }
+
+    /// Keeps the deserialized version for the next retrievals on this thread. Versions no
+    /// snapshot can see anymore are forgotten once there are more than the capacity.
+    fn remember(&self, version: u64, md: Box<dyn Metadata>) {
+        DECODED.with(|decoded| {
+            let mut decoded = decoded.borrow_mut();
+            decoded.retain(|_, decoded| decoded.cache.strong_count() > 0);
+            let versions = &mut decoded
+                .entry(self.id)
+                .or_insert_with(|| Decoded {
+                    cache: Arc::downgrade(&self.alive),
+                    versions: HashMap::new(),
+                })
+                .versions;
+            if versions.len() >= self.capacity {
+                let live_versions = self.inner().live_versions();
+                versions.retain(|version, _| live_versions.contains(version));
+            }
+            versions.insert(version, md);
+        });
+    }
+}
+
+impl<T: OptimizerType> Drop for SharedMdCache<T> {
+    fn drop(&mut self) {
+        // other threads forget their metadata of the cache on their next retrieval
+        let _ = DECODED.try_with(|decoded| {
+            if let Ok(mut decoded) = decoded.try_borrow_mut() {
+                decoded.remove(&self.id);
+            }
+        });
+    }
+}
+
+/// The metadata of a [`SharedMdCache`] as of when the snapshot was taken.
+pub struct SharedMdSnapshot<T: OptimizerType> {
+    shared_cache: Arc<SharedMdCache<T>>,
+    generation: u64,
+}
+
+impl<T: OptimizerType> SharedMdSnapshot<T> {
+    /// Returns the metadata with its version.
+    pub fn retrieve_versioned(&self, md_id: &T::MdId) -> Result<(Box<dyn Metadata>, u64), MdError> {
+        self.shared_cache.retrieve_at(md_id, self.generation)
+    }
+}
+
+impl<T: OptimizerType> Drop for SharedMdSnapshot<T> {
+    fn drop(&mut self) {
+        let mut inner = self.shared_cache.inner();
+        if let Some(pins) = inner.pins.get_mut(&self.generation) {
+            *pins -= 1;
+            if *pins == 0 {
+                inner.pins.remove(&self.generation);
+            }
+        }
+        inner.prune();
+    }
+}
+
+impl<T: OptimizerType> MdProvider<T> for SharedMdSnapshot<T> {
+    fn retrieve_metadata(&self, md_id: &T::MdId) -> Result<Box<dyn Metadata>, MdError> {
+        self.retrieve_versioned(md_id).map(|(md, _)| md)
+    }
+
+    fn find_metadata(&self, filter: MdFilter) -> Result<Option<MdEntry<T>>, MdError> {
+        match self.shared_cache.md_provider.find_metadata(filter)? {
+            // go through the cache, so the entry gets cached and versioned
+            Some((md_id, _)) => self.retrieve_metadata(&md_id).map(|md| Some((md_id, md))),
+            None => Ok(None),
+        }
+    }
+}
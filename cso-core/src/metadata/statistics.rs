@@ -3,6 +3,17 @@ use std::fmt::Debug;
 use std::rc::Rc;
 
 pub trait Stats: Debug + AsAny {
+    /// Returns whether a group should keep `new_stats`, derived from another of its logical
+    /// plans, instead of these statistics.
     fn should_update(&self, new_stats: &Rc<dyn Stats>) -> bool;
     fn output_row_count(&self) -> u64;
+
+    /// Returns the statistics with `output_row_count` rows instead, like an override of the
+    /// optimizer options.
+    fn override_output_row_count(&self, output_row_count: u64) -> Rc<dyn Stats>;
+
+    /// Describes the statistics in explain output.
+    fn explain(&self) -> String {
+        format!("rows={}", self.output_row_count())
+    }
 }
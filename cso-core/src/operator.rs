@@ -3,7 +3,7 @@ use crate::cost::Cost;
 use crate::metadata::MdAccessor;
 use crate::metadata::Stats;
 use crate::property::PhysicalProperties;
-use crate::{ColumnRefSet, OptimizerType, Plan};
+use crate::{ColumnRefSet, LogicalPlan, OptimizerType, Plan};
 use dyn_clonable::clonable;
 use std::fmt::Debug;
 use std::rc::Rc;
@@ -14,6 +14,12 @@ pub trait LogicalOperator<T: OptimizerType>: AsAny + Debug {
     fn derive_statistics(&self, _md_accessor: &MdAccessor<T>, input_stats: &[Rc<dyn Stats>]) -> Rc<dyn Stats>;
     /// Returns the columns in the table needed for the current operator.
     fn derive_output_columns(&self, inputs: &[Plan<T>], column_set: &mut ColumnRefSet);
+    /// Returns the plan the operator stands for, like the definition of a view, which replaces it
+    /// when the memo is initialized. Columns the plan defines take ids from `next_column_id`, so
+    /// they don't collide with the other columns of the query.
+    fn expand(&self, _md_accessor: &MdAccessor<T>, _next_column_id: &mut u32) -> Option<LogicalPlan<T>> {
+        None
+    }
 }
 
 impl<O: OptimizerType> dyn LogicalOperator<O> {
@@ -31,6 +37,14 @@ pub trait PhysicalOperator<T: OptimizerType>: AsAny + Clone + Debug {
     fn required_properties(&self, input_prop: Rc<PhysicalProperties<T>>) -> Vec<Vec<Rc<PhysicalProperties<T>>>>;
     fn compute_cost(&self, _stats: Option<&dyn Stats>) -> Cost;
     fn equal(&self, other: &dyn PhysicalOperator<T>) -> bool;
+    /// Returns the columns referenced by the current operator.
+    fn derive_used_columns(&self, _column_set: &mut ColumnRefSet) {}
+    /// Returns the columns produced by the current operator, by default the columns of its inputs.
+    fn derive_output_columns(&self, input_columns: &[ColumnRefSet], column_set: &mut ColumnRefSet) {
+        input_columns
+            .iter()
+            .for_each(|columns| column_set.union_with(columns));
+    }
 }
 
 impl<T: OptimizerType> dyn PhysicalOperator<T> {
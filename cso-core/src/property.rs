@@ -58,6 +58,14 @@ impl<T: OptimizerType> PhysicalProperties<T> {
         })
     }
 
+    pub fn with_properties(properties: Vec<Box<dyn PhysicalProperty<T>>>) -> Rc<PhysicalProperties<T>> {
+        Rc::new(PhysicalProperties { properties })
+    }
+
+    pub fn properties(&self) -> &[Box<dyn PhysicalProperty<T>>] {
+        &self.properties
+    }
+
     pub fn satisfy(&self, required_prop: &PhysicalProperties<T>) -> bool {
         // all output properties should be super set of required one
 
@@ -25,9 +25,12 @@ impl<T: OptimizerType> DeriveStatsTask<T> {
             return;
         }
 
-        let stats = plan.derive_statistics(optimizer_ctx);
+        let mut stats = plan.derive_statistics(optimizer_ctx);
 
         let group = plan.group();
+        if let Some(row_count) = group.borrow().row_count_override() {
+            stats = stats.override_output_row_count(row_count);
+        }
         group.borrow_mut().update_statistics(stats);
 
         plan.set_stats_derived();
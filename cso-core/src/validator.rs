@@ -0,0 +1,107 @@
+//! Checks the invariants of an optimized physical plan:
+//! - every operator only references columns produced by its inputs,
+//! - the root delivers the required physical properties,
+//! - the inputs of every operator deliver the properties it requires from them.
+
+use crate::property::PhysicalProperties;
+use crate::{ColumnRefSet, OptimizerType, PhysicalPlan};
+use std::rc::Rc;
+
+/// Validates the plan against the properties required from its root.
+/// Returns a description of the first violated invariant.
+pub fn validate_plan<T: OptimizerType>(
+    plan: &PhysicalPlan<T>,
+    required_properties: &Rc<PhysicalProperties<T>>,
+) -> Result<(), String> {
+    validate_columns(plan)?;
+
+    let output_properties = plan.derive_output_properties();
+    if !output_properties.satisfy(required_properties) {
+        return Err(format!(
+            "{} delivers {:?}, which does not satisfy the required {:?}",
+            plan.operator().name(),
+            output_properties.properties(),
+            required_properties.properties()
+        ));
+    }
+    validate_properties(plan, required_properties)
+}
+
+/// Returns the output columns of the plan.
+fn validate_columns<T: OptimizerType>(plan: &PhysicalPlan<T>) -> Result<ColumnRefSet, String> {
+    let input_columns = plan
+        .inputs()
+        .iter()
+        .map(validate_columns)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let op = plan.operator();
+    // leaf operators read their columns from the table directly
+    if !input_columns.is_empty() {
+        let mut available_columns = ColumnRefSet::new();
+        input_columns
+            .iter()
+            .for_each(|columns| available_columns.union_with(columns));
+
+        let mut used_columns = ColumnRefSet::new();
+        op.derive_used_columns(&mut used_columns);
+        if !available_columns.is_superset(&used_columns) {
+            let missing_columns = used_columns
+                .iter()
+                .filter(|id| !available_columns.contains(*id))
+                .collect::<Vec<_>>();
+            return Err(format!(
+                "{} references columns {:?} not provided by its inputs",
+                op.name(),
+                missing_columns
+            ));
+        }
+    }
+
+    let mut output_columns = ColumnRefSet::new();
+    op.derive_output_columns(&input_columns, &mut output_columns);
+    Ok(output_columns)
+}
+
+fn validate_properties<T: OptimizerType>(
+    plan: &PhysicalPlan<T>,
+    required_properties: &Rc<PhysicalProperties<T>>,
+) -> Result<(), String> {
+    if plan.inputs().is_empty() {
+        return Ok(());
+    }
+
+    let input_properties = plan
+        .inputs()
+        .iter()
+        .map(|input| input.derive_output_properties())
+        .collect::<Vec<_>>();
+
+    // any of the alternatives required from the inputs is fine, as long as it is delivered
+    let op = plan.operator();
+    let input_required_properties = op
+        .required_properties(required_properties.clone())
+        .into_iter()
+        .find(|alternative| {
+            alternative.len() == input_properties.len()
+                && alternative
+                    .iter()
+                    .zip(&input_properties)
+                    .all(|(required, delivered)| delivered.satisfy(required))
+        })
+        .ok_or_else(|| {
+            format!(
+                "inputs of {} deliver {:?}, which does not satisfy any of its required properties",
+                op.name(),
+                input_properties
+                    .iter()
+                    .map(|properties| properties.properties())
+                    .collect::<Vec<_>>()
+            )
+        })?;
+
+    for (input, required) in plan.inputs().iter().zip(&input_required_properties) {
+        validate_properties(input, required)?;
+    }
+    Ok(())
+}
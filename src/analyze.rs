@@ -0,0 +1,301 @@
+//! Builds statistics from column values, like `ANALYZE` does.
+//!
+//! The values may be the full table or a sample of it. For a sample, counts are scaled to the
+//! number of rows of the table and the number of distinct values is estimated with the
+//! Haas-Stokes estimator used by PostgreSQL.
+//!
+//! Values much more common than the average become most common values, the histogram describes
+//! the other values.
+
+use crate::datum::Datum;
+use crate::sketch::HyperLogLog;
+use crate::statistics::{Bucket, ColumnStats, Histogram, Mcv, RelationStats};
+
+pub const DEFAULT_BUCKET_COUNT: usize = 10;
+pub const DEFAULT_MCV_COUNT: usize = 10;
+
+/// A value must be this many times more common than the average to be a most common value.
+const MCV_MIN_RATIO: f64 = 1.25;
+
+/// Collects the values of a column and builds its statistics.
+#[derive(Clone, Debug)]
+pub struct ColumnStatsBuilder {
+    col_id: usize,
+    name: String,
+    bucket_count: usize,
+    mcv_count: usize,
+    values: Vec<Datum>,
+    null_count: u64,
+}
+
+impl ColumnStatsBuilder {
+    pub fn new(col_id: usize, name: String) -> Self {
+        Self {
+            col_id,
+            name,
+            bucket_count: DEFAULT_BUCKET_COUNT,
+            mcv_count: DEFAULT_MCV_COUNT,
+            values: Vec::new(),
+            null_count: 0,
+        }
+    }
+
+    /// Sets the maximal number of histogram buckets.
+    pub fn with_bucket_count(mut self, bucket_count: usize) -> Self {
+        assert!(bucket_count > 0, "bucket count must be positive");
+        self.bucket_count = bucket_count;
+        self
+    }
+
+    /// Sets the maximal number of most common values, 0 disables them.
+    pub fn with_mcv_count(mut self, mcv_count: usize) -> Self {
+        self.mcv_count = mcv_count;
+        self
+    }
+
+    /// Adds a value, `None` stands for null.
+    pub fn add(&mut self, value: Option<Datum>) {
+        match value {
+            Some(value) if !value.is_null() => self.values.push(value),
+            _ => self.null_count += 1,
+        }
+    }
+
+    pub fn add_values<I: IntoIterator<Item = Option<Datum>>>(&mut self, values: I) {
+        values.into_iter().for_each(|value| self.add(value));
+    }
+
+    /// Returns the number of added values, including nulls.
+    pub fn row_count(&self) -> u64 {
+        self.values.len() as u64 + self.null_count
+    }
+
+    /// Builds a sketch of the distinct values, which can be merged with the sketches of the
+    /// other partitions of the table.
+    pub fn build_sketch(&self, precision: u8) -> HyperLogLog {
+        let mut sketch = HyperLogLog::new(precision);
+        self.values.iter().for_each(|value| sketch.add(value));
+        sketch
+    }
+
+    /// Builds the statistics of the added values, which are all values of the column.
+    /// Returns `None` if no value was added.
+    pub fn build(&self) -> Option<ColumnStats> {
+        self.build_from_sample(self.row_count())
+    }
+
+    /// Builds the statistics of a column of `total_rows` rows, of which the added values are
+    /// a random sample. Returns `None` if no value was added.
+    pub fn build_from_sample(&self, total_rows: u64) -> Option<ColumnStats> {
+        if self.row_count() == 0 {
+            return None;
+        }
+        if self.values.is_empty() {
+            // the bounds of a column of nulls are meaningless
+            let column_stats = ColumnStats::new(
+                self.col_id,
+                self.name.clone(),
+                Datum::Null,
+                Datum::Null,
+                total_rows,
+                None,
+            );
+            return Some(column_stats.with_ndv(0));
+        }
+
+        let mut values = self.values.clone();
+        values.sort();
+
+        let scale = total_rows as f64 / self.row_count() as f64;
+        let null_count = (self.null_count as f64 * scale).round() as u64;
+        let non_null_rows = total_rows.saturating_sub(null_count).max(1);
+
+        let sample_ndv = count_distinct(&values);
+        let ndv = estimate_ndv(&values, sample_ndv, non_null_rows);
+
+        let mcvs = self.most_common_values(&values, sample_ndv);
+        let histogram_values = values
+            .iter()
+            .filter(|value| !mcvs.iter().any(|(mcv, _)| mcv == *value))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // distribute the estimated distinct values over buckets in proportion to the sample
+        let mcv_count = mcvs.len() as u64;
+        let ndv_scale = (ndv - mcv_count) as f64 / (sample_ndv - mcv_count).max(1) as f64;
+        let buckets = equi_depth_buckets(&histogram_values, self.bucket_count)
+            .into_iter()
+            .map(|bucket| {
+                let bucket_ndv = (count_distinct(bucket) as f64 * ndv_scale).round().max(1.0) as u64;
+                let value_count = (bucket.len() as f64 * scale).round() as u64;
+                Bucket::new(
+                    bucket[0].clone(),
+                    bucket[bucket.len() - 1].clone(),
+                    bucket_ndv,
+                    value_count,
+                )
+            })
+            .collect::<Vec<_>>();
+        let histogram = (!buckets.is_empty()).then(|| Histogram::new(buckets));
+
+        let mcvs = mcvs
+            .into_iter()
+            .map(|(value, count)| Mcv::new(value, count as f64 / self.row_count() as f64))
+            .collect();
+
+        let column_stats = ColumnStats::new(
+            self.col_id,
+            self.name.clone(),
+            values[0].clone(),
+            values[values.len() - 1].clone(),
+            null_count,
+            histogram,
+        );
+        Some(column_stats.with_ndv(ndv).with_mcvs(mcvs))
+    }
+
+    /// Returns the most common values with their counts, the most common first.
+    fn most_common_values(&self, sorted_values: &[Datum], sample_ndv: u64) -> Vec<(Datum, u64)> {
+        let min_count = MCV_MIN_RATIO * sorted_values.len() as f64 / sample_ndv as f64;
+        let mut mcvs = sorted_values
+            .chunk_by(|l, r| l == r)
+            .map(|run| (run[0].clone(), run.len() as u64))
+            .filter(|(_, count)| *count > 1 && *count as f64 >= min_count)
+            .collect::<Vec<_>>();
+        mcvs.sort_by(|l, r| r.1.cmp(&l.1).then(l.0.cmp(&r.0)));
+        mcvs.truncate(self.mcv_count);
+        mcvs
+    }
+}
+
+fn count_distinct(sorted_values: &[Datum]) -> u64 {
+    if sorted_values.is_empty() {
+        return 0;
+    }
+    1 + sorted_values.windows(2).filter(|pair| pair[0] != pair[1]).count() as u64
+}
+
+/// Estimates the distinct values of `total` non-null values from the sorted sample.
+fn estimate_ndv(sorted_values: &[Datum], sample_ndv: u64, total: u64) -> u64 {
+    let n = sorted_values.len() as f64;
+    if n as u64 >= total {
+        return sample_ndv;
+    }
+
+    // values seen exactly once in the sample
+    let mut singletons = 0;
+    let mut start = 0;
+    for end in 1..=sorted_values.len() {
+        if end == sorted_values.len() || sorted_values[end] != sorted_values[start] {
+            if end - start == 1 {
+                singletons += 1;
+            }
+            start = end;
+        }
+    }
+
+    let d = sample_ndv as f64;
+    let f1 = singletons as f64;
+    let ndv = n * d / (n - f1 + f1 * n / total as f64);
+    (ndv.round() as u64).clamp(sample_ndv, total)
+}
+
+/// Splits the sorted values into at most `bucket_count` buckets holding about the same number of
+/// values. Equal values always fall into the same bucket.
+fn equi_depth_buckets(sorted_values: &[Datum], bucket_count: usize) -> Vec<&[Datum]> {
+    let depth = sorted_values.len().div_ceil(bucket_count).max(1);
+    let mut buckets = Vec::with_capacity(bucket_count);
+
+    let mut start = 0;
+    while start < sorted_values.len() {
+        let mut end = (start + depth).min(sorted_values.len());
+        while end < sorted_values.len() && sorted_values[end] == sorted_values[end - 1] {
+            end += 1;
+        }
+        buckets.push(&sorted_values[start..end]);
+        start = end;
+    }
+    buckets
+}
+
+/// Collects the rows of a table and builds the statistics of the table and its columns.
+#[derive(Clone, Debug)]
+pub struct RelationStatsBuilder {
+    name: String,
+    columns: Vec<ColumnStatsBuilder>,
+    rows: u64,
+    total_rows: Option<u64>,
+}
+
+impl RelationStatsBuilder {
+    /// Creates a builder for a table with the columns, the column id is the position of a column.
+    pub fn new(name: String, column_names: &[&str]) -> Self {
+        let columns = column_names
+            .iter()
+            .enumerate()
+            .map(|(col_id, name)| ColumnStatsBuilder::new(col_id, name.to_string()))
+            .collect();
+        Self {
+            name,
+            columns,
+            rows: 0,
+            total_rows: None,
+        }
+    }
+
+    pub fn with_bucket_count(mut self, bucket_count: usize) -> Self {
+        self.columns = self
+            .columns
+            .into_iter()
+            .map(|column| column.with_bucket_count(bucket_count))
+            .collect();
+        self
+    }
+
+    pub fn with_mcv_count(mut self, mcv_count: usize) -> Self {
+        self.columns = self
+            .columns
+            .into_iter()
+            .map(|column| column.with_mcv_count(mcv_count))
+            .collect();
+        self
+    }
+
+    /// The added rows are a sample of a table with `total_rows` rows.
+    pub fn with_total_rows(mut self, total_rows: u64) -> Self {
+        self.total_rows = Some(total_rows);
+        self
+    }
+
+    /// Adds a row with a value per column, `None` stands for null.
+    pub fn add_row(&mut self, row: &[Option<Datum>]) {
+        assert_eq!(
+            row.len(),
+            self.columns.len(),
+            "row of {} has wrong number of values",
+            self.name
+        );
+        for (column, value) in self.columns.iter_mut().zip(row) {
+            column.add(value.clone());
+        }
+        self.rows += 1;
+    }
+
+    /// Builds the relation statistics and the statistics of every column, unless no row was
+    /// added, using `col_stat_mdids[i]` as mdid of the statistics of column `i`.
+    pub fn build(&self, col_stat_mdids: &[u64]) -> (RelationStats, Vec<(u64, ColumnStats)>) {
+        assert_eq!(col_stat_mdids.len(), self.columns.len(), "expect a mdid per column");
+
+        let total_rows = self.total_rows.unwrap_or(self.rows);
+        let column_stats = self
+            .columns
+            .iter()
+            .zip(col_stat_mdids)
+            .filter_map(|(column, md_id)| Some((*md_id, column.build_from_sample(total_rows)?)))
+            .collect::<Vec<_>>();
+
+        let md_ids = column_stats.iter().map(|(md_id, _)| *md_id).collect();
+        let relation_stats = RelationStats::new(self.name.clone(), total_rows, total_rows == 0, md_ids);
+        (relation_stats, column_stats)
+    }
+}
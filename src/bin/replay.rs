@@ -0,0 +1,40 @@
+//! Replays a minidump and reports whether the optimizer still produces the captured plan.
+//!
+//! Usage: replay <minidump.json>
+
+use cso_demo::minidump::Minidump;
+use std::env;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let Some(path) = env::args().nth(1) else {
+        eprintln!("usage: replay <minidump.json>");
+        return ExitCode::from(2);
+    };
+
+    let minidump = match Minidump::read_from_file(&path) {
+        Ok(minidump) => minidump,
+        Err(err) => {
+            eprintln!("failed to read minidump {path}: {err}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let plan = match minidump.replay() {
+        Ok(plan) => plan,
+        Err(err) => {
+            eprintln!("failed to replay minidump {path}: {err}");
+            return ExitCode::from(2);
+        }
+    };
+    match minidump.diff(&plan) {
+        None => {
+            println!("replayed plan matches the captured plan");
+            ExitCode::SUCCESS
+        }
+        Some(diff) => {
+            println!("replayed plan differs from the captured plan:\n{diff}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,7 +1,285 @@
+use crate::expression::Const;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+/// The largest scale of a decimal, so that any two decimals can be compared exactly.
+pub const MAX_DECIMAL_SCALE: u8 = 18;
 
 /// Datum is the struct to represent a single value in optimizer.
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+///
+/// Datums are totally ordered: null sorts first, then booleans, numbers, strings, dates and
+/// timestamps. Numbers of different types are ordered by their values, and equal values of
+/// different types by their type. Floats are ordered as by `f64::total_cmp`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Datum {
+    Null,
+    Bool(bool),
     I32(i32),
+    I64(i64),
+    F64(f64),
+    /// `unscaled * 10^-scale`
+    Decimal {
+        unscaled: i64,
+        scale: u8,
+    },
+    String(String),
+    /// Days since 1970-01-01
+    Date(i32),
+    /// Microseconds since 1970-01-01 00:00:00 UTC
+    Timestamp(i64),
+}
+
+impl Datum {
+    pub fn decimal(unscaled: i64, scale: u8) -> Self {
+        assert!(
+            scale <= MAX_DECIMAL_SCALE,
+            "scale of decimal must be at most {MAX_DECIMAL_SCALE}"
+        );
+        Datum::Decimal { unscaled, scale }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Datum::Null)
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            Datum::I32(_) | Datum::I64(_) | Datum::F64(_) | Datum::Decimal { .. }
+        )
+    }
+
+    /// Returns the value as a float, if it is a number.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Datum::I32(value) => Some(*value as f64),
+            Datum::I64(value) => Some(*value as f64),
+            Datum::F64(value) => Some(*value),
+            Datum::Decimal { unscaled, scale } => Some(*unscaled as f64 / 10f64.powi(*scale as i32)),
+            _ => None,
+        }
+    }
+
+    /// Compares the values of two datums like `cmp`, except that numbers of different types
+    /// with the same value are equal, as they are in SQL.
+    pub fn cmp_value(&self, other: &Datum) -> Ordering {
+        if self.is_numeric() && other.is_numeric() {
+            self.cmp_numeric_value(other)
+        } else {
+            self.cmp(other)
+        }
+    }
+
+    /// Returns the distance between two numbers, dates or timestamps, in the unit of the values:
+    /// days for dates and microseconds for timestamps.
+    pub fn distance(&self, other: &Datum) -> Option<f64> {
+        if self.kind_rank() != other.kind_rank() {
+            return None;
+        }
+        Some((self.linear_position()? - other.linear_position()?).abs())
+    }
+
+    /// Returns where the value lies between `lower` and `upper`, from 0 at `lower` to 1 at
+    /// `upper`, assuming the values in between are evenly spread. Values outside of the range
+    /// are clamped to it. Returns `None` if the values can't be interpolated, like values of
+    /// different kinds.
+    ///
+    /// Strings are interpolated on their first bytes after the prefix `lower` and `upper` share.
+    pub fn interpolate(&self, lower: &Datum, upper: &Datum) -> Option<f64> {
+        let kind = self.kind_rank();
+        if lower.kind_rank() != kind || upper.kind_rank() != kind || lower > upper {
+            return None;
+        }
+        if self <= lower {
+            return Some(0.0);
+        }
+        if self >= upper {
+            return Some(1.0);
+        }
+
+        let (value, lower, upper) = match (self, lower, upper) {
+            (Datum::String(value), Datum::String(lower), Datum::String(upper)) => {
+                let prefix = common_prefix_len(lower.as_bytes(), upper.as_bytes());
+                (
+                    string_position(&value.as_bytes()[prefix..]),
+                    string_position(&lower.as_bytes()[prefix..]),
+                    string_position(&upper.as_bytes()[prefix..]),
+                )
+            }
+            _ => (
+                self.linear_position()?,
+                lower.linear_position()?,
+                upper.linear_position()?,
+            ),
+        };
+        if upper <= lower {
+            // the range is too narrow to tell the values apart
+            return Some(0.5);
+        }
+        Some(((value - lower) / (upper - lower)).clamp(0.0, 1.0))
+    }
+
+    /// Returns the position of the value on a line of its kind.
+    fn linear_position(&self) -> Option<f64> {
+        match self {
+            Datum::Bool(value) => Some(*value as u8 as f64),
+            Datum::Date(value) => Some(*value as f64),
+            Datum::Timestamp(value) => Some(*value as f64),
+            _ => self.as_f64().filter(|value| value.is_finite()),
+        }
+    }
+
+    /// Position of the kind of the value in the order of datums.
+    fn kind_rank(&self) -> u8 {
+        match self {
+            Datum::Null => 0,
+            Datum::Bool(_) => 1,
+            Datum::I32(_) | Datum::I64(_) | Datum::F64(_) | Datum::Decimal { .. } => 2,
+            Datum::String(_) => 3,
+            Datum::Date(_) => 4,
+            Datum::Timestamp(_) => 5,
+        }
+    }
+
+    /// Position of the type of the value among the types of its kind.
+    fn type_rank(&self) -> u8 {
+        match self {
+            Datum::I32(_) => 0,
+            Datum::I64(_) => 1,
+            Datum::Decimal { .. } => 2,
+            Datum::F64(_) => 3,
+            _ => 0,
+        }
+    }
+
+    /// Returns the exact value of an integer or decimal as `(unscaled, scale)`.
+    fn exact_numeric(&self) -> Option<(i128, u8)> {
+        match self {
+            Datum::I32(value) => Some((*value as i128, 0)),
+            Datum::I64(value) => Some((*value as i128, 0)),
+            Datum::Decimal { unscaled, scale } if *scale <= MAX_DECIMAL_SCALE => Some((*unscaled as i128, *scale)),
+            _ => None,
+        }
+    }
+
+    fn cmp_numeric_value(&self, other: &Datum) -> Ordering {
+        match (self.exact_numeric(), other.exact_numeric()) {
+            (Some((l, l_scale)), Some((r, r_scale))) => {
+                let l = l * 10i128.pow(r_scale.saturating_sub(l_scale) as u32);
+                let r = r * 10i128.pow(l_scale.saturating_sub(r_scale) as u32);
+                l.cmp(&r)
+            }
+            _ => self.as_f64().unwrap().total_cmp(&other.as_f64().unwrap()),
+        }
+    }
+
+    fn cmp_numeric(&self, other: &Datum) -> Ordering {
+        self.cmp_numeric_value(other)
+            .then_with(|| self.type_rank().cmp(&other.type_rank()))
+            .then_with(|| match (self, other) {
+                (
+                    Datum::Decimal {
+                        unscaled: l_unscaled,
+                        scale: l_scale,
+                    },
+                    Datum::Decimal {
+                        unscaled: r_unscaled,
+                        scale: r_scale,
+                    },
+                ) => l_scale.cmp(r_scale).then(l_unscaled.cmp(r_unscaled)),
+                _ => Ordering::Equal,
+            })
+    }
+}
+
+/// Number of bytes of a string used to interpolate it.
+const STRING_POSITION_BYTES: usize = 8;
+
+fn common_prefix_len(l: &[u8], r: &[u8]) -> usize {
+    l.iter().zip(r).take_while(|(l, r)| l == r).count()
+}
+
+/// Maps the first bytes of a string to a position in [0, 1), preserving their order.
+fn string_position(bytes: &[u8]) -> f64 {
+    bytes
+        .iter()
+        .take(STRING_POSITION_BYTES)
+        .rev()
+        .fold(0.0, |position, byte| (position + *byte as f64) / 256.0)
+}
+
+impl Ord for Datum {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Datum::Null, Datum::Null) => Ordering::Equal,
+            (Datum::Bool(l), Datum::Bool(r)) => l.cmp(r),
+            (Datum::String(l), Datum::String(r)) => l.cmp(r),
+            (Datum::Date(l), Datum::Date(r)) => l.cmp(r),
+            (Datum::Timestamp(l), Datum::Timestamp(r)) => l.cmp(r),
+            (l, r) if l.is_numeric() && r.is_numeric() => l.cmp_numeric(r),
+            (l, r) => l.kind_rank().cmp(&r.kind_rank()),
+        }
+    }
+}
+
+impl PartialOrd for Datum {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Datum {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Datum {}
+
+impl Hash for Datum {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Datum::Null => {}
+            Datum::Bool(value) => value.hash(state),
+            Datum::I32(value) => value.hash(state),
+            Datum::I64(value) => value.hash(state),
+            Datum::F64(value) => value.to_bits().hash(state),
+            Datum::Decimal { unscaled, scale } => (unscaled, scale).hash(state),
+            Datum::String(value) => value.hash(state),
+            Datum::Date(value) => value.hash(state),
+            Datum::Timestamp(value) => value.hash(state),
+        }
+    }
+}
+
+impl From<Const> for Datum {
+    fn from(value: Const) -> Self {
+        match value {
+            Const::Int32(value) => Datum::I32(value),
+            Const::Int64(value) => Datum::I64(value),
+            Const::Str(value) => Datum::String(value),
+        }
+    }
+}
+
+impl From<&Const> for Datum {
+    fn from(value: &Const) -> Self {
+        Datum::from(value.clone())
+    }
+}
+
+impl TryFrom<Datum> for Const {
+    /// The datum without a constant of its type.
+    type Error = Datum;
+
+    fn try_from(value: Datum) -> Result<Self, Self::Error> {
+        match value {
+            Datum::I32(value) => Ok(Const::Int32(value)),
+            Datum::I64(value) => Ok(Const::Int64(value)),
+            Datum::String(value) => Ok(Const::Str(value)),
+            value => Err(value),
+        }
+    }
 }
@@ -0,0 +1,185 @@
+use crate::dsl::Catalog;
+use crate::expression::{
+    And, ColumnVar, Const, Equal, GreaterThan, GreaterThanEqual, InList, IsNotNull, IsNull, LessThan, LessThanEqual,
+    Not, NotEqual, Or,
+};
+use crate::operator::logical_scan::TableDesc;
+use crate::operator::physical_filter::PhysicalFilter;
+use crate::operator::physical_index_scan::PhysicalIndexScan;
+use crate::operator::physical_partition_scan::PhysicalPartitionScan;
+use crate::operator::physical_project::PhysicalProject;
+use crate::operator::physical_scan::PhysicalScan;
+use crate::operator::physical_sort::{OrderSpec, PhysicalSort};
+use crate::operator::PhysicalOperator;
+use crate::PhysicalPlan;
+use cso_core::expression::ScalarExpression;
+use std::rc::Rc;
+
+pub fn format_physical_plan(plan: &PhysicalPlan, catalog: &Catalog) -> String {
+    let mut lines = vec![];
+    plan_lines(plan, catalog, &mut lines);
+    lines.join("\n")
+}
+
+fn plan_lines(plan: &PhysicalPlan, catalog: &Catalog, lines: &mut Vec<String>) {
+    lines.push(format_physical_operator(plan.operator().as_ref(), catalog));
+
+    match plan.inputs() {
+        [] => {}
+        [input] => {
+            let first = lines.len();
+            plan_lines(input, catalog, lines);
+            lines[first] = format!("<- {}", lines[first]);
+        }
+        inputs => {
+            lines.push("<- (".to_string());
+            for (i, input) in inputs.iter().enumerate() {
+                let mut input_lines = vec![];
+                plan_lines(input, catalog, &mut input_lines);
+                if i + 1 < inputs.len() {
+                    input_lines.last_mut().unwrap().push(',');
+                }
+                lines.extend(input_lines.into_iter().map(|line| format!("  {line}")));
+            }
+            lines.push(")".to_string());
+        }
+    }
+}
+
+fn format_physical_operator(op: &PhysicalOperator, catalog: &Catalog) -> String {
+    if let Some(scan) = op.downcast_ref::<PhysicalScan>() {
+        format!(
+            "Scan[{}: {}]",
+            table_name(scan.table_desc(), catalog),
+            format_columns(scan.output_columns())
+        )
+    } else if let Some(index_scan) = op.downcast_ref::<PhysicalIndexScan>() {
+        format!(
+            "IndexScan[{}.{}: {}; {}]",
+            table_name(index_scan.table_desc(), catalog),
+            index_scan.index_desc().name(),
+            format_columns(index_scan.output_columns()),
+            format_expression(index_scan.predicate().as_ref())
+        )
+    } else if let Some(partition_scan) = op.downcast_ref::<PhysicalPartitionScan>() {
+        let partitions = partition_scan.partitions().iter().map(|partition| partition.name());
+        format!(
+            "PartitionScan[{}: {}; {}]",
+            table_name(partition_scan.table_desc(), catalog),
+            format_columns(partition_scan.output_columns()),
+            partitions.collect::<Vec<_>>().join(", ")
+        )
+    } else if let Some(filter) = op.downcast_ref::<PhysicalFilter>() {
+        format!("Filter[{}]", format_expression(filter.predicate()))
+    } else if let Some(project) = op.downcast_ref::<PhysicalProject>() {
+        format!("Project[{}]", format_expressions(project.project()))
+    } else if let Some(sort) = op.downcast_ref::<PhysicalSort>() {
+        format!("Sort[{}]", format_order_spec(sort.order_spec()))
+    } else {
+        format!("{:?}", op)
+    }
+}
+
+fn table_name(table_desc: &TableDesc, catalog: &Catalog) -> String {
+    match catalog.table_name(table_desc.md_id()) {
+        Some(name) => name.to_string(),
+        None => table_desc.md_id().to_string(),
+    }
+}
+
+fn format_columns(columns: &[ColumnVar]) -> String {
+    columns
+        .iter()
+        .map(|column| format!("c{}", column.id()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_order_spec(order_spec: &OrderSpec) -> String {
+    order_spec
+        .order_desc
+        .iter()
+        .map(|ordering| {
+            let mut text = format!("c{}", ordering.key.id());
+            if !ordering.ascending {
+                text.push_str(" desc");
+            }
+            if !ordering.nulls_first {
+                text.push_str(" nulls last");
+            }
+            text
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_expressions(exprs: &[Rc<dyn ScalarExpression>]) -> String {
+    exprs
+        .iter()
+        .map(|expr| format_expression(expr.as_ref()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn format_expression(expr: &dyn ScalarExpression) -> String {
+    if let Some(column) = expr.downcast_ref::<ColumnVar>() {
+        format!("c{}", column.id())
+    } else if let Some(value) = expr.downcast_ref::<Const>() {
+        match value {
+            Const::Int32(value) => value.to_string(),
+            Const::Int64(value) => value.to_string(),
+            Const::Str(value) => format!("'{}'", value.replace('\'', "''")),
+        }
+    } else if let Some(cmp) = expr.downcast_ref::<Equal>() {
+        format_comparison(cmp.left(), "=", cmp.right())
+    } else if let Some(cmp) = expr.downcast_ref::<NotEqual>() {
+        format_comparison(cmp.left(), "!=", cmp.right())
+    } else if let Some(cmp) = expr.downcast_ref::<LessThan>() {
+        format_comparison(cmp.left(), "<", cmp.right())
+    } else if let Some(cmp) = expr.downcast_ref::<LessThanEqual>() {
+        format_comparison(cmp.left(), "<=", cmp.right())
+    } else if let Some(cmp) = expr.downcast_ref::<GreaterThan>() {
+        format_comparison(cmp.left(), ">", cmp.right())
+    } else if let Some(cmp) = expr.downcast_ref::<GreaterThanEqual>() {
+        format_comparison(cmp.left(), ">=", cmp.right())
+    } else if let Some(and) = expr.downcast_ref::<And>() {
+        format!("And({})", format_expressions(and.expressions()))
+    } else if let Some(or) = expr.downcast_ref::<Or>() {
+        let args = or.expressions().iter().map(|expr| format_expression(expr.as_ref()));
+        format!("Or({})", args.collect::<Vec<_>>().join(", "))
+    } else if let Some(not) = expr.downcast_ref::<Not>() {
+        format!("Not({})", format_expression(not.expression()))
+    } else if let Some(is_null) = expr.downcast_ref::<IsNull>() {
+        format!("IsNull({})", format_expression(is_null.inner()))
+    } else if let Some(is_not_null) = expr.downcast_ref::<IsNotNull>() {
+        format!("IsNotNull({})", format_expression(is_not_null.inner()))
+    } else if let Some(in_list) = expr.downcast_ref::<InList>() {
+        let args = std::iter::once(in_list.expr())
+            .chain(in_list.list().iter().map(|expr| expr.as_ref()))
+            .map(format_expression);
+        format!("In({})", args.collect::<Vec<_>>().join(", "))
+    } else {
+        format!("{:?}", expr)
+    }
+}
+
+fn format_comparison(left: &dyn ScalarExpression, op: &str, right: &dyn ScalarExpression) -> String {
+    let operand = |expr: &dyn ScalarExpression| {
+        let text = format_expression(expr);
+        if is_comparison(expr) {
+            format!("({text})")
+        } else {
+            text
+        }
+    };
+    format!("{} {} {}", operand(left), op, operand(right))
+}
+
+fn is_comparison(expr: &dyn ScalarExpression) -> bool {
+    expr.downcast_ref::<Equal>().is_some()
+        || expr.downcast_ref::<NotEqual>().is_some()
+        || expr.downcast_ref::<LessThan>().is_some()
+        || expr.downcast_ref::<LessThanEqual>().is_some()
+        || expr.downcast_ref::<GreaterThan>().is_some()
+        || expr.downcast_ref::<GreaterThanEqual>().is_some()
+}
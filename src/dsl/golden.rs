@@ -0,0 +1,150 @@
+//! Golden file tests. Every case optimizes a plan written in the plan DSL and checks the result
+//! against the expected physical plan:
+//!
+//! ```text
+//! # can completely cover filter
+//! # sql: select c2, c3 from t1 where c1 is null order by c1;
+//! plan: Project[c1, c2] <- Filter[IsNull(c0)] <- Scan[t1: c0, c1, c2]
+//! order: c0
+//! expected:
+//! Project[c1, c2]
+//! <- IndexScan[t1.IDX_1: c0, c1, c2; And(IsNull(c0))]
+//! ```
+//!
+//! A case starts at its `plan:` line and ends at a blank line or the next case, lines starting
+//! with `#` are comments and `order` may be omitted when no order is required. The expected plan
+//! may span several lines, one operator per line.
+
+use crate::dsl::{format_physical_plan, match_physical_plan, parse_logical_plan, parse_required_properties, Catalog};
+use crate::metadata::{CachedMdProvider, MdAccessor, MdCache};
+use crate::rule::create_rule_set;
+use crate::{Optimizer, Options};
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    Plan,
+    Order,
+    Expected,
+}
+
+#[derive(Default)]
+struct Case {
+    line: usize,
+    plan: String,
+    order: String,
+    expected: String,
+}
+
+fn parse_cases(content: &str) -> Result<Vec<Case>, String> {
+    let mut cases = vec![];
+    let mut case: Option<Case> = None;
+    let mut section = Section::Plan;
+
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.is_empty() {
+            cases.extend(case.take());
+            continue;
+        }
+
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) if matches!(key, "plan" | "order" | "expected") => (Some(key), value),
+            _ => (None, line),
+        };
+
+        match key {
+            Some("plan") => {
+                cases.extend(case.take());
+                case = Some(Case {
+                    line: line_number,
+                    ..Case::default()
+                });
+                section = Section::Plan;
+            }
+            Some("order") => section = Section::Order,
+            Some(_) => section = Section::Expected,
+            None => {}
+        }
+
+        let Some(case) = case.as_mut() else {
+            return Err(format!("line {line_number}: expected 'plan:'"));
+        };
+        let text = match section {
+            Section::Plan => &mut case.plan,
+            Section::Order => &mut case.order,
+            Section::Expected => &mut case.expected,
+        };
+        text.push_str(value.trim());
+        text.push('\n');
+    }
+    cases.extend(case);
+
+    Ok(cases)
+}
+
+/// Runs every case in the golden file against the metadata in `md_cache`.
+/// Returns the diffs of all failed cases.
+pub fn run_golden_file<P: AsRef<Path>>(path: P, md_cache: &MdCache) -> Result<(), String> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let cases = parse_cases(&content).map_err(|err| format!("{}:{err}", path.display()))?;
+    let catalog = Catalog::from_md_cache(md_cache);
+
+    let mut failures = vec![];
+    for case in cases {
+        if let Err(err) = run_case(&case, md_cache, &catalog) {
+            failures.push(format!("{}:{}: {}\n{err}", path.display(), case.line, case.plan.trim()));
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("\n"))
+    }
+}
+
+/// Runs every `.test` file in the directory, see [`run_golden_file`].
+pub fn run_golden_dir<P: AsRef<Path>>(dir: P, md_cache: &MdCache) -> Result<(), String> {
+    let dir = dir.as_ref();
+    let entries = fs::read_dir(dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "test"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let failures = paths
+        .iter()
+        .filter_map(|path| run_golden_file(path, md_cache).err())
+        .collect::<Vec<_>>();
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("\n"))
+    }
+}
+
+fn run_case(case: &Case, md_cache: &MdCache, catalog: &Catalog) -> Result<(), String> {
+    let plan = parse_logical_plan(&case.plan, catalog).map_err(|err| format!("invalid plan: {err}"))?;
+    let required_properties = parse_required_properties(&case.order).map_err(|err| format!("invalid order: {err}"))?;
+
+    let md_provider = Rc::new(CachedMdProvider::new(md_cache.clone()));
+    let md_accessor = MdAccessor::new(md_provider);
+    let mut optimizer = Optimizer::new(Options::default());
+    let physical_plan = optimizer.optimize(plan, required_properties, md_accessor, create_rule_set());
+
+    match_physical_plan(&physical_plan, &case.expected, catalog).map_err(|diff| {
+        let actual = format_physical_plan(&physical_plan, catalog);
+        format!("{diff}actual:\n{actual}\n")
+    })
+}
@@ -0,0 +1,152 @@
+//! A compact textual format for plans, mainly used to write tests.
+//!
+//! A plan is written top-down, and the inputs of an operator follow `<-`:
+//!
+//! ```text
+//! Project[c1, c2] <- Filter[IsNull(c0)] <- Scan[t1: c0, c1, c2]
+//! ```
+//!
+//! Operators with several inputs list them in parentheses: `Op[..] <- (Scan[t1: c0], Scan[t2: c1])`.
+//!
+//! Logical operators:
+//! - `Scan[table: columns]`
+//! - `Filter[predicate]`
+//! - `Project[expressions]`
+//! - `View[view: columns]`, returning the output columns of the view as `columns`
+//!
+//! Physical operators additionally include:
+//! - `IndexScan[table.index: columns; predicate]`
+//! - `PartitionScan[table: columns; partitions]`
+//! - `Sort[orderings]`, e.g. `Sort[c0, c1 desc nulls last]`
+//!
+//! Columns are written as `c<id>`, and expressions as `IsNull(c0)`, `IsNotNull(c0)`, `And(..)`,
+//! `Or(..)`, `Not(..)`, comparisons like `c0 <= 10`, integers and quoted strings (`'abc'`).
+
+mod formatter;
+pub mod golden;
+mod parser;
+
+use crate::metadata::MdCache;
+use crate::property::PhysicalProperties;
+use crate::statistics::{RelationMetadata, ViewMetadata};
+use crate::util::diff_lines;
+use crate::{LogicalPlan, PhysicalPlan};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+
+/// Maps table and view names used in the plan text to their mdids.
+#[derive(Clone, Debug, Default)]
+pub struct Catalog {
+    tables: HashMap<String, u64>,
+    views: HashMap<String, u64>,
+}
+
+impl Catalog {
+    pub fn new() -> Self {
+        Self {
+            tables: HashMap::new(),
+            views: HashMap::new(),
+        }
+    }
+
+    /// Creates a catalog of every relation and view in the metadata cache.
+    pub fn from_md_cache(md_cache: &MdCache) -> Self {
+        let mut catalog = Catalog::new();
+        for (md_id, md) in md_cache.iter() {
+            if let Some(relation_md) = md.downcast_ref::<RelationMetadata>() {
+                catalog.add_table(relation_md.name(), *md_id);
+            } else if let Some(view_md) = md.downcast_ref::<ViewMetadata>() {
+                catalog.add_view(view_md.name(), *md_id);
+            }
+        }
+        catalog
+    }
+
+    pub fn add_table(&mut self, name: &str, md_id: u64) {
+        self.tables.insert(name.to_string(), md_id);
+    }
+
+    pub fn add_view(&mut self, name: &str, md_id: u64) {
+        self.views.insert(name.to_string(), md_id);
+    }
+
+    pub fn view_md_id(&self, name: &str) -> Option<u64> {
+        self.views.get(name).copied()
+    }
+
+    pub fn table_md_id(&self, name: &str) -> Option<u64> {
+        self.tables.get(name).copied()
+    }
+
+    pub fn table_name(&self, md_id: u64) -> Option<&str> {
+        self.tables
+            .iter()
+            .find(|(_, id)| **id == md_id)
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    message: String,
+    position: usize,
+}
+
+impl ParseError {
+    fn new(message: String, position: usize) -> Self {
+        Self { message, position }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Byte offset in the input where the error was detected.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub fn parse_logical_plan(text: &str, catalog: &Catalog) -> Result<LogicalPlan, ParseError> {
+    parser::Parser::new(text, catalog)?.parse_logical_plan()
+}
+
+/// Parses a sort order like `c0, c1 desc`. An empty text requires no properties.
+pub fn parse_required_properties(text: &str) -> Result<Rc<PhysicalProperties>, ParseError> {
+    parser::Parser::new(text, &Catalog::new())?.parse_required_properties()
+}
+
+/// Formats the plan with one operator per line.
+pub fn format_physical_plan(plan: &PhysicalPlan, catalog: &Catalog) -> String {
+    formatter::format_physical_plan(plan, catalog)
+}
+
+/// Checks `plan` against the expected plan text. Whitespace is not significant.
+/// On mismatch, returns a diff between the expected and the actual plan.
+pub fn match_physical_plan(plan: &PhysicalPlan, expected: &str, catalog: &Catalog) -> Result<(), String> {
+    let actual = format_physical_plan(plan, catalog);
+    let expected_tokens = parser::tokenize(expected).map_err(|err| format!("invalid expected plan: {err}"))?;
+    let actual_tokens = parser::tokenize(&actual).expect("formatted plan should be tokenizable");
+
+    let same_tokens = expected_tokens.len() == actual_tokens.len()
+        && expected_tokens
+            .iter()
+            .zip(&actual_tokens)
+            .all(|((expected, _), (actual, _))| expected == actual);
+    if same_tokens {
+        return Ok(());
+    }
+
+    let expected = expected.lines().map(str::trim).collect::<Vec<_>>().join("\n");
+    let actual = actual.lines().map(str::trim).collect::<Vec<_>>().join("\n");
+    Err(diff_lines(expected.trim(), &actual).unwrap_or_default())
+}
@@ -0,0 +1,383 @@
+use crate::dsl::{Catalog, ParseError};
+use crate::expression::{
+    And, ColumnVar, Const, Equal, GreaterThan, GreaterThanEqual, InList, IsNotNull, IsNull, LessThan, LessThanEqual,
+    Not, NotEqual, Or,
+};
+use crate::operator::logical_filter::LogicalFilter;
+use crate::operator::logical_project::LogicalProject;
+use crate::operator::logical_scan::{LogicalScan, TableDesc};
+use crate::operator::logical_view_scan::LogicalViewScan;
+use crate::operator::physical_sort::{OrderSpec, Ordering};
+use crate::operator::LogicalOperator;
+use crate::property::sort_property::SortProperty;
+use crate::property::PhysicalProperties;
+use crate::LogicalPlan;
+use cso_core::expression::ScalarExpression;
+use std::rc::Rc;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    Ident(String),
+    Int(i64),
+    Str(String),
+    LBracket,
+    RBracket,
+    LParen,
+    RParen,
+    Comma,
+    Colon,
+    Semicolon,
+    Dot,
+    Arrow,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+/// Splits the text into tokens, each paired with its byte offset.
+pub fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let c = bytes[pos] as char;
+        let start = pos;
+        let next = bytes.get(pos + 1).map(|b| *b as char);
+
+        let token = match c {
+            c if c.is_ascii_whitespace() => {
+                pos += 1;
+                continue;
+            }
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            ',' => Token::Comma,
+            ':' => Token::Colon,
+            ';' => Token::Semicolon,
+            '.' => Token::Dot,
+            '=' => Token::Eq,
+            '!' if next == Some('=') => Token::NotEq,
+            // an arrow follows an operator, `c0<-1` compares c0 with -1
+            '<' if next == Some('-') && matches!(tokens.last(), Some((Token::RBracket, _))) => Token::Arrow,
+            '<' if next == Some('=') => Token::LtEq,
+            '<' if next == Some('>') => Token::NotEq,
+            '<' => Token::Lt,
+            '>' if next == Some('=') => Token::GtEq,
+            '>' => Token::Gt,
+            '\'' => {
+                let mut value = String::new();
+                pos += 1;
+                loop {
+                    match bytes.get(pos) {
+                        None => return Err(ParseError::new("unterminated string".to_string(), start)),
+                        Some(b'\'') if bytes.get(pos + 1) == Some(&b'\'') => {
+                            value.push('\'');
+                            pos += 2;
+                        }
+                        Some(b'\'') => break,
+                        Some(_) => {
+                            let ch = text[pos..].chars().next().unwrap();
+                            value.push(ch);
+                            pos += ch.len_utf8();
+                        }
+                    }
+                }
+                tokens.push((Token::Str(value), start));
+                pos += 1;
+                continue;
+            }
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                pos += 1;
+                while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+                    pos += 1;
+                }
+                let value = text[start..pos]
+                    .parse::<i64>()
+                    .map_err(|err| ParseError::new(format!("invalid integer: {err}"), start))?;
+                tokens.push((Token::Int(value), start));
+                continue;
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                while pos < bytes.len() && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_') {
+                    pos += 1;
+                }
+                tokens.push((Token::Ident(text[start..pos].to_string()), start));
+                continue;
+            }
+            c => return Err(ParseError::new(format!("unexpected character '{c}'"), start)),
+        };
+
+        pos += match token {
+            Token::NotEq | Token::Arrow | Token::LtEq | Token::GtEq => 2,
+            _ => 1,
+        };
+        tokens.push((token, start));
+    }
+
+    Ok(tokens)
+}
+
+type MakeComparison = fn(Box<dyn ScalarExpression>, Box<dyn ScalarExpression>) -> Box<dyn ScalarExpression>;
+
+pub struct Parser<'a> {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    end: usize,
+    catalog: &'a Catalog,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(text: &str, catalog: &'a Catalog) -> Result<Self, ParseError> {
+        Ok(Self {
+            tokens: tokenize(text)?,
+            pos: 0,
+            end: text.len(),
+            catalog,
+        })
+    }
+
+    pub fn parse_logical_plan(mut self) -> Result<LogicalPlan, ParseError> {
+        let plan = self.logical_plan()?;
+        self.expect_end()?;
+        Ok(plan)
+    }
+
+    pub fn parse_required_properties(mut self) -> Result<Rc<PhysicalProperties>, ParseError> {
+        if self.peek().is_none() {
+            return Ok(Rc::new(PhysicalProperties::new()));
+        }
+        let order_desc = self.comma_separated(|parser| parser.ordering())?;
+        self.expect_end()?;
+        let sort_property = SortProperty::with_order(OrderSpec { order_desc });
+        Ok(PhysicalProperties::with_property(Box::new(sort_property)))
+    }
+
+    fn logical_plan(&mut self) -> Result<LogicalPlan, ParseError> {
+        let op = self.logical_operator()?;
+
+        let mut inputs = vec![];
+        if self.eat(&Token::Arrow) {
+            if self.eat(&Token::LParen) {
+                inputs = self.comma_separated(|parser| parser.logical_plan())?;
+                self.expect(&Token::RParen)?;
+            } else {
+                inputs.push(self.logical_plan()?);
+            }
+        }
+
+        Ok(LogicalPlan::new(op, inputs, vec![]))
+    }
+
+    fn logical_operator(&mut self) -> Result<Rc<LogicalOperator>, ParseError> {
+        let position = self.position();
+        let name = self.ident()?;
+        self.expect(&Token::LBracket)?;
+
+        let op: Rc<LogicalOperator> = match name.as_str() {
+            "Scan" => {
+                let table_position = self.position();
+                let table = self.ident()?;
+                let md_id = self
+                    .catalog
+                    .table_md_id(&table)
+                    .ok_or_else(|| ParseError::new(format!("unknown table '{table}'"), table_position))?;
+                self.expect(&Token::Colon)?;
+                let output_columns = self.comma_separated(|parser| parser.column())?;
+                Rc::new(LogicalScan::new(TableDesc::new(md_id), output_columns))
+            }
+            "View" => {
+                let view_position = self.position();
+                let view = self.ident()?;
+                let md_id = self
+                    .catalog
+                    .view_md_id(&view)
+                    .ok_or_else(|| ParseError::new(format!("unknown view '{view}'"), view_position))?;
+                self.expect(&Token::Colon)?;
+                let output_columns = self.comma_separated(|parser| parser.column())?;
+                Rc::new(LogicalViewScan::new(md_id, output_columns))
+            }
+            "Filter" => Rc::new(LogicalFilter::new(self.boolean_expression()?.into())),
+            "Project" => {
+                let project = self.comma_separated(|parser| parser.expression().map(Rc::from))?;
+                Rc::new(LogicalProject::new(project))
+            }
+            _ => return Err(ParseError::new(format!("unknown logical operator '{name}'"), position)),
+        };
+
+        self.expect(&Token::RBracket)?;
+        Ok(op)
+    }
+
+    fn ordering(&mut self) -> Result<Ordering, ParseError> {
+        let key = self.column()?;
+        let mut ordering = Ordering::new(key.id());
+
+        loop {
+            let position = self.position();
+            match self.peek() {
+                Some(Token::Ident(word)) if word == "asc" => ordering.ascending = true,
+                Some(Token::Ident(word)) if word == "desc" => ordering.ascending = false,
+                Some(Token::Ident(word)) if word == "nulls" => {
+                    self.pos += 1;
+                    match self.ident()?.as_str() {
+                        "first" => ordering.nulls_first = true,
+                        "last" => ordering.nulls_first = false,
+                        _ => return Err(ParseError::new("expected 'first' or 'last'".to_string(), position)),
+                    }
+                    continue;
+                }
+                _ => break,
+            }
+            self.pos += 1;
+        }
+
+        Ok(ordering)
+    }
+
+    fn expression(&mut self) -> Result<Box<dyn ScalarExpression>, ParseError> {
+        let left = self.primary()?;
+
+        let make: MakeComparison = match self.peek() {
+            Some(Token::Eq) => |l, r| Box::new(Equal::new(l, r)),
+            Some(Token::NotEq) => |l, r| Box::new(NotEqual::new(l, r)),
+            Some(Token::Lt) => |l, r| Box::new(LessThan::new(l, r)),
+            Some(Token::LtEq) => |l, r| Box::new(LessThanEqual::new(l, r)),
+            Some(Token::Gt) => |l, r| Box::new(GreaterThan::new(l, r)),
+            Some(Token::GtEq) => |l, r| Box::new(GreaterThanEqual::new(l, r)),
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+
+        let right = self.primary()?;
+        Ok(make(left, right))
+    }
+
+    fn primary(&mut self) -> Result<Box<dyn ScalarExpression>, ParseError> {
+        let position = self.position();
+        match self.next() {
+            Some(Token::Int(value)) => Ok(match i32::try_from(value) {
+                Ok(value) => Box::new(Const::Int32(value)),
+                Err(_) => Box::new(Const::Int64(value)),
+            }),
+            Some(Token::Str(value)) => Ok(Box::new(Const::Str(value))),
+            Some(Token::LParen) => {
+                let expr = self.expression()?;
+                self.expect(&Token::RParen)?;
+                Ok(expr)
+            }
+            Some(Token::Ident(name)) => {
+                if let Some(column) = parse_column(&name) {
+                    return Ok(Box::new(column));
+                }
+
+                self.expect(&Token::LParen)?;
+                let expr: Box<dyn ScalarExpression> = match name.as_str() {
+                    "And" => {
+                        let args = self.comma_separated(|parser| parser.boolean_expression())?;
+                        Box::new(And::new(args.into_iter().map(Rc::from).collect()))
+                    }
+                    "Or" => Box::new(Or::new(self.comma_separated(|parser| parser.boolean_expression())?)),
+                    "Not" => Box::new(Not::new(self.boolean_expression()?)),
+                    "IsNull" => Box::new(IsNull::new(self.expression()?)),
+                    "IsNotNull" => Box::new(IsNotNull::new(self.expression()?)),
+                    "In" => {
+                        let expr = self.expression()?;
+                        self.expect(&Token::Comma)?;
+                        Box::new(InList::new(expr, self.comma_separated(|parser| parser.expression())?))
+                    }
+                    _ => return Err(ParseError::new(format!("unknown function '{name}'"), position)),
+                };
+                self.expect(&Token::RParen)?;
+                Ok(expr)
+            }
+            _ => Err(ParseError::new("expected expression".to_string(), position)),
+        }
+    }
+
+    fn boolean_expression(&mut self) -> Result<Box<dyn ScalarExpression>, ParseError> {
+        let position = self.position();
+        let expr = self.expression()?;
+        if !expr.is_boolean_expression() {
+            return Err(ParseError::new("expected boolean expression".to_string(), position));
+        }
+        Ok(expr)
+    }
+
+    fn column(&mut self) -> Result<ColumnVar, ParseError> {
+        let position = self.position();
+        let name = self.ident()?;
+        parse_column(&name).ok_or_else(|| ParseError::new(format!("expected column, found '{name}'"), position))
+    }
+
+    fn comma_separated<T>(
+        &mut self,
+        mut parse: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut items = vec![parse(self)?];
+        while self.eat(&Token::Comma) {
+            items.push(parse(self)?);
+        }
+        Ok(items)
+    }
+
+    fn ident(&mut self) -> Result<String, ParseError> {
+        let position = self.position();
+        match self.next() {
+            Some(Token::Ident(name)) => Ok(name),
+            _ => Err(ParseError::new("expected identifier".to_string(), position)),
+        }
+    }
+
+    fn expect(&mut self, token: &Token) -> Result<(), ParseError> {
+        let position = self.position();
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(ParseError::new(format!("expected {token:?}"), position))
+        }
+    }
+
+    fn expect_end(&self) -> Result<(), ParseError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(token) => Err(ParseError::new(format!("unexpected {token:?}"), self.position())),
+        }
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |(_, position)| *position)
+    }
+}
+
+fn parse_column(name: &str) -> Option<ColumnVar> {
+    let id = name.strip_prefix('c')?;
+    if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    id.parse().ok().map(ColumnVar::new)
+}
@@ -1,7 +1,8 @@
 use cso_core::expression::ScalarExpression;
 use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Equal {
     left: Box<dyn ScalarExpression>,
     right: Box<dyn ScalarExpression>,
@@ -21,6 +22,7 @@ impl Equal {
     }
 }
 
+#[typetag::serde]
 impl ScalarExpression for Equal {
     fn is_boolean_expression(&self) -> bool {
         true
@@ -39,7 +41,7 @@ impl ScalarExpression for Equal {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NotEqual {
     left: Box<dyn ScalarExpression>,
     right: Box<dyn ScalarExpression>,
@@ -59,6 +61,7 @@ impl NotEqual {
     }
 }
 
+#[typetag::serde]
 impl ScalarExpression for NotEqual {
     fn is_boolean_expression(&self) -> bool {
         true
@@ -77,7 +80,7 @@ impl ScalarExpression for NotEqual {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GreaterThan {
     left: Box<dyn ScalarExpression>,
     right: Box<dyn ScalarExpression>,
@@ -97,6 +100,7 @@ impl GreaterThan {
     }
 }
 
+#[typetag::serde]
 impl ScalarExpression for GreaterThan {
     fn is_boolean_expression(&self) -> bool {
         true
@@ -115,7 +119,7 @@ impl ScalarExpression for GreaterThan {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LessThan {
     left: Box<dyn ScalarExpression>,
     right: Box<dyn ScalarExpression>,
@@ -135,6 +139,7 @@ impl LessThan {
     }
 }
 
+#[typetag::serde]
 impl ScalarExpression for LessThan {
     fn is_boolean_expression(&self) -> bool {
         true
@@ -153,7 +158,7 @@ impl ScalarExpression for LessThan {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GreaterThanEqual {
     left: Box<dyn ScalarExpression>,
     right: Box<dyn ScalarExpression>,
@@ -173,6 +178,7 @@ impl GreaterThanEqual {
     }
 }
 
+#[typetag::serde]
 impl ScalarExpression for GreaterThanEqual {
     fn is_boolean_expression(&self) -> bool {
         true
@@ -191,7 +197,7 @@ impl ScalarExpression for GreaterThanEqual {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LessThanEqual {
     left: Box<dyn ScalarExpression>,
     right: Box<dyn ScalarExpression>,
@@ -211,6 +217,7 @@ impl LessThanEqual {
     }
 }
 
+#[typetag::serde]
 impl ScalarExpression for LessThanEqual {
     fn is_boolean_expression(&self) -> bool {
         true
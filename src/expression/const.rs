@@ -1,13 +1,15 @@
 use cso_core::expression::ScalarExpression;
 use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Eq, Hash, PartialEq, Debug)]
+#[derive(Clone, Eq, Hash, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Const {
     Int32(i32),
     Int64(i64),
     Str(String),
 }
 
+#[typetag::serde]
 impl ScalarExpression for Const {
     fn equal(&self, other: &dyn ScalarExpression) -> bool {
         match other.downcast_ref::<Const>() {
@@ -0,0 +1,214 @@
+//! Proves that predicates imply another predicate, e.g. that `c0 > 5` implies `c0 >= 0` and
+//! `c0 IS NOT NULL`.
+//!
+//! The check is sound but incomplete: it compares the predicates on the same operand one by one,
+//! so not finding a proof doesn't mean there is none.
+
+use crate::datum::Datum;
+use crate::expression::{
+    And, Const, Equal, GreaterThan, GreaterThanEqual, InList, IsNotNull, IsNull, LessThan, LessThanEqual, Not,
+    NotEqual, Or,
+};
+use cso_core::expression::ScalarExpression;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+/// Returns whether every row satisfying all of `premises` satisfies `conclusion`.
+pub fn implies(premises: &[Rc<dyn ScalarExpression>], conclusion: &dyn ScalarExpression) -> bool {
+    let mut conjuncts = vec![];
+    premises
+        .iter()
+        .for_each(|premise| split_conjuncts(premise.as_ref(), &mut conjuncts));
+    implied_by(&conjuncts, conclusion)
+}
+
+fn split_conjuncts<'a>(expr: &'a dyn ScalarExpression, conjuncts: &mut Vec<&'a dyn ScalarExpression>) {
+    match expr.downcast_ref::<And>() {
+        Some(and) => and
+            .expressions()
+            .iter()
+            .for_each(|expr| split_conjuncts(expr.as_ref(), conjuncts)),
+        None => conjuncts.push(expr),
+    }
+}
+
+fn implied_by(premises: &[&dyn ScalarExpression], conclusion: &dyn ScalarExpression) -> bool {
+    if let Some(and) = conclusion.downcast_ref::<And>() {
+        return and.expressions().iter().all(|expr| implied_by(premises, expr.as_ref()));
+    }
+    if let Some(or) = conclusion.downcast_ref::<Or>() {
+        if or.expressions().iter().any(|expr| implied_by(premises, expr.as_ref())) {
+            return true;
+        }
+    }
+    premises.iter().any(|premise| premise_implies(*premise, conclusion))
+}
+
+fn premise_implies(premise: &dyn ScalarExpression, conclusion: &dyn ScalarExpression) -> bool {
+    if premise == conclusion {
+        return true;
+    }
+    if let Some(or) = premise.downcast_ref::<Or>() {
+        // every row satisfies one of the disjuncts
+        return or
+            .expressions()
+            .iter()
+            .all(|expr| implied_by(&[expr.as_ref()], conclusion));
+    }
+
+    match (Atom::parse(premise), Atom::parse(conclusion)) {
+        (Some(premise), Some(conclusion)) => premise.implies(&conclusion),
+        _ => false,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    /// Returns whether `x op value` holds when `x` compares with `value` as `ordering`.
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Op::Eq => ordering == Ordering::Equal,
+            Op::Ne => ordering != Ordering::Equal,
+            Op::Lt => ordering == Ordering::Less,
+            Op::Le => ordering != Ordering::Greater,
+            Op::Gt => ordering == Ordering::Greater,
+            Op::Ge => ordering != Ordering::Less,
+        }
+    }
+
+    /// The operator of `value op x` for `x op value`.
+    fn flip(self) -> Self {
+        match self {
+            Op::Lt => Op::Gt,
+            Op::Le => Op::Ge,
+            Op::Gt => Op::Lt,
+            Op::Ge => Op::Le,
+            op => op,
+        }
+    }
+}
+
+/// A predicate on a single operand, which is compared with constants.
+enum Atom<'a> {
+    Compare(&'a dyn ScalarExpression, Op, Datum),
+    In(&'a dyn ScalarExpression, Vec<Datum>),
+    IsNull(&'a dyn ScalarExpression),
+    IsNotNull(&'a dyn ScalarExpression),
+}
+
+impl<'a> Atom<'a> {
+    fn parse(expr: &'a dyn ScalarExpression) -> Option<Self> {
+        if let Some(is_null) = expr.downcast_ref::<IsNull>() {
+            return Some(Atom::IsNull(is_null.inner()));
+        }
+        if let Some(is_not_null) = expr.downcast_ref::<IsNotNull>() {
+            return Some(Atom::IsNotNull(is_not_null.inner()));
+        }
+        if let Some(not) = expr.downcast_ref::<Not>() {
+            return match Atom::parse(not.expression())? {
+                Atom::IsNull(operand) => Some(Atom::IsNotNull(operand)),
+                Atom::Compare(operand, Op::Eq, value) => Some(Atom::Compare(operand, Op::Ne, value)),
+                _ => None,
+            };
+        }
+        if let Some(in_list) = expr.downcast_ref::<InList>() {
+            let values = in_list.list().iter().map(|expr| const_datum(expr.as_ref()));
+            return Some(Atom::In(in_list.expr(), values.collect::<Option<_>>()?));
+        }
+
+        let (left, op, right) = if let Some(cmp) = expr.downcast_ref::<Equal>() {
+            (cmp.left(), Op::Eq, cmp.right())
+        } else if let Some(cmp) = expr.downcast_ref::<NotEqual>() {
+            (cmp.left(), Op::Ne, cmp.right())
+        } else if let Some(cmp) = expr.downcast_ref::<LessThan>() {
+            (cmp.left(), Op::Lt, cmp.right())
+        } else if let Some(cmp) = expr.downcast_ref::<LessThanEqual>() {
+            (cmp.left(), Op::Le, cmp.right())
+        } else if let Some(cmp) = expr.downcast_ref::<GreaterThan>() {
+            (cmp.left(), Op::Gt, cmp.right())
+        } else if let Some(cmp) = expr.downcast_ref::<GreaterThanEqual>() {
+            (cmp.left(), Op::Ge, cmp.right())
+        } else {
+            return None;
+        };
+        match (const_datum(left), const_datum(right)) {
+            (None, Some(value)) => Some(Atom::Compare(left, op, value)),
+            (Some(value), None) => Some(Atom::Compare(right, op.flip(), value)),
+            _ => None,
+        }
+    }
+
+    fn operand(&self) -> &'a dyn ScalarExpression {
+        match self {
+            Atom::Compare(operand, ..) | Atom::In(operand, _) | Atom::IsNull(operand) | Atom::IsNotNull(operand) => {
+                *operand
+            }
+        }
+    }
+
+    /// Returns whether the atom holds when its operand is `value`.
+    fn holds_for(&self, value: &Datum) -> bool {
+        match self {
+            Atom::Compare(_, op, bound) => op.holds(value.cmp_value(bound)),
+            Atom::In(_, values) => values.iter().any(|v| value.cmp_value(v) == Ordering::Equal),
+            Atom::IsNull(_) => false,
+            Atom::IsNotNull(_) => true,
+        }
+    }
+
+    fn implies(&self, conclusion: &Atom) -> bool {
+        if self.operand() != conclusion.operand() {
+            return false;
+        }
+        match (self, conclusion) {
+            (Atom::IsNull(_), Atom::IsNull(_)) => true,
+            (Atom::IsNull(_), _) | (_, Atom::IsNull(_)) => false,
+            // comparisons are never true on null
+            (_, Atom::IsNotNull(_)) => true,
+            (Atom::IsNotNull(_), _) => false,
+            (Atom::Compare(_, Op::Eq, value), _) => conclusion.holds_for(value),
+            (Atom::In(_, values), _) => values.iter().all(|value| conclusion.holds_for(value)),
+            (Atom::Compare(_, op, value), Atom::Compare(_, conclusion_op, bound)) => {
+                range_implies(*op, value, *conclusion_op, bound)
+            }
+            (Atom::Compare(..), Atom::In(..)) => false,
+        }
+    }
+}
+
+/// Returns whether `x op value` implies `x conclusion_op bound`, where `op` is not `Eq`. The
+/// values between two bounds are assumed to be dense.
+fn range_implies(op: Op, value: &Datum, conclusion_op: Op, bound: &Datum) -> bool {
+    let ordering = value.cmp_value(bound);
+    match (op, conclusion_op) {
+        (Op::Ne, Op::Ne) => ordering == Ordering::Equal,
+        (Op::Ne, _) => false,
+        // the bound is outside of the range of the premise
+        (_, Op::Ne) => !op.holds(bound.cmp_value(value)),
+        (_, Op::Eq) => false,
+        (Op::Lt | Op::Le, Op::Lt | Op::Le) | (Op::Gt | Op::Ge, Op::Gt | Op::Ge) => {
+            // the range of the premise ends before the bound, or at it when it is included
+            let inner = if matches!(op, Op::Lt | Op::Le) {
+                ordering == Ordering::Less
+            } else {
+                ordering == Ordering::Greater
+            };
+            let included = op == Op::Lt || op == Op::Gt || conclusion_op == Op::Le || conclusion_op == Op::Ge;
+            inner || (ordering == Ordering::Equal && included)
+        }
+        _ => false,
+    }
+}
+
+fn const_datum(expr: &dyn ScalarExpression) -> Option<Datum> {
+    expr.downcast_ref::<Const>().map(Datum::from)
+}
@@ -0,0 +1,48 @@
+use cso_core::expression::ScalarExpression;
+use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
+
+/// `expr IN (list...)`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InList {
+    expr: Box<dyn ScalarExpression>,
+    list: Vec<Box<dyn ScalarExpression>>,
+}
+
+impl InList {
+    pub fn new(expr: Box<dyn ScalarExpression>, list: Vec<Box<dyn ScalarExpression>>) -> Self {
+        assert!(!list.is_empty(), "in list must not be empty");
+        Self { expr, list }
+    }
+
+    pub fn expr(&self) -> &dyn ScalarExpression {
+        self.expr.as_ref()
+    }
+
+    pub fn list(&self) -> &[Box<dyn ScalarExpression>] {
+        &self.list
+    }
+}
+
+#[typetag::serde]
+impl ScalarExpression for InList {
+    fn is_boolean_expression(&self) -> bool {
+        true
+    }
+
+    fn equal(&self, other: &dyn ScalarExpression) -> bool {
+        match other.downcast_ref::<InList>() {
+            Some(other) => {
+                self.expr.eq(&other.expr)
+                    && self.list.len() == other.list.len()
+                    && self.list.iter().zip(&other.list).all(|(l, r)| l.eq(r))
+            }
+            None => false,
+        }
+    }
+
+    fn derive_used_columns(&self, col_set: &mut ColumnRefSet) {
+        self.expr.derive_used_columns(col_set);
+        self.list.iter().for_each(|expr| expr.derive_used_columns(col_set));
+    }
+}
@@ -1,7 +1,8 @@
 use cso_core::expression::ScalarExpression;
 use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct IsNull {
     inner: Box<dyn ScalarExpression>,
 }
@@ -10,8 +11,13 @@ impl IsNull {
     pub fn new(inner: Box<dyn ScalarExpression>) -> Self {
         Self { inner }
     }
+
+    pub fn inner(&self) -> &dyn ScalarExpression {
+        self.inner.as_ref()
+    }
 }
 
+#[typetag::serde]
 impl ScalarExpression for IsNull {
     fn is_boolean_expression(&self) -> bool {
         true
@@ -29,7 +35,7 @@ impl ScalarExpression for IsNull {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct IsNotNull {
     inner: Box<dyn ScalarExpression>,
 }
@@ -38,8 +44,13 @@ impl IsNotNull {
     pub fn new(inner: Box<dyn ScalarExpression>) -> Self {
         Self { inner }
     }
+
+    pub fn inner(&self) -> &dyn ScalarExpression {
+        self.inner.as_ref()
+    }
 }
 
+#[typetag::serde]
 impl ScalarExpression for IsNotNull {
     fn is_boolean_expression(&self) -> bool {
         true
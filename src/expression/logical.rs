@@ -1,8 +1,9 @@
 use cso_core::expression::ScalarExpression;
 use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct And {
     expressions: Vec<Rc<dyn ScalarExpression>>,
 }
@@ -18,6 +19,7 @@ impl And {
     }
 }
 
+#[typetag::serde]
 impl ScalarExpression for And {
     fn is_boolean_expression(&self) -> bool {
         true
@@ -35,7 +37,7 @@ impl ScalarExpression for And {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Or {
     expressions: Vec<Box<dyn ScalarExpression>>,
 }
@@ -45,8 +47,13 @@ impl Or {
         assert!(expressions.iter().all(|expr| expr.is_boolean_expression()));
         Or { expressions }
     }
+
+    pub fn expressions(&self) -> &[Box<dyn ScalarExpression>] {
+        &self.expressions
+    }
 }
 
+#[typetag::serde]
 impl ScalarExpression for Or {
     fn is_boolean_expression(&self) -> bool {
         true
@@ -64,7 +71,7 @@ impl ScalarExpression for Or {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Not {
     expression: Box<dyn ScalarExpression>,
 }
@@ -74,8 +81,13 @@ impl Not {
         assert!(expression.is_boolean_expression());
         Not { expression }
     }
+
+    pub fn expression(&self) -> &dyn ScalarExpression {
+        self.expression.as_ref()
+    }
 }
 
+#[typetag::serde]
 impl ScalarExpression for Not {
     fn is_boolean_expression(&self) -> bool {
         true
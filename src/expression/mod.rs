@@ -1,12 +1,18 @@
 mod cmp;
 mod r#const;
+mod implication;
+mod in_list;
 mod is_null;
 mod logical;
+mod remap;
 mod var;
 
 pub use self::cmp::{Equal, GreaterThan, GreaterThanEqual, LessThan, LessThanEqual, NotEqual};
+pub use self::implication::implies;
+pub use self::in_list::InList;
 pub use self::is_null::{IsNotNull, IsNull};
 pub use self::logical::{And, Not, Or};
 pub use self::r#const::Const;
+pub use self::remap::remap_columns;
 pub use self::var::ColumnVar;
 pub use cso_core::expression::{AggregateExpression, ScalarExpression};
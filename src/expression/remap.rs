@@ -0,0 +1,80 @@
+//! Rewrites the columns referenced by expressions, e.g. to give the columns of an expanded view
+//! ids of their own.
+
+use crate::expression::{
+    And, ColumnVar, Const, Equal, GreaterThan, GreaterThanEqual, InList, IsNotNull, IsNull, LessThan, LessThanEqual,
+    Not, NotEqual, Or,
+};
+use cso_core::expression::ScalarExpression;
+
+/// Returns the expression with every column `c` replaced by the column `remap(c)`.
+pub fn remap_columns(expr: &dyn ScalarExpression, remap: &mut dyn FnMut(u32) -> u32) -> Box<dyn ScalarExpression> {
+    if let Some(column) = expr.downcast_ref::<ColumnVar>() {
+        return Box::new(ColumnVar::new(remap(column.id())));
+    }
+    if let Some(value) = expr.downcast_ref::<Const>() {
+        return Box::new(value.clone());
+    }
+    if let Some(and) = expr.downcast_ref::<And>() {
+        let expressions = and
+            .expressions()
+            .iter()
+            .map(|expr| remap_columns(expr.as_ref(), remap).into());
+        return Box::new(And::new(expressions.collect()));
+    }
+    if let Some(or) = expr.downcast_ref::<Or>() {
+        let expressions = or.expressions().iter().map(|expr| remap_columns(expr.as_ref(), remap));
+        return Box::new(Or::new(expressions.collect()));
+    }
+    if let Some(not) = expr.downcast_ref::<Not>() {
+        return Box::new(Not::new(remap_columns(not.expression(), remap)));
+    }
+    if let Some(is_null) = expr.downcast_ref::<IsNull>() {
+        return Box::new(IsNull::new(remap_columns(is_null.inner(), remap)));
+    }
+    if let Some(is_not_null) = expr.downcast_ref::<IsNotNull>() {
+        return Box::new(IsNotNull::new(remap_columns(is_not_null.inner(), remap)));
+    }
+    if let Some(in_list) = expr.downcast_ref::<InList>() {
+        let expr = remap_columns(in_list.expr(), remap);
+        let list = in_list.list().iter().map(|expr| remap_columns(expr.as_ref(), remap));
+        return Box::new(InList::new(expr, list.collect()));
+    }
+    if let Some(cmp) = expr.downcast_ref::<Equal>() {
+        return Box::new(Equal::new(
+            remap_columns(cmp.left(), remap),
+            remap_columns(cmp.right(), remap),
+        ));
+    }
+    if let Some(cmp) = expr.downcast_ref::<NotEqual>() {
+        return Box::new(NotEqual::new(
+            remap_columns(cmp.left(), remap),
+            remap_columns(cmp.right(), remap),
+        ));
+    }
+    if let Some(cmp) = expr.downcast_ref::<LessThan>() {
+        return Box::new(LessThan::new(
+            remap_columns(cmp.left(), remap),
+            remap_columns(cmp.right(), remap),
+        ));
+    }
+    if let Some(cmp) = expr.downcast_ref::<LessThanEqual>() {
+        return Box::new(LessThanEqual::new(
+            remap_columns(cmp.left(), remap),
+            remap_columns(cmp.right(), remap),
+        ));
+    }
+    if let Some(cmp) = expr.downcast_ref::<GreaterThan>() {
+        return Box::new(GreaterThan::new(
+            remap_columns(cmp.left(), remap),
+            remap_columns(cmp.right(), remap),
+        ));
+    }
+    if let Some(cmp) = expr.downcast_ref::<GreaterThanEqual>() {
+        return Box::new(GreaterThanEqual::new(
+            remap_columns(cmp.left(), remap),
+            remap_columns(cmp.right(), remap),
+        ));
+    }
+    panic!("unsupported expression: {expr:?}");
+}
@@ -18,6 +18,7 @@ impl ColumnVar {
     }
 }
 
+#[typetag::serde]
 impl ScalarExpression for ColumnVar {
     fn equal(&self, other: &dyn ScalarExpression) -> bool {
         match other.downcast_ref::<ColumnVar>() {
@@ -0,0 +1,358 @@
+//! Random plans for fuzzing the optimizer.
+//!
+//! [`PlanGenerator`] builds a random catalog (relations, column statistics and indexes) and
+//! random logical plans with required sort properties over it. The same seed always generates
+//! the same case, so a failing case can be reproduced from its seed alone.
+
+use crate::datum::Datum;
+use crate::expression::{
+    And, ColumnVar, Const, Equal, GreaterThan, GreaterThanEqual, IsNotNull, IsNull, LessThan, LessThanEqual, Not,
+    NotEqual, Or,
+};
+use crate::metadata::{MdCache, Metadata};
+use crate::operator::logical_filter::LogicalFilter;
+use crate::operator::logical_project::LogicalProject;
+use crate::operator::logical_scan::{LogicalScan, TableDesc};
+use crate::operator::physical_sort::{OrderSpec, Ordering};
+use crate::property::sort_property::SortProperty;
+use crate::property::PhysicalProperties;
+use crate::statistics::{
+    Bucket, ColumnMetadata, ColumnStats, Histogram, IndexInfo, IndexMd, RelationMetadata, RelationStats,
+};
+use crate::LogicalPlan;
+use cso_core::expression::ScalarExpression;
+use std::rc::Rc;
+
+const MAX_TABLES: usize = 3;
+const MAX_COLUMNS: usize = 6;
+const MAX_INDEXES: usize = 3;
+const MAX_OPERATORS: usize = 4;
+const MAX_PREDICATE_DEPTH: usize = 3;
+const MAX_SORT_KEYS: usize = 3;
+
+/// A small deterministic pseudo random number generator (SplitMix64).
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a value in `[0, bound)`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        debug_assert!(bound > 0);
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Returns a value in `[low, high]`.
+    pub fn between(&mut self, low: usize, high: usize) -> usize {
+        low + self.below(high - low + 1)
+    }
+
+    /// Returns true with the probability of `numerator / denominator`.
+    pub fn chance(&mut self, numerator: usize, denominator: usize) -> bool {
+        self.below(denominator) < numerator
+    }
+
+    pub fn choose<'a, E>(&mut self, items: &'a [E]) -> &'a E {
+        &items[self.below(items.len())]
+    }
+
+    /// Returns `count` distinct items in random order.
+    pub fn sample<E: Clone>(&mut self, items: &[E], count: usize) -> Vec<E> {
+        let mut items = items.to_vec();
+        for i in 0..count.min(items.len()) {
+            let j = i + self.below(items.len() - i);
+            items.swap(i, j);
+        }
+        items.truncate(count);
+        items
+    }
+}
+
+#[derive(Clone, Debug)]
+struct RandomTable {
+    md_id: u64,
+    column_count: usize,
+}
+
+/// A random catalog, every relation has its metadata, statistics and indexes in the cache.
+#[derive(Clone)]
+pub struct RandomCatalog {
+    md_cache: MdCache,
+    tables: Vec<RandomTable>,
+}
+
+impl RandomCatalog {
+    pub fn md_cache(&self) -> &MdCache {
+        &self.md_cache
+    }
+}
+
+/// A randomly generated optimization problem.
+#[derive(Clone)]
+pub struct FuzzCase {
+    seed: u64,
+    catalog: RandomCatalog,
+    plan: LogicalPlan,
+    required_properties: Rc<PhysicalProperties>,
+}
+
+impl FuzzCase {
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn catalog(&self) -> &RandomCatalog {
+        &self.catalog
+    }
+
+    pub fn md_cache(&self) -> &MdCache {
+        self.catalog.md_cache()
+    }
+
+    pub fn plan(&self) -> &LogicalPlan {
+        &self.plan
+    }
+
+    pub fn required_properties(&self) -> &Rc<PhysicalProperties> {
+        &self.required_properties
+    }
+}
+
+pub struct PlanGenerator {
+    rng: Rng,
+    next_md_id: u64,
+}
+
+impl PlanGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Rng::new(seed),
+            next_md_id: 1,
+        }
+    }
+
+    /// Generates a catalog and a plan over it for the seed.
+    pub fn generate_case(seed: u64) -> FuzzCase {
+        let mut generator = PlanGenerator::new(seed);
+        let catalog = generator.generate_catalog();
+        let (plan, output_columns) = generator.generate_plan(&catalog);
+        let required_properties = generator.generate_required_properties(&output_columns);
+        FuzzCase {
+            seed,
+            catalog,
+            plan,
+            required_properties,
+        }
+    }
+
+    pub fn generate_catalog(&mut self) -> RandomCatalog {
+        let mut md_cache = MdCache::new();
+        let table_count = self.rng.between(1, MAX_TABLES);
+        let tables = (0..table_count)
+            .map(|i| self.generate_table(i, &mut md_cache))
+            .collect();
+        RandomCatalog { md_cache, tables }
+    }
+
+    fn generate_table(&mut self, table_index: usize, md_cache: &mut MdCache) -> RandomTable {
+        let rel_md_id = self.allocate_md_id();
+        let rel_stats_md_id = self.allocate_md_id();
+        let column_count = self.rng.between(1, MAX_COLUMNS);
+        let rows = self.rng.between(0, 100_000) as u64;
+
+        let columns = (0..column_count)
+            .map(|i| {
+                let nullable = self.rng.chance(1, 2);
+                ColumnMetadata::new(format!("c{i}"), i as u64, nullable, 4, Datum::I32(0))
+            })
+            .collect();
+
+        let mut col_stat_mdids = vec![];
+        for col_id in 0..column_count {
+            if self.rng.chance(2, 3) {
+                let md_id = self.allocate_md_id();
+                let column_stats = self.generate_column_stats(col_id, rows);
+                md_cache.insert(md_id, Box::new(column_stats) as Box<dyn Metadata>);
+                col_stat_mdids.push(md_id);
+            }
+        }
+
+        let all_columns = (0..column_count as u32).map(ColumnVar::new).collect::<Vec<_>>();
+        let mut index_info_list = vec![];
+        for i in 0..self.rng.below(MAX_INDEXES + 1) {
+            let md_id = self.allocate_md_id();
+            let key_count = self.rng.between(1, column_count);
+            let key_columns = self.rng.sample(&all_columns, key_count);
+            let mut included_columns = key_columns.clone();
+            for column in &all_columns {
+                if !included_columns.contains(column) && self.rng.chance(1, 2) {
+                    included_columns.push(column.clone());
+                }
+            }
+            let index_md = IndexMd::new(md_id, format!("IDX_{table_index}_{i}"), key_columns, included_columns);
+            md_cache.insert(md_id, Box::new(index_md) as Box<dyn Metadata>);
+            index_info_list.push(IndexInfo::new(md_id));
+        }
+
+        let name = format!("t{table_index}");
+        let rel_stats = RelationStats::new(name.clone(), rows, rows == 0, col_stat_mdids);
+        let rel_md = RelationMetadata::new(name, columns, rel_stats_md_id, index_info_list);
+        md_cache.insert(rel_md_id, Box::new(rel_md) as Box<dyn Metadata>);
+        md_cache.insert(rel_stats_md_id, Box::new(rel_stats) as Box<dyn Metadata>);
+
+        RandomTable {
+            md_id: rel_md_id,
+            column_count,
+        }
+    }
+
+    fn generate_column_stats(&mut self, col_id: usize, rows: u64) -> ColumnStats {
+        let null_count = self.rng.between(0, rows as usize) as u64;
+        let min = self.rng.between(0, 1000) as i32 - 500;
+        let max = min + self.rng.between(0, 1000) as i32;
+
+        let histogram = self.rng.chance(1, 2).then(|| {
+            let bucket_count = self.rng.between(1, 4);
+            let width = ((max - min) / bucket_count as i32).max(1);
+            let buckets = (0..bucket_count)
+                .map(|i| {
+                    let lower = min + width * i as i32;
+                    let value_count = self.rng.between(0, 1000) as u64;
+                    let ndv = self.rng.between(0, value_count as usize) as u64;
+                    Bucket::new(Datum::I32(lower), Datum::I32(lower + width), ndv, value_count)
+                })
+                .collect();
+            Histogram::new(buckets)
+        });
+
+        ColumnStats::new(
+            col_id,
+            format!("c{col_id}"),
+            Datum::I32(min),
+            Datum::I32(max),
+            null_count,
+            histogram,
+        )
+    }
+
+    /// Generates a plan of filters and projects over a scan of a random table.
+    /// Returns the plan with the columns its root produces.
+    pub fn generate_plan(&mut self, catalog: &RandomCatalog) -> (LogicalPlan, Vec<ColumnVar>) {
+        let table = self.rng.choose(&catalog.tables).clone();
+        let all_columns = (0..table.column_count as u32).map(ColumnVar::new).collect::<Vec<_>>();
+        let column_count = self.rng.between(1, all_columns.len());
+        let mut columns = self.rng.sample(&all_columns, column_count);
+        columns.sort_by_key(|column| column.id());
+
+        let scan = LogicalScan::new(TableDesc::new(table.md_id), columns.clone());
+        let mut plan = LogicalPlan::new(Rc::new(scan), vec![], vec![]);
+
+        for _ in 0..self.rng.below(MAX_OPERATORS + 1) {
+            if self.rng.chance(1, 2) {
+                let predicate = self.generate_predicate(&columns, MAX_PREDICATE_DEPTH);
+                let filter = LogicalFilter::new(Rc::from(predicate));
+                plan = LogicalPlan::new(Rc::new(filter), vec![plan], vec![]);
+            } else {
+                let project_count = self.rng.between(1, columns.len());
+                columns = self.rng.sample(&columns, project_count);
+                let project = columns
+                    .iter()
+                    .map(|column| Rc::new(column.clone()) as Rc<dyn ScalarExpression>)
+                    .collect();
+                plan = LogicalPlan::new(Rc::new(LogicalProject::new(project)), vec![plan], vec![]);
+            }
+        }
+
+        (plan, columns)
+    }
+
+    fn generate_predicate(&mut self, columns: &[ColumnVar], depth: usize) -> Box<dyn ScalarExpression> {
+        let choice = if depth == 0 {
+            self.rng.below(3)
+        } else {
+            self.rng.below(6)
+        };
+        match choice {
+            0 => Box::new(IsNull::new(self.generate_column(columns))),
+            1 => Box::new(IsNotNull::new(self.generate_column(columns))),
+            2 => self.generate_comparison(columns),
+            3 => {
+                let count = self.rng.between(1, 3);
+                let predicates = (0..count)
+                    .map(|_| Rc::from(self.generate_predicate(columns, depth - 1)))
+                    .collect();
+                Box::new(And::new(predicates))
+            }
+            4 => {
+                let count = self.rng.between(1, 3);
+                let predicates = (0..count)
+                    .map(|_| self.generate_predicate(columns, depth - 1))
+                    .collect();
+                Box::new(Or::new(predicates))
+            }
+            _ => Box::new(Not::new(self.generate_predicate(columns, depth - 1))),
+        }
+    }
+
+    fn generate_comparison(&mut self, columns: &[ColumnVar]) -> Box<dyn ScalarExpression> {
+        let column = self.generate_column(columns);
+        let value = Box::new(Const::Int32(self.rng.between(0, 1000) as i32 - 500));
+        let (left, right) = if self.rng.chance(1, 2) {
+            (column, value as Box<dyn ScalarExpression>)
+        } else {
+            (value as Box<dyn ScalarExpression>, column)
+        };
+        match self.rng.below(6) {
+            0 => Box::new(Equal::new(left, right)),
+            1 => Box::new(NotEqual::new(left, right)),
+            2 => Box::new(LessThan::new(left, right)),
+            3 => Box::new(LessThanEqual::new(left, right)),
+            4 => Box::new(GreaterThan::new(left, right)),
+            _ => Box::new(GreaterThanEqual::new(left, right)),
+        }
+    }
+
+    fn generate_column(&mut self, columns: &[ColumnVar]) -> Box<dyn ScalarExpression> {
+        Box::new(self.rng.choose(columns).clone())
+    }
+
+    /// Generates no required properties or a sort on some of the columns.
+    pub fn generate_required_properties(&mut self, columns: &[ColumnVar]) -> Rc<PhysicalProperties> {
+        if self.rng.chance(1, 3) {
+            return Rc::new(PhysicalProperties::new());
+        }
+
+        let key_count = self.rng.between(1, columns.len().min(MAX_SORT_KEYS));
+        let order_desc = self
+            .rng
+            .sample(columns, key_count)
+            .into_iter()
+            .map(|key| Ordering {
+                key,
+                ascending: self.rng.chance(3, 4),
+                nulls_first: self.rng.chance(3, 4),
+            })
+            .collect();
+        let sort_property = SortProperty::with_order(OrderSpec { order_desc });
+        PhysicalProperties::with_property(Box::new(sort_property))
+    }
+
+    fn allocate_md_id(&mut self) -> u64 {
+        let md_id = self.next_md_id;
+        self.next_md_id += 1;
+        md_id
+    }
+}
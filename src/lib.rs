@@ -4,18 +4,33 @@
 #![allow(clippy::new_without_default)]
 #![allow(clippy::borrowed_box)]
 
+use crate::minidump::Minidump;
 use crate::operator::OperatorId;
 use crate::rule::RuleId;
-use cso_core::OptimizerType;
+use cso_core::{Capture, OptimizerType};
+use std::io;
+use std::path::Path;
 
+pub mod analyze;
 pub mod cost;
 pub mod datum;
+pub mod dsl;
 pub mod expression;
+#[cfg(any(test, feature = "fuzz"))]
+pub mod fuzz;
+pub mod metadata;
+pub mod minidump;
 pub mod operator;
+pub mod partition;
 pub mod property;
 pub mod rule;
+pub mod selectivity;
+pub mod serialize;
+pub mod sketch;
 pub mod statistics;
 
+mod util;
+
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub struct Demo;
 
@@ -23,19 +38,10 @@ impl OptimizerType for Demo {
     type RuleId = RuleId;
     type OperatorId = OperatorId;
     type MdId = u64;
-}
-
-pub use cso_core::Options;
-
-pub mod metadata {
-    use crate::Demo;
 
-    pub type MdAccessor = cso_core::metadata::MdAccessor<Demo>;
-    pub type MdCache = cso_core::metadata::MdCache<Demo>;
-    pub type MdProvider = dyn cso_core::metadata::MdProvider<Demo>;
-    pub type CachedMdProvider = cso_core::metadata::CachedMdProvider<Demo>;
-    pub use cso_core::metadata::Metadata;
-    pub use cso_core::metadata::Stats;
+    fn write_minidump(capture: &Capture<Self>, path: &Path) -> io::Result<()> {
+        Minidump::from_capture(capture).write_to_file(path)
+    }
 }
 
 pub(crate) type GroupPlan = cso_core::memo::GroupPlan<Demo>;
@@ -48,3 +54,4 @@ pub type OptimizerContext = cso_core::OptimizerContext<Demo>;
 pub type LogicalPlan = cso_core::LogicalPlan<Demo>;
 pub type PhysicalPlan = cso_core::PhysicalPlan<Demo>;
 pub type Optimizer = cso_core::Optimizer<Demo>;
+pub type Options = cso_core::Options<Demo>;
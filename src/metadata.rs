@@ -0,0 +1,94 @@
+use crate::sketch::HyperLogLog;
+use crate::statistics::{ColumnStats, IndexMd, MultiColumnStats, RelationMetadata, RelationStats, ViewMetadata};
+use crate::Demo;
+
+pub type MdAccessor = cso_core::metadata::MdAccessor<Demo>;
+pub type MdCache = cso_core::metadata::MdCache<Demo>;
+pub type MdProvider = dyn cso_core::metadata::MdProvider<Demo>;
+pub type CachedMdProvider = cso_core::metadata::CachedMdProvider<Demo>;
+pub type JsonDirMdProvider = cso_core::metadata::JsonDirMdProvider<Demo>;
+pub type SharedMdCache = cso_core::metadata::SharedMdCache<Demo>;
+pub type SharedMdSnapshot = cso_core::metadata::SharedMdSnapshot<Demo>;
+pub type MdEntry = cso_core::metadata::MdEntry<Demo>;
+pub use cso_core::metadata::MdError;
+pub use cso_core::metadata::Metadata;
+pub use cso_core::metadata::Stats;
+
+/// Typed lookups of the demo metadata.
+pub trait MdAccessorExt {
+    fn retrieve_relation(&self, md_id: u64) -> Result<RelationMetadata, MdError>;
+    fn retrieve_relation_stats(&self, md_id: u64) -> Result<RelationStats, MdError>;
+    fn retrieve_index(&self, md_id: u64) -> Result<IndexMd, MdError>;
+    fn retrieve_column_stats(&self, md_id: u64) -> Result<ColumnStats, MdError>;
+    fn retrieve_sketch(&self, md_id: u64) -> Result<HyperLogLog, MdError>;
+    fn retrieve_multi_column_stats(&self, md_id: u64) -> Result<MultiColumnStats, MdError>;
+    fn retrieve_view(&self, md_id: u64) -> Result<ViewMetadata, MdError>;
+
+    /// Returns the relation with the name and its mdid.
+    fn relation_by_name(&self, name: &str) -> Result<(u64, RelationMetadata), MdError>;
+    /// Returns the statistics of the relation.
+    fn relation_stats(&self, relation: &RelationMetadata) -> Result<RelationStats, MdError>;
+    /// Returns all indexes of the relation.
+    fn relation_indexes(&self, relation: &RelationMetadata) -> Result<Vec<IndexMd>, MdError>;
+    /// Returns the statistics of the column at position `col_id` of the relation, if analyzed.
+    fn column_stats(&self, relation: &RelationMetadata, col_id: usize) -> Result<Option<ColumnStats>, MdError>;
+}
+
+impl MdAccessorExt for MdAccessor {
+    fn retrieve_relation(&self, md_id: u64) -> Result<RelationMetadata, MdError> {
+        self.retrieve_metadata_as(&md_id)
+    }
+
+    fn retrieve_relation_stats(&self, md_id: u64) -> Result<RelationStats, MdError> {
+        self.retrieve_metadata_as(&md_id)
+    }
+
+    fn retrieve_index(&self, md_id: u64) -> Result<IndexMd, MdError> {
+        self.retrieve_metadata_as(&md_id)
+    }
+
+    fn retrieve_column_stats(&self, md_id: u64) -> Result<ColumnStats, MdError> {
+        self.retrieve_metadata_as(&md_id)
+    }
+
+    fn retrieve_sketch(&self, md_id: u64) -> Result<HyperLogLog, MdError> {
+        self.retrieve_metadata_as(&md_id)
+    }
+
+    fn retrieve_multi_column_stats(&self, md_id: u64) -> Result<MultiColumnStats, MdError> {
+        self.retrieve_metadata_as(&md_id)
+    }
+
+    fn retrieve_view(&self, md_id: u64) -> Result<ViewMetadata, MdError> {
+        self.retrieve_metadata_as(&md_id)
+    }
+
+    fn relation_by_name(&self, name: &str) -> Result<(u64, RelationMetadata), MdError> {
+        self.find_metadata_as(|relation: &RelationMetadata| relation.name() == name)?
+            .ok_or_else(|| MdError::NameNotFound {
+                name: name.to_string(),
+                expected: "RelationMetadata",
+            })
+    }
+
+    fn relation_stats(&self, relation: &RelationMetadata) -> Result<RelationStats, MdError> {
+        self.retrieve_relation_stats(relation.rel_stats_mdid())
+    }
+
+    fn relation_indexes(&self, relation: &RelationMetadata) -> Result<Vec<IndexMd>, MdError> {
+        (0..relation.index_count())
+            .map(|i| self.retrieve_index(relation.index_mdid(i)))
+            .collect()
+    }
+
+    fn column_stats(&self, relation: &RelationMetadata, col_id: usize) -> Result<Option<ColumnStats>, MdError> {
+        let relation_stats = self.relation_stats(relation)?;
+        for md_id in relation_stats.col_stat_mdids() {
+            let column_stats = self.retrieve_column_stats(*md_id)?;
+            if column_stats.col_id() == col_id {
+                return Ok(Some(column_stats));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,127 @@
+//! Minidumps capture everything an optimization depends on, so that a plan choice can be
+//! reproduced offline: the input plan, the required properties, the options, the rules, the
+//! metadata the optimizer actually fetched and the resulting plan.
+//!
+//! An optimization writes its minidump to a file when given a path with
+//! [`Options::with_minidump_path`], or [`Minidump::capture`] keeps it in memory.
+
+use crate::metadata::{CachedMdProvider, MdAccessor, MdCache, MdProvider};
+use crate::property::PhysicalProperties;
+use crate::rule::RuleId;
+use crate::util::diff_lines;
+use crate::{Demo, LogicalPlan, Optimizer, Options, PhysicalPlan};
+use cso_core::rule::{RuleRef, RuleSet};
+use cso_core::Capture;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+#[derive(Serialize, Deserialize)]
+pub struct Minidump {
+    options: Options,
+    transform_rules: Vec<RuleId>,
+    implement_rules: Vec<RuleId>,
+    #[serde(with = "crate::serialize::logical_plan")]
+    plan: LogicalPlan,
+    #[serde(with = "crate::serialize::physical_properties")]
+    required_properties: Rc<PhysicalProperties>,
+    metadata: MdCache,
+    #[serde(with = "crate::serialize::physical_plan")]
+    result: PhysicalPlan,
+}
+
+impl Minidump {
+    /// Optimizes `plan` and captures the optimization as a minidump. The optimized plan is
+    /// available through [`Minidump::result`].
+    pub fn capture(
+        options: Options,
+        plan: LogicalPlan,
+        required_properties: Rc<PhysicalProperties>,
+        md_provider: Rc<MdProvider>,
+        rule_set: RuleSet<Demo>,
+    ) -> Minidump {
+        let md_accessor = MdAccessor::new(md_provider);
+        let mut optimizer = Optimizer::new(options);
+        optimizer.optimize_and_capture(plan, required_properties, md_accessor, rule_set, Minidump::from_capture)
+    }
+
+    pub fn from_capture(capture: &Capture<Demo>) -> Minidump {
+        let rule_ids = |rules: &[RuleRef<Demo>]| rules.iter().map(|rule| rule.rule_id()).collect();
+        Minidump {
+            options: capture.options.clone(),
+            transform_rules: rule_ids(capture.rule_set.transform_rules()),
+            implement_rules: rule_ids(capture.rule_set.implement_rules()),
+            plan: capture.plan.clone(),
+            required_properties: capture.required_properties.clone(),
+            metadata: capture.metadata.clone(),
+            result: capture.result.clone(),
+        }
+    }
+
+    pub fn read_from_file<P: AsRef<Path>>(path: P) -> io::Result<Minidump> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)
+    }
+
+    pub fn metadata(&self) -> &MdCache {
+        &self.metadata
+    }
+
+    pub fn result(&self) -> &PhysicalPlan {
+        &self.result
+    }
+
+    /// Runs the captured optimization again, using only the metadata stored in the minidump.
+    /// Fails if the minidump names rules this build doesn't have.
+    pub fn replay(&self) -> Result<PhysicalPlan, String> {
+        let md_provider = Rc::new(CachedMdProvider::new(self.metadata.clone()));
+        let md_accessor = MdAccessor::new(md_provider);
+
+        let mut optimizer = Optimizer::new(self.options.clone());
+        Ok(optimizer.optimize(
+            self.plan.clone(),
+            self.required_properties.clone(),
+            md_accessor,
+            self.rule_set()?,
+        ))
+    }
+
+    /// Returns a diff between the captured plan and `plan`, or `None` if they are equal.
+    pub fn diff(&self, plan: &PhysicalPlan) -> Option<String> {
+        if self.result == *plan {
+            return None;
+        }
+        let diff = diff_lines(&self.result.explain(), &plan.explain())
+            // the operators may differ in what explain doesn't show
+            .or_else(|| diff_lines(&format!("{:#?}", self.result), &format!("{plan:#?}")))
+            .unwrap_or_else(|| "the plans print the same but differ\n".to_string());
+        Some(diff)
+    }
+
+    fn rule_set(&self) -> Result<RuleSet<Demo>, String> {
+        let all_rules = crate::rule::create_rule_set();
+        let select = |candidates: &[RuleRef<Demo>], ids: &[RuleId]| -> Result<Vec<RuleRef<Demo>>, String> {
+            ids.iter()
+                .map(|id| {
+                    candidates
+                        .iter()
+                        .find(|rule| rule.rule_id() == *id)
+                        .cloned()
+                        .ok_or_else(|| format!("unknown rule: {id:?}"))
+                })
+                .collect()
+        };
+
+        let mut rule_set = RuleSet::new();
+        rule_set.set_transform_rules(select(all_rules.transform_rules(), &self.transform_rules)?);
+        rule_set.set_implement_rules(select(all_rules.implement_rules(), &self.implement_rules)?);
+        Ok(rule_set)
+    }
+}
@@ -1,11 +1,15 @@
-use crate::expression::And;
+use crate::expression::{And, ColumnVar};
 use crate::metadata::MdAccessor;
 use crate::operator::OperatorId;
+use crate::selectivity::{conjunction_selectivity, estimated_from_stats, restrict_column_stats};
+use crate::statistics::{ColumnStats, EstimateSource, Statistics};
 use crate::{Demo, Plan};
 use cso_core::expression::ScalarExpression;
 use cso_core::metadata::Stats;
 use cso_core::operator::LogicalOperator;
 use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::rc::Rc;
 
 pub fn split_predicate(input: &Rc<dyn ScalarExpression>, predicates: &mut Vec<Rc<dyn ScalarExpression>>) {
@@ -19,7 +23,68 @@ pub fn split_predicate(input: &Rc<dyn ScalarExpression>, predicates: &mut Vec<Rc
     }
 }
 
-#[derive(Debug)]
+/// Derives the statistics of the rows with `input_stats` satisfying all the predicates.
+///
+/// The statistics of a column compared with constants, or tested for null, are restricted to the
+/// values satisfying its predicates. Then every column is scaled down to the output rows, as if
+/// the other predicates were independent of it. Multi-column statistics are only kept, scaled
+/// down, for columns no predicate restricted.
+///
+/// The estimate is never more trustworthy than the input statistics, and it is a default guess
+/// if any predicate couldn't be estimated from statistics.
+pub fn derive_filter_stats(input_stats: &Statistics, predicates: &[Rc<dyn ScalarExpression>]) -> Statistics {
+    let selectivity = conjunction_selectivity(predicates, input_stats);
+
+    let input_rows = input_stats.output_row_count();
+    let mut output_rows = (input_rows as f64 * selectivity).round() as u64;
+    if input_rows > 0 {
+        output_rows = output_rows.max(1);
+    }
+
+    let source = if predicates.is_empty() {
+        input_stats.source()
+    } else if !predicates
+        .iter()
+        .all(|predicate| estimated_from_stats(predicate.as_ref(), input_stats))
+    {
+        EstimateSource::DefaultGuess
+    } else if predicates.len() == 1 {
+        EstimateSource::ColumnStats
+    } else {
+        EstimateSource::MultiplePredicates
+    };
+
+    // column id -> (column stats, rows) restricted by the predicates on the column
+    let mut restricted = BTreeMap::<u32, (ColumnStats, u64)>::new();
+    for predicate in predicates {
+        let column_stats = |column: &ColumnVar| match restricted.get(&column.id()) {
+            Some(restricted) => Some(restricted.clone()),
+            None => Some((input_stats.column_stats_of(column)?.clone(), input_rows)),
+        };
+        if let Some((column, column_stats, rows)) = restrict_column_stats(predicate.as_ref(), column_stats) {
+            restricted.insert(column.id(), (column_stats, rows));
+        }
+    }
+
+    let column_stats = input_stats.column_stats().iter().map(|(id, column_stats)| {
+        let column_stats = match restricted.get(id) {
+            Some((column_stats, rows)) => column_stats.scale(*rows, output_rows),
+            None => column_stats.scale(input_rows, output_rows),
+        };
+        (*id, column_stats)
+    });
+    // the joint values of columns restricted by the predicates aren't known anymore
+    let multi_column_stats = input_stats
+        .multi_column_stats()
+        .iter()
+        .filter(|(group, _)| group.iter().all(|column| !restricted.contains_key(&column.id())))
+        .map(|(group, stats)| (group.clone(), stats.scale(input_rows, output_rows)));
+    Statistics::new(output_rows, source.min(input_stats.source()), column_stats.collect())
+        .with_multi_column_stats(multi_column_stats.collect())
+        .with_column_widths(input_stats.column_widths().clone())
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LogicalFilter {
     predicate: Rc<dyn ScalarExpression>,
 }
@@ -51,7 +116,8 @@ impl LogicalOperator<Demo> for LogicalFilter {
     }
 
     fn derive_statistics(&self, _md_accessor: &MdAccessor, input_stats: &[Rc<dyn Stats>]) -> Rc<dyn Stats> {
-        input_stats[0].clone()
+        let input_stats = input_stats[0].as_any().downcast_ref::<Statistics>().unwrap();
+        Rc::new(derive_filter_stats(input_stats, &self.split_predicate()))
     }
 
     fn derive_output_columns(&self, inputs: &[Plan], column_set: &mut ColumnRefSet) {
@@ -1,22 +1,28 @@
-use crate::expression::ColumnVar;
+use crate::expression::{implies, ColumnVar};
 use crate::metadata::MdAccessor;
+use crate::operator::logical_filter::{derive_filter_stats, split_predicate};
 use crate::operator::logical_scan::{derive_scan_stats, TableDesc};
 use crate::operator::OperatorId;
-use crate::statistics::{IndexMd, IndexType};
+use crate::statistics::{IndexMd, IndexType, Statistics};
 use crate::{Demo, Plan};
 use cso_core::expression::ScalarExpression;
 use cso_core::metadata::Stats;
 use cso_core::operator::LogicalOperator;
 use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct IndexDesc {
     mdid: u64,
     name: String,
     index_type: IndexType,
     key_columns: Vec<ColumnVar>,
     included_columns: Vec<ColumnVar>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    key_expressions: Vec<Rc<dyn ScalarExpression>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    predicate: Option<Rc<dyn ScalarExpression>>,
 }
 
 impl IndexDesc {
@@ -33,9 +39,34 @@ impl IndexDesc {
             index_type,
             key_columns,
             included_columns,
+            key_expressions: Vec::new(),
+            predicate: None,
         }
     }
 
+    pub fn with_key_expressions(mut self, key_expressions: Vec<Rc<dyn ScalarExpression>>) -> Self {
+        self.key_expressions = key_expressions;
+        self
+    }
+
+    /// Makes the index partial, only holding the rows satisfying `predicate`.
+    pub fn with_predicate(mut self, predicate: Option<Rc<dyn ScalarExpression>>) -> Self {
+        self.predicate = predicate;
+        self
+    }
+
+    pub fn mdid(&self) -> u64 {
+        self.mdid
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn index_type(&self) -> IndexType {
+        self.index_type
+    }
+
     pub fn key_columns(&self) -> &[ColumnVar] {
         &self.key_columns
     }
@@ -44,12 +75,27 @@ impl IndexDesc {
         &self.included_columns
     }
 
+    pub fn key_expressions(&self) -> &[Rc<dyn ScalarExpression>] {
+        &self.key_expressions
+    }
+
+    /// The predicate of a partial index.
+    pub fn predicate(&self) -> Option<&Rc<dyn ScalarExpression>> {
+        self.predicate.as_ref()
+    }
+
     pub fn key_columns_count(&self) -> usize {
         self.key_columns.len()
     }
+
+    /// Returns whether the index includes all the columns, so they are read without fetching
+    /// the rows from the table.
+    pub fn covers(&self, columns: &[ColumnVar]) -> bool {
+        columns.iter().all(|column| self.included_columns.contains(column))
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LogicalIndexScan {
     index_desc: IndexDesc,
     table_desc: TableDesc,
@@ -70,11 +116,21 @@ impl LogicalIndexScan {
             index_md.index_type(),
             index_md.key_columns().to_vec(),
             index_md.included_columns().to_vec(),
-        );
+        )
+        .with_key_expressions(index_md.key_expressions().to_vec())
+        .with_predicate(index_md.predicate().cloned());
+        Self::from_index_desc(table_desc, index_desc, output_columns, predicate)
+    }
 
+    pub fn from_index_desc(
+        table_desc: TableDesc,
+        index_desc: IndexDesc,
+        output_columns: Vec<ColumnVar>,
+        predicate: Rc<dyn ScalarExpression>,
+    ) -> Self {
         Self {
             index_desc,
-            table_desc: table_desc.clone(),
+            table_desc,
             output_columns,
             predicate,
         }
@@ -107,10 +163,19 @@ impl LogicalOperator<Demo> for LogicalIndexScan {
     }
 
     fn derive_statistics(&self, md_accessor: &MdAccessor, input_stats: &[Rc<dyn Stats>]) -> Rc<dyn Stats> {
-        let base_table_stats = derive_scan_stats(md_accessor, input_stats, self.table_desc());
-
-        // todo: derive index scan stats from base_table_stats and index desc.
-        base_table_stats
+        let table_stats = derive_scan_stats(md_accessor, input_stats, self.table_desc(), self.output_columns());
+        let table_stats = table_stats.as_any().downcast_ref::<Statistics>().unwrap();
+
+        // the rows looked up, among the rows a partial index holds, unless the lookup only finds
+        // rows the index holds anyway
+        let mut predicates = vec![];
+        split_predicate(&self.predicate, &mut predicates);
+        if let Some(index_predicate) = self.index_desc.predicate() {
+            if !implies(&predicates, index_predicate.as_ref()) {
+                predicates.push(index_predicate.clone());
+            }
+        }
+        Rc::new(derive_filter_stats(table_stats, &predicates))
     }
 
     fn derive_output_columns(&self, inputs: &[Plan], column_set: &mut ColumnRefSet) {
@@ -0,0 +1,163 @@
+use crate::expression::ColumnVar;
+use crate::metadata::{MdAccessor, MdAccessorExt};
+use crate::operator::logical_scan::{derive_relation_stats, TableDesc};
+use crate::operator::OperatorId;
+use crate::partition::Partition;
+use crate::statistics::{ColumnStats, EstimateSource, Statistics};
+use crate::{Demo, Plan};
+use cso_core::expression::ScalarExpression;
+use cso_core::metadata::Stats;
+use cso_core::operator::LogicalOperator;
+use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartitionDesc {
+    name: String,
+    rel_stats_mdid: u64,
+}
+
+impl PartitionDesc {
+    pub fn new(name: String, rel_stats_mdid: u64) -> Self {
+        Self { name, rel_stats_mdid }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn rel_stats_mdid(&self) -> u64 {
+        self.rel_stats_mdid
+    }
+}
+
+impl From<&Partition> for PartitionDesc {
+    fn from(partition: &Partition) -> Self {
+        PartitionDesc::new(partition.name().to_string(), partition.rel_stats_mdid())
+    }
+}
+
+/// Scans the selected partitions of a partitioned table.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LogicalPartitionScan {
+    table_desc: TableDesc,
+    output_columns: Vec<ColumnVar>,
+    partitions: Vec<PartitionDesc>,
+}
+
+impl LogicalPartitionScan {
+    pub fn new(table_desc: TableDesc, output_columns: Vec<ColumnVar>, partitions: Vec<PartitionDesc>) -> Self {
+        LogicalPartitionScan {
+            table_desc,
+            output_columns,
+            partitions,
+        }
+    }
+
+    pub fn table_desc(&self) -> &TableDesc {
+        &self.table_desc
+    }
+
+    pub fn output_columns(&self) -> &[ColumnVar] {
+        &self.output_columns
+    }
+
+    pub fn partitions(&self) -> &[PartitionDesc] {
+        &self.partitions
+    }
+}
+
+/// Derives the statistics of scanning `output_columns` of the partitions, combining the
+/// statistics of each partition.
+pub fn derive_partition_scan_stats(
+    md_accessor: &MdAccessor,
+    input_stats: &[Rc<dyn Stats>],
+    table_desc: &TableDesc,
+    output_columns: &[ColumnVar],
+    partitions: &[PartitionDesc],
+) -> Rc<dyn Stats> {
+    debug_assert!(input_stats.is_empty());
+
+    let partition_stats = partitions
+        .iter()
+        .map(|partition| {
+            let rel_stats = md_accessor
+                .retrieve_relation_stats(partition.rel_stats_mdid())
+                .unwrap_or_else(|err| panic!("{err}"));
+            derive_relation_stats(md_accessor, &rel_stats, table_desc, output_columns)
+        })
+        .collect::<Vec<_>>();
+    if let [stats] = partition_stats.as_slice() {
+        return Rc::new(stats.clone());
+    }
+
+    let output_row_count = partition_stats.iter().map(|stats| stats.output_row_count()).sum();
+    let mut column_stats = BTreeMap::new();
+    for column in output_columns {
+        // columns not analyzed in every partition are left without statistics
+        let parts = partition_stats
+            .iter()
+            .map(|stats| Some((stats.output_row_count(), stats.column_stats_of(column)?)))
+            .collect::<Option<Vec<_>>>();
+        let Some(parts) = parts.filter(|parts| !parts.is_empty()) else {
+            continue;
+        };
+
+        let mut merged = ColumnStats::merge(&parts);
+        let sketch_mdids = parts
+            .iter()
+            .map(|(_, stats)| stats.sketch_mdid())
+            .collect::<Option<Vec<_>>>();
+        if let Some(sketch_mdids) = sketch_mdids {
+            let mut sketches = sketch_mdids.iter().map(|md_id| {
+                md_accessor
+                    .retrieve_sketch(*md_id)
+                    .unwrap_or_else(|err| panic!("{err}"))
+            });
+            let mut sketch = sketches.next().unwrap();
+            sketches.for_each(|other| sketch.merge(&other));
+            merged = merged.with_ndv(sketch.estimate());
+        }
+        column_stats.insert(column.id(), merged);
+    }
+    // the partitions share the columns of the table
+    let column_widths = partition_stats
+        .first()
+        .map(|stats| stats.column_widths().clone())
+        .unwrap_or_default();
+    let source = partition_stats
+        .iter()
+        .map(|stats| stats.source())
+        .min()
+        .unwrap_or(EstimateSource::BaseStats);
+    Rc::new(Statistics::new(output_row_count, source, column_stats).with_column_widths(column_widths))
+}
+
+impl LogicalOperator<Demo> for LogicalPartitionScan {
+    fn name(&self) -> &str {
+        "logical partition scan"
+    }
+
+    fn operator_id(&self) -> &OperatorId {
+        &OperatorId::LogicalPartitionScan
+    }
+
+    fn derive_statistics(&self, md_accessor: &MdAccessor, input_stats: &[Rc<dyn Stats>]) -> Rc<dyn Stats> {
+        derive_partition_scan_stats(
+            md_accessor,
+            input_stats,
+            self.table_desc(),
+            self.output_columns(),
+            self.partitions(),
+        )
+    }
+
+    fn derive_output_columns(&self, inputs: &[Plan], column_set: &mut ColumnRefSet) {
+        debug_assert!(inputs.is_empty());
+        self.output_columns
+            .iter()
+            .for_each(|expr| expr.derive_used_columns(column_set));
+    }
+}
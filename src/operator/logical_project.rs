@@ -1,13 +1,15 @@
 use crate::metadata::MdAccessor;
 use crate::operator::OperatorId;
+use crate::statistics::Statistics;
 use crate::{Demo, Plan};
 use cso_core::expression::ScalarExpression;
 use cso_core::metadata::Stats;
 use cso_core::operator::LogicalOperator;
 use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LogicalProject {
     project: Vec<Rc<dyn ScalarExpression>>,
 }
@@ -32,7 +34,12 @@ impl LogicalOperator<Demo> for LogicalProject {
     }
 
     fn derive_statistics(&self, _md_accessor: &MdAccessor, input_stats: &[Rc<dyn Stats>]) -> Rc<dyn Stats> {
-        input_stats[0].clone()
+        let input_stats = input_stats[0].as_any().downcast_ref::<Statistics>().unwrap();
+        let mut columns = ColumnRefSet::new();
+        self.project
+            .iter()
+            .for_each(|scalar| scalar.derive_used_columns(&mut columns));
+        Rc::new(input_stats.project(&columns))
     }
 
     fn derive_output_columns(&self, inputs: &[Plan], column_set: &mut ColumnRefSet) {
@@ -1,30 +1,52 @@
 use crate::expression::ColumnVar;
-use crate::metadata::MdAccessor;
+use crate::metadata::{MdAccessor, MdAccessorExt};
 use crate::operator::OperatorId;
-use crate::statistics::{RelationMetadata, RelationStats, Statistics};
+use crate::statistics::{EstimateSource, RelationStats, Statistics};
 use crate::{Demo, Plan};
 use cso_core::expression::ScalarExpression;
 use cso_core::metadata::Stats;
 use cso_core::operator::LogicalOperator;
 use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::rc::Rc;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TableDesc {
     md_id: u64,
+    /// Column id of every column of the table by position, empty if the ids are the positions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    column_ids: Vec<u32>,
 }
 
 impl TableDesc {
     pub const fn new(md_id: u64) -> Self {
-        Self { md_id }
+        Self {
+            md_id,
+            column_ids: Vec::new(),
+        }
+    }
+
+    pub fn with_column_ids(mut self, column_ids: Vec<u32>) -> Self {
+        self.column_ids = column_ids;
+        self
     }
 
     pub fn md_id(&self) -> u64 {
         self.md_id
     }
+
+    /// Returns the column of the table at `position`.
+    pub fn column(&self, position: usize) -> ColumnVar {
+        match self.column_ids.get(position) {
+            Some(id) => ColumnVar::new(*id),
+            None if self.column_ids.is_empty() => ColumnVar::new(position as u32),
+            None => panic!("table {} has no column at position {position}", self.md_id),
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LogicalScan {
     table_desc: TableDesc,
     output_columns: Vec<ColumnVar>,
@@ -47,41 +69,102 @@ impl LogicalScan {
     }
 }
 
+/// Derives the statistics of scanning `output_columns` of the table.
 pub fn derive_scan_stats(
     md_accessor: &MdAccessor,
     input_stats: &[Rc<dyn Stats>],
     table_desc: &TableDesc,
+    output_columns: &[ColumnVar],
 ) -> Rc<dyn Stats> {
     debug_assert!(input_stats.is_empty());
 
-    let relation_md_id = table_desc.md_id();
     let rel_md = md_accessor
-        .retrieve_metadata(&relation_md_id)
-        .expect("Missing metadata");
-    let rel_md = rel_md
-        .downcast_ref::<RelationMetadata>()
-        .expect("RelationMetadata expected");
-
-    let rel_stats_md_id = rel_md.rel_stats_mdid();
+        .retrieve_relation(table_desc.md_id())
+        .unwrap_or_else(|err| panic!("{err}"));
     let rel_stats = md_accessor
-        .retrieve_metadata(&rel_stats_md_id)
-        .expect("Missing metadata");
-    let rel_stats = rel_stats
-        .downcast_ref::<RelationStats>()
-        .expect("RelationStats expected");
+        .relation_stats(&rel_md)
+        .unwrap_or_else(|err| panic!("{err}"));
+    Rc::new(derive_relation_stats(
+        md_accessor,
+        &rel_stats,
+        table_desc,
+        output_columns,
+    ))
+}
 
+/// Derives the statistics of `output_columns` of the rows `rel_stats` describes, which are the
+/// rows of the table or of some of its partitions. If the options override the row count of the
+/// table, the rows are scaled to it.
+pub fn derive_relation_stats(
+    md_accessor: &MdAccessor,
+    rel_stats: &RelationStats,
+    table_desc: &TableDesc,
+    output_columns: &[ColumnVar],
+) -> Statistics {
     let output_row_count = rel_stats.rows();
 
-    let mut column_stats = Vec::new();
+    let rel_md = md_accessor
+        .retrieve_relation(table_desc.md_id())
+        .unwrap_or_else(|err| panic!("{err}"));
+    let column_widths = rel_md
+        .column_metadata()
+        .iter()
+        .enumerate()
+        .map(|(position, column_md)| (table_desc.column(position), column_md.width()))
+        .filter(|(column, _)| output_columns.contains(column))
+        .map(|(column, width)| (column.id(), width))
+        .collect();
+
+    let mut column_stats = BTreeMap::new();
     for col_stats_md_id in rel_stats.col_stat_mdids() {
-        let col_stats = md_accessor
-            .retrieve_metadata(col_stats_md_id)
-            .expect("Missing metadata");
-        column_stats.push(col_stats);
+        let mut col_stats = md_accessor
+            .retrieve_column_stats(*col_stats_md_id)
+            .unwrap_or_else(|err| panic!("{err}"));
+        let column = table_desc.column(col_stats.col_id());
+        if !output_columns.contains(&column) {
+            continue;
+        }
+        if let (None, Some(sketch_mdid)) = (col_stats.ndv(), col_stats.sketch_mdid()) {
+            let sketch = md_accessor
+                .retrieve_sketch(sketch_mdid)
+                .unwrap_or_else(|err| panic!("{err}"));
+            col_stats = col_stats.with_ndv(sketch.estimate());
+        }
+        column_stats.insert(column.id(), col_stats);
     }
 
-    let stats = Statistics::new(output_row_count, column_stats);
-    Rc::new(stats)
+    let mut multi_column_stats = Vec::new();
+    for md_id in rel_stats.multi_col_stat_mdids() {
+        let stats = md_accessor
+            .retrieve_multi_column_stats(*md_id)
+            .unwrap_or_else(|err| panic!("{err}"));
+        let columns = stats
+            .col_ids()
+            .iter()
+            .map(|col_id| table_desc.column(*col_id))
+            .collect::<Vec<_>>();
+        if columns.iter().all(|column| output_columns.contains(column)) {
+            multi_column_stats.push((columns, stats));
+        }
+    }
+
+    let stats = Statistics::new(output_row_count, EstimateSource::BaseStats, column_stats)
+        .with_multi_column_stats(multi_column_stats)
+        .with_column_widths(column_widths);
+    match md_accessor.relation_row_count(&table_desc.md_id()) {
+        Some(table_rows) => {
+            let analyzed_rows = md_accessor
+                .relation_stats(&rel_md)
+                .unwrap_or_else(|err| panic!("{err}"))
+                .rows();
+            let rows = match analyzed_rows {
+                0 => table_rows,
+                _ => (output_row_count as f64 * table_rows as f64 / analyzed_rows as f64).round() as u64,
+            };
+            stats.scale(rows)
+        }
+        None => stats,
+    }
 }
 
 impl LogicalOperator<Demo> for LogicalScan {
@@ -94,7 +177,7 @@ impl LogicalOperator<Demo> for LogicalScan {
     }
 
     fn derive_statistics(&self, md_accessor: &MdAccessor, input_stats: &[Rc<dyn Stats>]) -> Rc<dyn Stats> {
-        derive_scan_stats(md_accessor, input_stats, self.table_desc())
+        derive_scan_stats(md_accessor, input_stats, self.table_desc(), self.output_columns())
     }
 
     fn derive_output_columns(&self, inputs: &[Plan], column_set: &mut ColumnRefSet) {
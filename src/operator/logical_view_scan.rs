@@ -0,0 +1,228 @@
+use crate::expression::{remap_columns, ColumnVar};
+use crate::metadata::{MdAccessor, MdAccessorExt};
+use crate::operator::logical_filter::LogicalFilter;
+use crate::operator::logical_index_scan::{IndexDesc, LogicalIndexScan};
+use crate::operator::logical_partition_scan::LogicalPartitionScan;
+use crate::operator::logical_project::LogicalProject;
+use crate::operator::logical_scan::{LogicalScan, TableDesc};
+use crate::operator::{LogicalOperator, OperatorId};
+use crate::{Demo, LogicalPlan, Plan};
+use cso_core::expression::ScalarExpression;
+use cso_core::metadata::Stats;
+use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// References a view, returning its output columns as `output_columns`. The reference is
+/// replaced by the definition of the view when the memo is initialized.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LogicalViewScan {
+    view_md_id: u64,
+    output_columns: Vec<ColumnVar>,
+}
+
+impl LogicalViewScan {
+    pub fn new(view_md_id: u64, output_columns: Vec<ColumnVar>) -> Self {
+        LogicalViewScan {
+            view_md_id,
+            output_columns,
+        }
+    }
+
+    pub fn view_md_id(&self) -> u64 {
+        self.view_md_id
+    }
+
+    pub fn output_columns(&self) -> &[ColumnVar] {
+        &self.output_columns
+    }
+}
+
+impl cso_core::operator::LogicalOperator<Demo> for LogicalViewScan {
+    fn name(&self) -> &str {
+        "logical view scan"
+    }
+
+    fn operator_id(&self) -> &OperatorId {
+        &OperatorId::LogicalViewScan
+    }
+
+    fn derive_statistics(&self, _md_accessor: &MdAccessor, _input_stats: &[Rc<dyn Stats>]) -> Rc<dyn Stats> {
+        unreachable!("views are expanded before optimization")
+    }
+
+    fn derive_output_columns(&self, inputs: &[Plan], column_set: &mut ColumnRefSet) {
+        debug_assert!(inputs.is_empty());
+        self.output_columns
+            .iter()
+            .for_each(|column| column.derive_used_columns(column_set));
+    }
+
+    fn expand(&self, md_accessor: &MdAccessor, next_column_id: &mut u32) -> Option<LogicalPlan> {
+        let mut expanding = vec![];
+        Some(expand_view(
+            md_accessor,
+            self.view_md_id,
+            &self.output_columns,
+            next_column_id,
+            &mut expanding,
+        ))
+    }
+}
+
+/// Expands the view returning `output_columns`, and the views its definition references.
+/// `expanding` holds the views being expanded, which the definition must not reference.
+fn expand_view(
+    md_accessor: &MdAccessor,
+    view_md_id: u64,
+    output_columns: &[ColumnVar],
+    next_column_id: &mut u32,
+    expanding: &mut Vec<u64>,
+) -> LogicalPlan {
+    let view_md = md_accessor
+        .retrieve_view(view_md_id)
+        .unwrap_or_else(|err| panic!("{err}"));
+    assert!(
+        !expanding.contains(&view_md_id),
+        "view {} is defined in terms of itself",
+        view_md.name()
+    );
+    assert_eq!(
+        view_md.output_columns().len(),
+        output_columns.len(),
+        "reference to view {} must return all its columns",
+        view_md.name()
+    );
+
+    // The output columns of the view take the ids of the reference, and the other columns of
+    // the definition fresh ones, so each reference to the view has columns of its own.
+    let column_ids = view_md
+        .output_columns()
+        .iter()
+        .zip(output_columns)
+        .map(|(view_column, column)| (view_column.id(), column.id()))
+        .collect();
+    expanding.push(view_md_id);
+    let mut column_mapping = ColumnMapping {
+        column_ids,
+        next_column_id,
+        expanding,
+    };
+    let plan = column_mapping.remap_plan(md_accessor, view_md.definition());
+    expanding.pop();
+    plan
+}
+
+struct ColumnMapping<'a> {
+    column_ids: HashMap<u32, u32>,
+    next_column_id: &'a mut u32,
+    expanding: &'a mut Vec<u64>,
+}
+
+impl ColumnMapping<'_> {
+    fn remap(&mut self, id: u32) -> u32 {
+        *self.column_ids.entry(id).or_insert_with(|| {
+            let id = *self.next_column_id;
+            *self.next_column_id += 1;
+            id
+        })
+    }
+
+    fn remap_column(&mut self, column: &ColumnVar) -> ColumnVar {
+        ColumnVar::new(self.remap(column.id()))
+    }
+
+    fn remap_columns(&mut self, columns: &[ColumnVar]) -> Vec<ColumnVar> {
+        columns.iter().map(|column| self.remap_column(column)).collect()
+    }
+
+    /// Remaps the columns of the table, which the definition may not all use.
+    fn remap_table_desc(&mut self, md_accessor: &MdAccessor, table_desc: &TableDesc) -> TableDesc {
+        let relation_md = md_accessor
+            .retrieve_relation(table_desc.md_id())
+            .unwrap_or_else(|err| panic!("{err}"));
+        let column_ids = (0..relation_md.column_metadata().len())
+            .map(|position| self.remap(table_desc.column(position).id()))
+            .collect();
+        TableDesc::new(table_desc.md_id()).with_column_ids(column_ids)
+    }
+
+    fn remap_index_desc(&mut self, index_desc: &IndexDesc) -> IndexDesc {
+        let key_expressions = index_desc
+            .key_expressions()
+            .iter()
+            .map(|expr| self.remap_expression(expr.as_ref()))
+            .collect();
+        let predicate = index_desc
+            .predicate()
+            .map(|predicate| self.remap_expression(predicate.as_ref()));
+        IndexDesc::new(
+            index_desc.mdid(),
+            index_desc.name().to_string(),
+            index_desc.index_type(),
+            self.remap_columns(index_desc.key_columns()),
+            self.remap_columns(index_desc.included_columns()),
+        )
+        .with_key_expressions(key_expressions)
+        .with_predicate(predicate)
+    }
+
+    fn remap_expression(&mut self, expr: &dyn ScalarExpression) -> Rc<dyn ScalarExpression> {
+        remap_columns(expr, &mut |id| self.remap(id)).into()
+    }
+
+    fn remap_plan(&mut self, md_accessor: &MdAccessor, plan: &LogicalPlan) -> LogicalPlan {
+        let op = plan.operator();
+        let op: Rc<LogicalOperator> = if let Some(scan) = op.downcast_ref::<LogicalScan>() {
+            let table_desc = self.remap_table_desc(md_accessor, scan.table_desc());
+            Rc::new(LogicalScan::new(table_desc, self.remap_columns(scan.output_columns())))
+        } else if let Some(scan) = op.downcast_ref::<LogicalPartitionScan>() {
+            let table_desc = self.remap_table_desc(md_accessor, scan.table_desc());
+            let output_columns = self.remap_columns(scan.output_columns());
+            Rc::new(LogicalPartitionScan::new(
+                table_desc,
+                output_columns,
+                scan.partitions().to_vec(),
+            ))
+        } else if let Some(scan) = op.downcast_ref::<LogicalIndexScan>() {
+            let table_desc = self.remap_table_desc(md_accessor, scan.table_desc());
+            let index_desc = self.remap_index_desc(scan.index_desc());
+            let output_columns = self.remap_columns(scan.output_columns());
+            let predicate = self.remap_expression(scan.predicate().as_ref());
+            Rc::new(LogicalIndexScan::from_index_desc(
+                table_desc,
+                index_desc,
+                output_columns,
+                predicate,
+            ))
+        } else if let Some(filter) = op.downcast_ref::<LogicalFilter>() {
+            Rc::new(LogicalFilter::new(self.remap_expression(filter.predicate().as_ref())))
+        } else if let Some(project) = op.downcast_ref::<LogicalProject>() {
+            let project = project
+                .project()
+                .iter()
+                .map(|expr| self.remap_expression(expr.as_ref()));
+            Rc::new(LogicalProject::new(project.collect()))
+        } else if let Some(view_scan) = op.downcast_ref::<LogicalViewScan>() {
+            debug_assert!(plan.inputs().is_empty());
+            let output_columns = self.remap_columns(view_scan.output_columns());
+            return expand_view(
+                md_accessor,
+                view_scan.view_md_id(),
+                &output_columns,
+                self.next_column_id,
+                self.expanding,
+            );
+        } else {
+            unreachable!("{} is not a logical operator", op.name());
+        };
+
+        let inputs = plan
+            .inputs()
+            .iter()
+            .map(|input| self.remap_plan(md_accessor, input))
+            .collect();
+        LogicalPlan::new(op, inputs, plan.required_properties().to_vec())
+    }
+}
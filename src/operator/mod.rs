@@ -2,10 +2,13 @@ use crate::Demo;
 
 pub mod logical_filter;
 pub mod logical_index_scan;
+pub mod logical_partition_scan;
 pub mod logical_project;
 pub mod logical_scan;
+pub mod logical_view_scan;
 pub mod physical_filter;
 pub mod physical_index_scan;
+pub mod physical_partition_scan;
 pub mod physical_project;
 pub mod physical_scan;
 pub mod physical_sort;
@@ -20,10 +23,13 @@ pub enum OperatorId {
     LogicalFilter,
     LogicalProject,
     LogicalIndexScan,
+    LogicalPartitionScan,
+    LogicalViewScan,
 
     PhysicalScan,
     PhysicalIndexScan,
     PhysicalFilter,
     PhysicalProject,
     PhysicalSort,
+    PhysicalPartitionScan,
 }
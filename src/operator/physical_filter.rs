@@ -6,9 +6,10 @@ use cso_core::cost::Cost;
 use cso_core::expression::ScalarExpression;
 use cso_core::metadata::Stats;
 use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PhysicalFilter {
     predicate: Rc<dyn ScalarExpression>,
 }
@@ -58,6 +59,10 @@ impl cso_core::operator::PhysicalOperator<Demo> for PhysicalFilter {
             None => false,
         }
     }
+
+    fn derive_used_columns(&self, column_set: &mut ColumnRefSet) {
+        self.predicate.derive_used_columns(column_set);
+    }
 }
 
 impl PartialEq for PhysicalFilter {
@@ -1,4 +1,7 @@
-use crate::cost::{COST_INDEX_FILTER_COST_UNIT, COST_INDEX_SCAN_TUP_COST_UNIT, COST_INDEX_SCAN_TUP_RANDOM_FACTOR};
+use crate::cost::{
+    COST_HEAP_FETCH_RANDOM_COST_UNIT, COST_INDEX_FILTER_COST_UNIT, COST_INDEX_SCAN_TUP_COST_UNIT,
+    COST_INDEX_SCAN_TUP_RANDOM_FACTOR,
+};
 use crate::expression::ColumnVar;
 use crate::operator::logical_index_scan::IndexDesc;
 use crate::operator::logical_scan::TableDesc;
@@ -6,13 +9,16 @@ use crate::operator::physical_sort::{OrderSpec, Ordering};
 use crate::operator::{OperatorId, PhysicalOperator};
 use crate::property::sort_property::SortProperty;
 use crate::property::PhysicalProperties;
+use crate::statistics::Statistics;
 use crate::Demo;
 use cso_core::cost::Cost;
 use cso_core::expression::ScalarExpression;
 use cso_core::metadata::Stats;
+use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PhysicalIndexScan {
     index_desc: IndexDesc,
     table_desc: TableDesc,
@@ -34,6 +40,22 @@ impl PhysicalIndexScan {
             predicate,
         }
     }
+
+    pub fn index_desc(&self) -> &IndexDesc {
+        &self.index_desc
+    }
+
+    pub fn table_desc(&self) -> &TableDesc {
+        &self.table_desc
+    }
+
+    pub fn output_columns(&self) -> &[ColumnVar] {
+        &self.output_columns
+    }
+
+    pub fn predicate(&self) -> &Rc<dyn ScalarExpression> {
+        &self.predicate
+    }
 }
 
 impl cso_core::operator::PhysicalOperator<Demo> for PhysicalIndexScan {
@@ -48,6 +70,9 @@ impl cso_core::operator::PhysicalOperator<Demo> for PhysicalIndexScan {
     fn derive_output_properties(&self, child_props: &[Rc<PhysicalProperties>]) -> Rc<PhysicalProperties> {
         debug_assert!(child_props.is_empty());
         let key_columns = self.index_desc.key_columns();
+        if !self.index_desc.index_type().is_ordered() || key_columns.is_empty() {
+            return Rc::new(PhysicalProperties::new());
+        }
 
         let mut order_desc = vec![];
         for key in key_columns {
@@ -65,10 +90,18 @@ impl cso_core::operator::PhysicalOperator<Demo> for PhysicalIndexScan {
     fn compute_cost(&self, stats: Option<&dyn Stats>) -> Cost {
         debug_assert!(stats.is_some());
 
+        // descend the index once, then walk the index rows looked up
+        let stats = stats.unwrap().as_any().downcast_ref::<Statistics>().unwrap();
         let index_key_column_count = self.index_desc.key_columns_count() as f64;
-        let cost_per_index_row = index_key_column_count * COST_INDEX_FILTER_COST_UNIT + COST_INDEX_SCAN_TUP_COST_UNIT;
-        let row_count = stats.unwrap().output_row_count() as f64;
-        Cost::new(row_count * cost_per_index_row + COST_INDEX_SCAN_TUP_RANDOM_FACTOR)
+        let row_width = stats.row_width() as f64;
+        let mut cost_per_row =
+            index_key_column_count * COST_INDEX_FILTER_COST_UNIT + row_width * COST_INDEX_SCAN_TUP_COST_UNIT;
+        if !self.index_desc.covers(&self.output_columns) {
+            // the other columns are fetched from the table, each row from a random page
+            cost_per_row += COST_HEAP_FETCH_RANDOM_COST_UNIT;
+        }
+        let row_count = stats.output_row_count() as f64;
+        Cost::new(row_count * cost_per_row + COST_INDEX_SCAN_TUP_RANDOM_FACTOR)
     }
 
     fn equal(&self, other: &PhysicalOperator) -> bool {
@@ -77,6 +110,17 @@ impl cso_core::operator::PhysicalOperator<Demo> for PhysicalIndexScan {
             None => false,
         }
     }
+
+    fn derive_used_columns(&self, column_set: &mut ColumnRefSet) {
+        self.predicate.derive_used_columns(column_set);
+    }
+
+    fn derive_output_columns(&self, input_columns: &[ColumnRefSet], column_set: &mut ColumnRefSet) {
+        debug_assert!(input_columns.is_empty());
+        self.output_columns
+            .iter()
+            .for_each(|column| column.derive_used_columns(column_set));
+    }
 }
 
 impl PartialEq for PhysicalIndexScan {
@@ -0,0 +1,85 @@
+use crate::cost::{COST_INIT_SCAN_FACTOR, COST_TABLE_SCAN_COST_UNIT};
+use crate::expression::ColumnVar;
+use crate::operator::logical_partition_scan::PartitionDesc;
+use crate::operator::logical_scan::TableDesc;
+use crate::operator::{OperatorId, PhysicalOperator};
+use crate::property::PhysicalProperties;
+use crate::statistics::Statistics;
+use crate::Demo;
+use cso_core::cost::Cost;
+use cso_core::expression::ScalarExpression;
+use cso_core::metadata::Stats;
+use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PhysicalPartitionScan {
+    table_desc: TableDesc,
+    output_columns: Vec<ColumnVar>,
+    partitions: Vec<PartitionDesc>,
+}
+
+impl PhysicalPartitionScan {
+    pub fn new(table_desc: TableDesc, output_columns: Vec<ColumnVar>, partitions: Vec<PartitionDesc>) -> Self {
+        PhysicalPartitionScan {
+            table_desc,
+            output_columns,
+            partitions,
+        }
+    }
+
+    pub fn table_desc(&self) -> &TableDesc {
+        &self.table_desc
+    }
+
+    pub fn output_columns(&self) -> &[ColumnVar] {
+        &self.output_columns
+    }
+
+    pub fn partitions(&self) -> &[PartitionDesc] {
+        &self.partitions
+    }
+}
+
+impl cso_core::operator::PhysicalOperator<Demo> for PhysicalPartitionScan {
+    fn name(&self) -> &str {
+        "physical partition scan"
+    }
+
+    fn operator_id(&self) -> &OperatorId {
+        &OperatorId::PhysicalPartitionScan
+    }
+
+    fn derive_output_properties(&self, _: &[Rc<PhysicalProperties>]) -> Rc<PhysicalProperties> {
+        Rc::new(PhysicalProperties::new())
+    }
+
+    fn required_properties(&self, _input_prop: Rc<PhysicalProperties>) -> Vec<Vec<Rc<PhysicalProperties>>> {
+        vec![vec![]]
+    }
+
+    fn compute_cost(&self, stats: Option<&dyn Stats>) -> Cost {
+        debug_assert!(stats.is_some());
+
+        // the pruned partitions are never read, so only the rows of the selected ones count
+        let stats = stats.unwrap().as_any().downcast_ref::<Statistics>().unwrap();
+        let row_count = stats.output_row_count() as f64;
+        let row_width = stats.row_width() as f64;
+        Cost::new(COST_INIT_SCAN_FACTOR + row_count * row_width * COST_TABLE_SCAN_COST_UNIT)
+    }
+
+    fn equal(&self, other: &PhysicalOperator) -> bool {
+        match other.downcast_ref::<PhysicalPartitionScan>() {
+            Some(other) => self.eq(other),
+            None => false,
+        }
+    }
+
+    fn derive_output_columns(&self, input_columns: &[ColumnRefSet], column_set: &mut ColumnRefSet) {
+        debug_assert!(input_columns.is_empty());
+        self.output_columns
+            .iter()
+            .for_each(|column| column.derive_used_columns(column_set));
+    }
+}
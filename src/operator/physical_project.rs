@@ -1,20 +1,27 @@
 use crate::cost::COST_TUP_DEFAULT_PROC_COST_UNIT;
 use crate::operator::{OperatorId, PhysicalOperator};
+use crate::property::sort_property::SortProperty;
 use crate::property::PhysicalProperties;
 use crate::Demo;
 use cso_core::cost::Cost;
 use cso_core::expression::ScalarExpression;
 use cso_core::metadata::Stats;
+use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct PhysicalProject {
-    _project: Vec<Rc<dyn ScalarExpression>>,
+    project: Vec<Rc<dyn ScalarExpression>>,
 }
 
 impl PhysicalProject {
     pub fn new(project: Vec<Rc<dyn ScalarExpression>>) -> Self {
-        PhysicalProject { _project: project }
+        PhysicalProject { project }
+    }
+
+    pub fn project(&self) -> &[Rc<dyn ScalarExpression>] {
+        &self.project
     }
 }
 
@@ -32,7 +39,24 @@ impl cso_core::operator::PhysicalOperator<Demo> for PhysicalProject {
     }
 
     fn required_properties(&self, input_prop: Rc<PhysicalProperties>) -> Vec<Vec<Rc<PhysicalProperties>>> {
-        vec![vec![Rc::new(PhysicalProperties::new())], vec![input_prop]]
+        // rows can only be sorted above the project on the columns it returns
+        let mut columns = ColumnRefSet::new();
+        self.derive_used_columns(&mut columns);
+        let sortable = input_prop
+            .properties()
+            .iter()
+            .filter_map(|property| property.downcast_ref::<SortProperty>())
+            .all(|sort| {
+                sort.order_spec()
+                    .order_desc
+                    .iter()
+                    .all(|ordering| columns.contains(ordering.key.id()))
+            });
+        if sortable {
+            vec![vec![Rc::new(PhysicalProperties::new())], vec![input_prop]]
+        } else {
+            vec![vec![input_prop]]
+        }
     }
 
     fn compute_cost(&self, stats: Option<&dyn Stats>) -> Cost {
@@ -48,4 +72,16 @@ impl cso_core::operator::PhysicalOperator<Demo> for PhysicalProject {
             None => false,
         }
     }
+
+    fn derive_used_columns(&self, column_set: &mut ColumnRefSet) {
+        self.project
+            .iter()
+            .for_each(|scalar| scalar.derive_used_columns(column_set));
+    }
+
+    /// Only returns the projected columns, the others are dropped.
+    fn derive_output_columns(&self, input_columns: &[ColumnRefSet], column_set: &mut ColumnRefSet) {
+        debug_assert_eq!(input_columns.len(), 1);
+        self.derive_used_columns(column_set);
+    }
 }
@@ -3,12 +3,16 @@ use crate::expression::ColumnVar;
 use crate::operator::logical_scan::TableDesc;
 use crate::operator::{OperatorId, PhysicalOperator};
 use crate::property::PhysicalProperties;
+use crate::statistics::Statistics;
 use crate::Demo;
 use cso_core::cost::Cost;
+use cso_core::expression::ScalarExpression;
 use cso_core::metadata::Stats;
+use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PhysicalScan {
     table_desc: TableDesc,
     output_columns: Vec<ColumnVar>,
@@ -21,6 +25,14 @@ impl PhysicalScan {
             output_columns,
         }
     }
+
+    pub fn table_desc(&self) -> &TableDesc {
+        &self.table_desc
+    }
+
+    pub fn output_columns(&self) -> &[ColumnVar] {
+        &self.output_columns
+    }
 }
 
 impl cso_core::operator::PhysicalOperator<Demo> for PhysicalScan {
@@ -43,8 +55,10 @@ impl cso_core::operator::PhysicalOperator<Demo> for PhysicalScan {
     fn compute_cost(&self, stats: Option<&dyn Stats>) -> Cost {
         debug_assert!(stats.is_some());
 
-        let row_count = stats.unwrap().output_row_count() as f64;
-        Cost::new(COST_INIT_SCAN_FACTOR + row_count * COST_TABLE_SCAN_COST_UNIT)
+        let stats = stats.unwrap().as_any().downcast_ref::<Statistics>().unwrap();
+        let row_count = stats.output_row_count() as f64;
+        let row_width = stats.row_width() as f64;
+        Cost::new(COST_INIT_SCAN_FACTOR + row_count * row_width * COST_TABLE_SCAN_COST_UNIT)
     }
 
     fn equal(&self, other: &PhysicalOperator) -> bool {
@@ -53,4 +67,11 @@ impl cso_core::operator::PhysicalOperator<Demo> for PhysicalScan {
             None => false,
         }
     }
+
+    fn derive_output_columns(&self, input_columns: &[ColumnRefSet], column_set: &mut ColumnRefSet) {
+        debug_assert!(input_columns.is_empty());
+        self.output_columns
+            .iter()
+            .for_each(|column| column.derive_used_columns(column_set));
+    }
 }
@@ -3,12 +3,16 @@ use crate::expression::ColumnVar;
 use crate::operator::{OperatorId, PhysicalOperator};
 use crate::property::sort_property::SortProperty;
 use crate::property::PhysicalProperties;
+use crate::statistics::Statistics;
 use crate::Demo;
 use cso_core::cost::Cost;
+use cso_core::expression::ScalarExpression;
 use cso_core::metadata::Stats;
+use cso_core::ColumnRefSet;
+use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 
-#[derive(Clone, Hash, Eq, PartialEq, Debug)]
+#[derive(Clone, Hash, Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Ordering {
     pub key: ColumnVar,
     pub ascending: bool,
@@ -25,12 +29,12 @@ impl Ordering {
     }
 }
 
-#[derive(Clone, Hash, Eq, PartialEq, Debug)]
+#[derive(Clone, Hash, Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct OrderSpec {
     pub order_desc: Vec<Ordering>,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct PhysicalSort {
     order_spec: OrderSpec,
 }
@@ -59,16 +63,18 @@ impl cso_core::operator::PhysicalOperator<Demo> for PhysicalSort {
     }
 
     fn required_properties(&self, _input_prop: Rc<PhysicalProperties>) -> Vec<Vec<Rc<PhysicalProperties>>> {
-        vec![vec![PhysicalProperties::with_property(Box::new(
-            SortProperty::with_order(self.order_spec.clone()),
-        ))]]
+        // sort establishes the order itself, any input order is fine
+        vec![vec![Rc::new(PhysicalProperties::new())]]
     }
 
     fn compute_cost(&self, stats: Option<&dyn Stats>) -> Cost {
         debug_assert!(stats.is_some());
 
-        let row_count = stats.unwrap().output_row_count().max(1) as f64;
-        Cost::new(row_count * row_count.log2() * COST_SORT_TUP_WIDTH_COST_UNIT)
+        // even a single row is copied through the sort, so it never comes for free
+        let stats = stats.unwrap().as_any().downcast_ref::<Statistics>().unwrap();
+        let row_count = stats.output_row_count().max(1) as f64;
+        let row_width = stats.row_width() as f64;
+        Cost::new(row_count * row_count.log2().max(1.0) * row_width * COST_SORT_TUP_WIDTH_COST_UNIT)
     }
 
     fn equal(&self, other: &PhysicalOperator) -> bool {
@@ -77,4 +83,11 @@ impl cso_core::operator::PhysicalOperator<Demo> for PhysicalSort {
             None => false,
         }
     }
+
+    fn derive_used_columns(&self, column_set: &mut ColumnRefSet) {
+        self.order_spec
+            .order_desc
+            .iter()
+            .for_each(|ordering| ordering.key.derive_used_columns(column_set));
+    }
 }
@@ -0,0 +1,201 @@
+//! Partitioned tables and static partition pruning.
+//!
+//! The rows of a partitioned table are split by the value of its partition key column into range
+//! or list partitions, each with its own statistics. Predicates comparing the partition key with
+//! constants rule partitions out before execution, so they are never scanned.
+
+use crate::datum::Datum;
+use crate::expression::{
+    And, ColumnVar, Const, Equal, GreaterThan, GreaterThanEqual, InList, IsNotNull, IsNull, LessThan, LessThanEqual, Or,
+};
+use cso_core::expression::ScalarExpression;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+/// The values of the partition key a partition holds.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PartitionBound {
+    /// Values in `[lower, upper)`, unbounded where `None`. Range partitions hold no nulls.
+    Range { lower: Option<Datum>, upper: Option<Datum> },
+    /// The listed values, which may include null.
+    List { values: Vec<Datum> },
+    /// The rows no other partition holds.
+    Default,
+}
+
+impl PartitionBound {
+    /// Returns whether the partition holds the rows whose key is `value`. Numbers of different
+    /// types are compared by value, so a bigint key may be compared with an integer constant.
+    pub fn contains(&self, value: &Datum) -> bool {
+        match self {
+            PartitionBound::Range { .. } if value.is_null() => false,
+            PartitionBound::Range { lower, upper } => {
+                lower.as_ref().is_none_or(|lower| lower.cmp_value(value).is_le())
+                    && upper.as_ref().is_none_or(|upper| value.cmp_value(upper).is_lt())
+            }
+            PartitionBound::List { values } => values.iter().any(|key| key.cmp_value(value).is_eq()),
+            PartitionBound::Default => true,
+        }
+    }
+
+    /// Returns whether the partition may hold a row whose key compares with `value` as
+    /// `ordering` (`Less` meaning `key < value`), or is equal to it if `or_equal`.
+    fn may_compare(&self, value: &Datum, ordering: Ordering, or_equal: bool) -> bool {
+        match self {
+            // the values of a range are dense, e.g. `[1, 2)` may hold 1.5 for all we know
+            PartitionBound::Range { lower, upper } => match ordering {
+                Ordering::Less => lower.as_ref().is_none_or(|lower| match lower.cmp_value(value) {
+                    Ordering::Less => true,
+                    Ordering::Equal => or_equal,
+                    Ordering::Greater => false,
+                }),
+                Ordering::Greater => upper.as_ref().is_none_or(|upper| value.cmp_value(upper).is_lt()),
+                Ordering::Equal => self.contains(value),
+            },
+            PartitionBound::List { values } => values.iter().filter(|key| !key.is_null()).any(|key| {
+                let key_ordering = key.cmp_value(value);
+                key_ordering == ordering || (or_equal && key_ordering.is_eq())
+            }),
+            PartitionBound::Default => true,
+        }
+    }
+
+    /// Returns whether the partition may hold rows satisfying the predicate over the partition
+    /// key `key`. Predicates that can't be evaluated on the bound are assumed to be satisfied.
+    fn may_satisfy(&self, predicate: &dyn ScalarExpression, key: &ColumnVar) -> bool {
+        if let Some(and) = predicate.downcast_ref::<And>() {
+            return and
+                .expressions()
+                .iter()
+                .all(|expr| self.may_satisfy(expr.as_ref(), key));
+        }
+        if let Some(or) = predicate.downcast_ref::<Or>() {
+            return or.expressions().iter().any(|expr| self.may_satisfy(expr.as_ref(), key));
+        }
+        if let Some(is_null) = predicate.downcast_ref::<IsNull>() {
+            return !is_key(is_null.inner(), key) || self.may_compare_null(true);
+        }
+        if let Some(is_not_null) = predicate.downcast_ref::<IsNotNull>() {
+            return !is_key(is_not_null.inner(), key) || self.may_compare_null(false);
+        }
+        if let Some(in_list) = predicate.downcast_ref::<InList>() {
+            if !is_key(in_list.expr(), key) {
+                return true;
+            }
+            return in_list.list().iter().any(|expr| match const_datum(expr.as_ref()) {
+                Some(value) => self.contains(&value),
+                None => true,
+            });
+        }
+
+        let comparison = if let Some(cmp) = predicate.downcast_ref::<Equal>() {
+            (cmp.left(), cmp.right(), Ordering::Equal, true)
+        } else if let Some(cmp) = predicate.downcast_ref::<LessThan>() {
+            (cmp.left(), cmp.right(), Ordering::Less, false)
+        } else if let Some(cmp) = predicate.downcast_ref::<LessThanEqual>() {
+            (cmp.left(), cmp.right(), Ordering::Less, true)
+        } else if let Some(cmp) = predicate.downcast_ref::<GreaterThan>() {
+            (cmp.left(), cmp.right(), Ordering::Greater, false)
+        } else if let Some(cmp) = predicate.downcast_ref::<GreaterThanEqual>() {
+            (cmp.left(), cmp.right(), Ordering::Greater, true)
+        } else {
+            return true;
+        };
+        let (left, right, ordering, or_equal) = comparison;
+        if is_key(left, key) {
+            const_datum(right).is_none_or(|value| self.may_compare(&value, ordering, or_equal))
+        } else if is_key(right, key) {
+            // `constant < key` is `key > constant`
+            const_datum(left).is_none_or(|value| self.may_compare(&value, ordering.reverse(), or_equal))
+        } else {
+            true
+        }
+    }
+
+    /// Returns whether the partition may hold rows whose key is null, or not null.
+    fn may_compare_null(&self, null: bool) -> bool {
+        match self {
+            PartitionBound::Range { .. } => !null,
+            PartitionBound::List { values } => values.iter().any(|value| value.is_null() == null),
+            PartitionBound::Default => true,
+        }
+    }
+}
+
+fn is_key(expr: &dyn ScalarExpression, key: &ColumnVar) -> bool {
+    expr.downcast_ref::<ColumnVar>() == Some(key)
+}
+
+fn const_datum(expr: &dyn ScalarExpression) -> Option<Datum> {
+    expr.downcast_ref::<Const>().map(Datum::from)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Partition {
+    name: String,
+    bound: PartitionBound,
+    rel_stats_mdid: u64,
+}
+
+impl Partition {
+    pub fn new(name: String, bound: PartitionBound, rel_stats_mdid: u64) -> Self {
+        Self {
+            name,
+            bound,
+            rel_stats_mdid,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn bound(&self) -> &PartitionBound {
+        &self.bound
+    }
+
+    /// Mdid of the `RelationStats` of the rows of the partition.
+    pub fn rel_stats_mdid(&self) -> u64 {
+        self.rel_stats_mdid
+    }
+}
+
+/// How the rows of a table are partitioned.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PartitionInfo {
+    key_col_id: usize, // Position of the partition key column in the relation
+    partitions: Vec<Partition>,
+}
+
+impl PartitionInfo {
+    pub fn new(key_col_id: usize, partitions: Vec<Partition>) -> Self {
+        let defaults = partitions
+            .iter()
+            .filter(|partition| partition.bound == PartitionBound::Default)
+            .count();
+        assert!(defaults <= 1, "a table has at most one default partition");
+        Self { key_col_id, partitions }
+    }
+
+    pub fn key_col_id(&self) -> usize {
+        self.key_col_id
+    }
+
+    pub fn partitions(&self) -> &[Partition] {
+        &self.partitions
+    }
+
+    /// Returns the positions of the partitions that may hold rows satisfying all the predicates,
+    /// where `key` is the partition key column.
+    pub fn prune(&self, key: &ColumnVar, predicates: &[Rc<dyn ScalarExpression>]) -> Vec<usize> {
+        (0..self.partitions.len())
+            .filter(|i| {
+                let bound = &self.partitions[*i].bound;
+                predicates
+                    .iter()
+                    .all(|predicate| bound.may_satisfy(predicate.as_ref(), key))
+            })
+            .collect()
+    }
+}
@@ -4,10 +4,11 @@ use crate::Demo;
 use crate::{GroupPlan, GroupRef};
 use cso_core::operator::Operator;
 use cso_core::property::Property;
+use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
-#[derive(Clone, Hash, Eq, PartialEq, Debug)]
+#[derive(Clone, Hash, Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct SortProperty {
     order_spec: OrderSpec,
 }
@@ -50,6 +51,10 @@ impl SortProperty {
         SortProperty { order_spec }
     }
 
+    pub fn order_spec(&self) -> &OrderSpec {
+        &self.order_spec
+    }
+
     pub fn satisfy(&self, required: &SortProperty) -> bool {
         if self.order_spec.order_desc.len() < required.order_spec.order_desc.len() {
             return false;
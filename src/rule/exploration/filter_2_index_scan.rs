@@ -1,10 +1,14 @@
-use crate::expression::And;
+use crate::expression::{
+    implies, And, Const, Equal, GreaterThan, GreaterThanEqual, InList, IsNotNull, IsNull, LessThan, LessThanEqual,
+    NotEqual,
+};
+use crate::metadata::MdAccessorExt;
 use crate::operator::logical_filter::LogicalFilter;
 use crate::operator::logical_index_scan::LogicalIndexScan;
 use crate::operator::logical_scan::LogicalScan;
 use crate::operator::OperatorId;
 use crate::rule::RuleId;
-use crate::statistics::{IndexMd, RelationMetadata};
+use crate::statistics::IndexMd;
 use crate::{Demo, OptimizerContext, Pattern, Plan};
 use cso_core::expression::ScalarExpression;
 use cso_core::operator::Operator;
@@ -56,32 +60,16 @@ impl Rule<Demo> for Filter2IndexScan {
         let table_desc = logical_scan.table_desc();
         let md_accessor = context.md_accessor();
         let relation_md = md_accessor
-            .retrieve_metadata(&table_desc.md_id())
-            .expect("Relation metadata missed!");
-        let relation_md = relation_md.downcast_ref::<RelationMetadata>().unwrap();
-
-        let predicate = logical_filter.predicate();
-        let mut filter_predicate_columns = ColumnRefSet::new();
-        predicate.derive_used_columns(&mut filter_predicate_columns);
-        let mut filter_required_columns = ColumnRefSet::new();
-        input.derive_output_columns(&mut filter_required_columns);
-        filter_required_columns.union_with(&filter_predicate_columns);
+            .retrieve_relation(table_desc.md_id())
+            .unwrap_or_else(|err| panic!("{err}"));
+        let indexes = md_accessor
+            .relation_indexes(&relation_md)
+            .unwrap_or_else(|err| panic!("{err}"));
 
         let predicates = logical_filter.split_predicate();
         let mut new_plans = vec![];
-        for i in 0..relation_md.index_count() {
-            let index_mdid = relation_md.index_mdid(i);
-            let index_md = md_accessor
-                .retrieve_metadata(&index_mdid)
-                .expect("Index metadata missed!");
-            let index_md = index_md.downcast_ref::<IndexMd>().unwrap();
-
-            if let Some((applicable_predicates, residual_predicates)) = index_matched(
-                index_md,
-                &predicates,
-                &filter_required_columns,
-                &filter_predicate_columns,
-            ) {
+        for index_md in &indexes {
+            if let Some((applicable_predicates, residual_predicates)) = index_matched(index_md, &predicates) {
                 let logical_index_scan = LogicalIndexScan::new(
                     table_desc.clone(),
                     index_md,
@@ -110,42 +98,62 @@ impl Rule<Demo> for Filter2IndexScan {
 
 type ApplicableAndResidualPredicates = (Rc<dyn ScalarExpression>, Option<Rc<dyn ScalarExpression>>);
 
+/// Splits the predicates into the ones the index looks up and the residual ones, if the index can
+/// be used. The columns the index doesn't include are fetched from the table.
 fn index_matched(
     index_md: &IndexMd,
     predicates: &[Rc<dyn ScalarExpression>],
-    required_columns: &ColumnRefSet,
-    predicate_columns: &ColumnRefSet,
 ) -> Option<ApplicableAndResidualPredicates> {
+    // A partial index only holds the rows satisfying its predicate, so the filter must not need any
+    // other row.
+    let index_predicate = index_md.predicate();
+    if index_predicate.is_some_and(|index_predicate| !implies(predicates, index_predicate.as_ref())) {
+        return None;
+    }
+
     let mut key_columns = ColumnRefSet::new();
     index_md
         .key_columns()
         .iter()
         .for_each(|key| key.derive_used_columns(&mut key_columns));
 
-    let mut include_columns = ColumnRefSet::new();
-    index_md
-        .included_columns()
-        .iter()
-        .for_each(|key| key.derive_used_columns(&mut include_columns));
-
-    if !include_columns.is_superset(required_columns) || key_columns.is_disjoint(predicate_columns) {
-        return None;
-    }
-
+    let index_type = index_md.index_type();
+    let key_expressions = index_md.key_expressions();
+    let mut looked_up_columns = ColumnRefSet::new();
+    let mut looked_up_expressions = vec![false; key_expressions.len()];
     let mut residual_predicates = Vec::new();
     let mut applicable_predicates = Vec::new();
     for expr in predicates {
         let mut used_columns = ColumnRefSet::new();
         expr.derive_used_columns(&mut used_columns);
-        if key_columns.is_superset(&used_columns) {
+        if key_columns.is_superset(&used_columns)
+            && !key_columns.is_disjoint(&used_columns)
+            && index_type.supports(expr.as_ref())
+        {
+            looked_up_columns.union_with(&used_columns);
+            applicable_predicates.push(expr.clone());
+        } else if let Some(i) = key_expression_matched(index_md, expr.as_ref()) {
+            looked_up_expressions[i] = true;
             applicable_predicates.push(expr.clone());
-        } else {
+        } else if !index_predicate.is_some_and(|index_predicate| {
+            // every row of the partial index satisfies the predicates its predicate implies
+            implies(std::slice::from_ref(index_predicate), expr.as_ref())
+        }) {
             // For now, we are not considering complex indexing scenarios. We simply assume that
             // when key_columns contain predicate used_columns, the current expr can use this index.
             residual_predicates.push(expr.clone());
         }
     }
-    debug_assert!(!applicable_predicates.is_empty());
+
+    if applicable_predicates.is_empty() {
+        // The key columns are only referenced together with other columns, e.g. `c0 = 1 OR c1 = 1`.
+        return None;
+    }
+    if index_type.requires_all_keys()
+        && !(looked_up_columns.is_superset(&key_columns) && looked_up_expressions.iter().all(|found| *found))
+    {
+        return None;
+    }
     if residual_predicates.is_empty() {
         Some((Rc::new(And::new(applicable_predicates)), None))
     } else {
@@ -155,3 +163,52 @@ fn index_matched(
         ))
     }
 }
+
+/// Returns the position of the key expression the predicate looks up, if it is the key
+/// expression itself, like `c0 > 10` on an index over `c0 > 10`, or compares it with constants.
+fn key_expression_matched(index_md: &IndexMd, predicate: &dyn ScalarExpression) -> Option<usize> {
+    let key_expressions = index_md.key_expressions();
+    if let Some(i) = key_expressions.iter().position(|key| key.as_ref() == predicate) {
+        return Some(i);
+    }
+    if !index_md.index_type().supports(predicate) {
+        return None;
+    }
+
+    let operands = comparison_operands(predicate)?;
+    let mut keys = operands
+        .iter()
+        .filter(|operand| operand.downcast_ref::<Const>().is_none())
+        .map(|operand| key_expressions.iter().position(|key| key.as_ref() == *operand));
+    match (keys.next(), keys.next()) {
+        (Some(key), None) => key,
+        _ => None,
+    }
+}
+
+/// Returns the operands of a comparison, null test or IN predicate.
+fn comparison_operands(predicate: &dyn ScalarExpression) -> Option<Vec<&dyn ScalarExpression>> {
+    if let Some(cmp) = predicate.downcast_ref::<Equal>() {
+        Some(vec![cmp.left(), cmp.right()])
+    } else if let Some(cmp) = predicate.downcast_ref::<NotEqual>() {
+        Some(vec![cmp.left(), cmp.right()])
+    } else if let Some(cmp) = predicate.downcast_ref::<LessThan>() {
+        Some(vec![cmp.left(), cmp.right()])
+    } else if let Some(cmp) = predicate.downcast_ref::<LessThanEqual>() {
+        Some(vec![cmp.left(), cmp.right()])
+    } else if let Some(cmp) = predicate.downcast_ref::<GreaterThan>() {
+        Some(vec![cmp.left(), cmp.right()])
+    } else if let Some(cmp) = predicate.downcast_ref::<GreaterThanEqual>() {
+        Some(vec![cmp.left(), cmp.right()])
+    } else if let Some(is_null) = predicate.downcast_ref::<IsNull>() {
+        Some(vec![is_null.inner()])
+    } else if let Some(is_not_null) = predicate.downcast_ref::<IsNotNull>() {
+        Some(vec![is_not_null.inner()])
+    } else if let Some(in_list) = predicate.downcast_ref::<InList>() {
+        let mut operands = vec![in_list.expr()];
+        operands.extend(in_list.list().iter().map(|expr| expr.as_ref()));
+        Some(operands)
+    } else {
+        None
+    }
+}
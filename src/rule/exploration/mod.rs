@@ -1 +1,2 @@
 pub mod filter_2_index_scan;
+pub mod partition_pruning;
@@ -0,0 +1,89 @@
+use crate::metadata::MdAccessorExt;
+use crate::operator::logical_filter::LogicalFilter;
+use crate::operator::logical_partition_scan::{LogicalPartitionScan, PartitionDesc};
+use crate::operator::logical_scan::LogicalScan;
+use crate::operator::OperatorId;
+use crate::rule::RuleId;
+use crate::{Demo, OptimizerContext, Pattern, Plan};
+use cso_core::operator::Operator;
+use cso_core::rule::{PatternType, Rule};
+use std::rc::Rc;
+
+/// Replaces the scan of a partitioned table under a filter by a scan of the partitions that may
+/// hold rows satisfying the filter.
+pub struct PartitionPruning {
+    pattern: Pattern,
+}
+
+impl PartitionPruning {
+    pub fn new() -> Self {
+        let pattern = Pattern::with_children(
+            PatternType::Operator(OperatorId::LogicalFilter),
+            vec![Pattern::new(PatternType::Operator(OperatorId::LogicalScan))],
+        );
+        Self { pattern }
+    }
+}
+
+impl Rule<Demo> for PartitionPruning {
+    fn name(&self) -> &str {
+        "PartitionPruning"
+    }
+
+    fn rule_id(&self) -> RuleId {
+        RuleId::PartitionPruning
+    }
+
+    fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    fn transform(&self, input: &Plan, context: &mut OptimizerContext) -> Vec<Plan> {
+        let logical_filter = input
+            .operator()
+            .logical_op()
+            .downcast_ref::<LogicalFilter>()
+            .expect("LogicalFilter expected!");
+
+        debug_assert_eq!(input.inputs().len(), 1);
+        let logical_scan = input.inputs()[0]
+            .operator()
+            .logical_op()
+            .downcast_ref::<LogicalScan>()
+            .expect("LogicalScan expected!");
+
+        let table_desc = logical_scan.table_desc();
+        let relation_md = context
+            .md_accessor()
+            .retrieve_relation(table_desc.md_id())
+            .unwrap_or_else(|err| panic!("{err}"));
+        let Some(partition_info) = relation_md.partition_info() else {
+            return vec![];
+        };
+
+        let key = table_desc.column(partition_info.key_col_id());
+        let selected = partition_info.prune(&key, &logical_filter.split_predicate());
+        if selected.len() == partition_info.partitions().len() {
+            return vec![];
+        }
+
+        let partitions = selected
+            .iter()
+            .map(|i| PartitionDesc::from(&partition_info.partitions()[*i]))
+            .collect();
+        let partition_scan =
+            LogicalPartitionScan::new(table_desc.clone(), logical_scan.output_columns().to_vec(), partitions);
+        let partition_scan_plan = Plan::new(Operator::Logical(Rc::new(partition_scan)), vec![], None);
+        // the filter still applies to the rows of the selected partitions
+        let filter_plan = Plan::new(
+            Operator::Logical(Rc::new(logical_filter.clone())),
+            vec![partition_scan_plan],
+            None,
+        );
+        vec![filter_plan]
+    }
+
+    fn is_transformation(&self) -> bool {
+        true
+    }
+}
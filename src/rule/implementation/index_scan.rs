@@ -21,11 +21,11 @@ impl IndexScanImplementation {
 
 impl cso_core::rule::Rule<Demo> for IndexScanImplementation {
     fn name(&self) -> &str {
-        "scan implementation"
+        "index scan implementation"
     }
 
     fn rule_id(&self) -> RuleId {
-        RuleId::ScanImplementation
+        RuleId::IndexScanImplementation
     }
 
     fn pattern(&self) -> &Pattern {
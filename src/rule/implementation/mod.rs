@@ -1,4 +1,5 @@
 pub mod filter;
 pub mod index_scan;
+pub mod partition_scan;
 pub mod project;
 pub mod scan;
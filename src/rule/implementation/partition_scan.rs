@@ -0,0 +1,56 @@
+use crate::operator::logical_partition_scan::LogicalPartitionScan;
+use crate::operator::physical_partition_scan::PhysicalPartitionScan;
+use crate::operator::OperatorId;
+use crate::rule::RuleId;
+use crate::{Demo, Pattern, PatternType};
+use crate::{OptimizerContext, Plan};
+use cso_core::operator::Operator;
+use std::rc::Rc;
+
+pub struct PartitionScanImplementation {
+    pattern: Pattern,
+}
+
+impl PartitionScanImplementation {
+    pub fn new() -> Self {
+        PartitionScanImplementation {
+            pattern: Pattern::new(PatternType::Operator(OperatorId::LogicalPartitionScan)),
+        }
+    }
+}
+
+impl cso_core::rule::Rule<Demo> for PartitionScanImplementation {
+    fn name(&self) -> &str {
+        "partition scan implementation"
+    }
+
+    fn rule_id(&self) -> RuleId {
+        RuleId::PartitionScanImplementation
+    }
+
+    fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    fn transform(&self, input: &Plan, _context: &mut OptimizerContext) -> Vec<Plan> {
+        let logical_partition_scan = input
+            .operator()
+            .logical_op()
+            .downcast_ref::<LogicalPartitionScan>()
+            .unwrap();
+        let physical_partition_scan = PhysicalPartitionScan::new(
+            logical_partition_scan.table_desc().clone(),
+            logical_partition_scan.output_columns().to_vec(),
+            logical_partition_scan.partitions().to_vec(),
+        );
+        vec![Plan::new(
+            Operator::Physical(Rc::new(physical_partition_scan)),
+            vec![],
+            input.group_plan().cloned(),
+        )]
+    }
+
+    fn is_implementation(&self) -> bool {
+        true
+    }
+}
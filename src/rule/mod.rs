@@ -2,15 +2,18 @@ mod exploration;
 mod implementation;
 
 use crate::rule::exploration::filter_2_index_scan::Filter2IndexScan;
+use crate::rule::exploration::partition_pruning::PartitionPruning;
 use crate::rule::implementation::filter::FilterImplementation;
 use crate::rule::implementation::index_scan::IndexScanImplementation;
+use crate::rule::implementation::partition_scan::PartitionScanImplementation;
 use crate::rule::implementation::project::ProjectImplementation;
 use crate::rule::implementation::scan::ScanImplementation;
 use crate::Demo;
 use cso_core::rule::RuleSet;
+use serde::{Deserialize, Serialize};
 use std::rc::Rc;
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 #[repr(u16)]
 pub enum RuleId {
     ScanImplementation = 1,
@@ -18,6 +21,8 @@ pub enum RuleId {
     ProjectImplementation = 3,
     IndexScanImplementation = 4,
     Filter2IndexScan = 5,
+    PartitionPruning = 6,
+    PartitionScanImplementation = 7,
 }
 
 impl cso_core::rule::RuleId for RuleId {
@@ -33,7 +38,8 @@ pub fn create_rule_set() -> RuleSet<Demo> {
         Rc::new(FilterImplementation::new()),
         Rc::new(ProjectImplementation::new()),
         Rc::new(IndexScanImplementation::new()),
+        Rc::new(PartitionScanImplementation::new()),
     ]);
-    rule_set.set_transform_rules(vec![Rc::new(Filter2IndexScan::new())]);
+    rule_set.set_transform_rules(vec![Rc::new(Filter2IndexScan::new()), Rc::new(PartitionPruning::new())]);
     rule_set
 }
@@ -0,0 +1,447 @@
+//! Estimates the fraction of rows satisfying a predicate on a column, from the column statistics.
+//!
+//! Equality first looks at the most common values, then at the histogram bucket holding the value,
+//! and finally spreads the remaining rows evenly over the remaining distinct values. Ranges add
+//! the most common values in the range to the part of the histogram below or above the bound.
+//!
+//! Conjunctions of equalities on correlated columns use multi-column statistics when available.
+
+use crate::datum::Datum;
+use crate::expression::{
+    And, ColumnVar, Const, Equal, GreaterThan, GreaterThanEqual, InList, IsNotNull, IsNull, LessThan, LessThanEqual,
+    Not, NotEqual, Or,
+};
+use crate::statistics::{ColumnStats, EstimateSource, MultiColumnStats, Statistics};
+use cso_core::expression::ScalarExpression;
+use cso_core::metadata::Stats;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+/// Selectivity of `column = value` when nothing is known about the column.
+pub const DEFAULT_EQUALITY_SELECTIVITY: f64 = 0.005;
+/// Selectivity of a range comparison like `column < value` when nothing is known about the column.
+pub const DEFAULT_RANGE_SELECTIVITY: f64 = 1.0 / 3.0;
+/// Selectivity of `column IS NULL` when nothing is known about the column.
+pub const DEFAULT_NULL_SELECTIVITY: f64 = 0.005;
+
+/// Estimates the fraction of the `rows` rows of a column equal to `value`.
+pub fn equality_selectivity(column_stats: &ColumnStats, rows: u64, value: &Datum) -> f64 {
+    if rows == 0 {
+        return 0.0;
+    }
+    if let Some(frequency) = column_stats.mcv_frequency(value) {
+        return frequency;
+    }
+    if value.cmp_value(column_stats.min()).is_lt() || value.cmp_value(column_stats.max()).is_gt() {
+        return 0.0;
+    }
+
+    let rows = rows as f64;
+    if let Some(histogram) = column_stats.histogram() {
+        let bucket = histogram.buckets().iter().find(|bucket| {
+            bucket.lower().cmp_value(value).is_le() && value.cmp_value(bucket.upper()).is_le() && bucket.ndv() > 0
+        });
+        if let Some(bucket) = bucket {
+            return (bucket.value_count() as f64 / bucket.ndv() as f64 / rows).min(1.0);
+        }
+    }
+
+    // spread the rows that are neither null nor a most common value over the other values
+    let rest = non_null_fraction(column_stats, rows) - mcv_fraction(column_stats);
+    let rest = rest.max(0.0);
+    let mcv_count = column_stats.mcvs().len() as u64;
+    match column_stats.ndv() {
+        Some(ndv) if ndv > mcv_count => rest / (ndv - mcv_count) as f64,
+        _ => DEFAULT_EQUALITY_SELECTIVITY.min(rest),
+    }
+}
+
+/// Estimates the fraction of the `rows` rows of a column equal to any of `values`.
+pub fn in_list_selectivity(column_stats: &ColumnStats, rows: u64, values: &[Datum]) -> f64 {
+    if rows == 0 {
+        return 0.0;
+    }
+    let mut values = values.to_vec();
+    values.sort_by(|value, other| value.cmp_value(other));
+    values.dedup_by(|value, other| value.cmp_value(other).is_eq());
+
+    let selectivity = values
+        .iter()
+        .map(|value| equality_selectivity(column_stats, rows, value))
+        .sum::<f64>();
+    selectivity.min(non_null_fraction(column_stats, rows as f64))
+}
+
+/// Estimates the selectivity of an equality or IN predicate comparing `column`, whose
+/// statistics are `column_stats`, with constants. Returns `None` for other predicates.
+pub fn column_predicate_selectivity(
+    predicate: &dyn ScalarExpression,
+    column: &ColumnVar,
+    column_stats: &ColumnStats,
+    rows: u64,
+) -> Option<f64> {
+    if predicate_column(predicate) != Some(column) {
+        return None;
+    }
+    if let Some((_, value)) = column_equality(predicate) {
+        Some(equality_selectivity(column_stats, rows, &value))
+    } else {
+        let values = in_list_values(predicate)?;
+        Some(in_list_selectivity(column_stats, rows, &values))
+    }
+}
+
+/// Estimates the fraction of the `rows` rows of a column comparing with `value` as `ordering`
+/// (`Less` meaning `column < value`), or equal to it if `or_equal`.
+pub fn range_selectivity(
+    column_stats: &ColumnStats,
+    rows: u64,
+    value: &Datum,
+    ordering: Ordering,
+    or_equal: bool,
+) -> f64 {
+    if rows == 0 || value.is_null() {
+        return 0.0;
+    }
+    let satisfies = |other: &Datum| {
+        let cmp = other.cmp_value(value);
+        cmp == ordering || (or_equal && cmp == Ordering::Equal)
+    };
+    let mcv_selectivity = column_stats
+        .mcvs()
+        .iter()
+        .filter(|mcv| satisfies(mcv.value()))
+        .map(|mcv| mcv.frequency())
+        .sum::<f64>();
+
+    // the values neither null nor most common are spread as the histogram says, or evenly over
+    // [min, max] without one
+    let below = match column_stats.histogram() {
+        Some(histogram) if histogram.value_count() > 0 => histogram.fraction_below(value),
+        _ => match value.interpolate(column_stats.min(), column_stats.max()) {
+            Some(below) => below,
+            None => return DEFAULT_RANGE_SELECTIVITY.min(non_null_fraction(column_stats, rows as f64)),
+        },
+    };
+    let rest = (non_null_fraction(column_stats, rows as f64) - mcv_fraction(column_stats)).max(0.0);
+    let point = if column_stats.mcv_frequency(value).is_some() {
+        0.0
+    } else {
+        equality_selectivity(column_stats, rows, value).min(rest)
+    };
+    let rest_selectivity = match (ordering, or_equal) {
+        (Ordering::Less, false) => rest * below,
+        (Ordering::Less, true) => rest * below + point,
+        (Ordering::Greater, false) => rest * (1.0 - below) - point,
+        (Ordering::Greater, true) => rest * (1.0 - below),
+        (Ordering::Equal, _) => point,
+    };
+    (mcv_selectivity + rest_selectivity.clamp(0.0, rest)).clamp(0.0, 1.0)
+}
+
+/// Estimates the fraction of the `rows` rows of a column that are null.
+pub fn null_selectivity(column_stats: &ColumnStats, rows: u64) -> f64 {
+    if rows == 0 {
+        return 0.0;
+    }
+    1.0 - non_null_fraction(column_stats, rows as f64)
+}
+
+/// Estimates the selectivity of the predicate over rows with `stats`, from the statistics of the
+/// columns it compares with constants, or default selectivities without them. Predicates that
+/// can't be estimated, and their negations, don't filter any row.
+pub fn predicate_selectivity(predicate: &dyn ScalarExpression, stats: &Statistics) -> f64 {
+    known_selectivity(predicate, stats).unwrap_or(1.0)
+}
+
+/// Estimates the selectivity of the predicate, or returns `None` if it can't be estimated.
+fn known_selectivity(predicate: &dyn ScalarExpression, stats: &Statistics) -> Option<f64> {
+    let rows = stats.output_row_count();
+    if let Some(and) = predicate.downcast_ref::<And>() {
+        return Some(conjunction_selectivity(and.expressions(), stats));
+    }
+    if let Some(or) = predicate.downcast_ref::<Or>() {
+        // as if the disjuncts were independent
+        let none = or
+            .expressions()
+            .iter()
+            .map(|expr| known_selectivity(expr.as_ref(), stats).map(|selectivity| 1.0 - selectivity))
+            .product::<Option<f64>>()?;
+        return Some((1.0 - none).clamp(0.0, 1.0));
+    }
+    if let Some(not) = predicate.downcast_ref::<Not>() {
+        let selectivity = known_selectivity(not.expression(), stats)?;
+        return Some((1.0 - selectivity).clamp(0.0, 1.0));
+    }
+    if let Some(is_null) = predicate.downcast_ref::<IsNull>() {
+        return Some(column_null_selectivity(is_null.inner(), stats));
+    }
+    if let Some(is_not_null) = predicate.downcast_ref::<IsNotNull>() {
+        return Some(1.0 - column_null_selectivity(is_not_null.inner(), stats));
+    }
+    if let Some((left, right, ordering, or_equal)) = comparison(predicate) {
+        let Some((column_stats, value, ordering)) = column_comparison(left, right, ordering, stats) else {
+            // comparisons of other expressions, like two columns
+            return Some(match (ordering, or_equal) {
+                (Ordering::Equal, true) => DEFAULT_EQUALITY_SELECTIVITY,
+                (Ordering::Equal, false) => 1.0 - DEFAULT_EQUALITY_SELECTIVITY,
+                _ => DEFAULT_RANGE_SELECTIVITY,
+            });
+        };
+        return Some(match (ordering, or_equal) {
+            (Ordering::Equal, true) => equality_selectivity(column_stats, rows, &value),
+            (Ordering::Equal, false) => {
+                let selectivity =
+                    non_null_fraction(column_stats, rows as f64) - equality_selectivity(column_stats, rows, &value);
+                selectivity.max(0.0)
+            }
+            _ => range_selectivity(column_stats, rows, &value, ordering, or_equal),
+        });
+    }
+
+    let column = predicate_column(predicate)?;
+    if let Some(column_stats) = stats.column_stats_of(column) {
+        if let Some(selectivity) = column_predicate_selectivity(predicate, column, column_stats, rows) {
+            return Some(selectivity);
+        }
+    }
+    in_list_values(predicate).map(|values| (values.len() as f64 * DEFAULT_EQUALITY_SELECTIVITY).min(1.0))
+}
+
+/// Returns whether the selectivity of the predicate over rows with `stats` is estimated from the
+/// statistics of the columns it compares, rather than guessed.
+pub fn estimated_from_stats(predicate: &dyn ScalarExpression, stats: &Statistics) -> bool {
+    if let Some(and) = predicate.downcast_ref::<And>() {
+        return and
+            .expressions()
+            .iter()
+            .all(|expr| estimated_from_stats(expr.as_ref(), stats));
+    }
+    if let Some(or) = predicate.downcast_ref::<Or>() {
+        return or
+            .expressions()
+            .iter()
+            .all(|expr| estimated_from_stats(expr.as_ref(), stats));
+    }
+    if let Some(not) = predicate.downcast_ref::<Not>() {
+        return estimated_from_stats(not.expression(), stats);
+    }
+    if let Some((left, right, ordering, _)) = comparison(predicate) {
+        return column_comparison(left, right, ordering, stats).is_some();
+    }
+    restricted_column(predicate).is_some_and(|column| stats.column_stats_of(column).is_some())
+}
+
+/// Derives the statistics of the column the predicate compares with constants, or tests for
+/// null, over the rows satisfying it. `column_stats` returns the statistics of a column with the
+/// number of rows they describe. Returns the column with its statistics and number of rows.
+pub fn restrict_column_stats(
+    predicate: &dyn ScalarExpression,
+    column_stats: impl Fn(&ColumnVar) -> Option<(ColumnStats, u64)>,
+) -> Option<(ColumnVar, ColumnStats, u64)> {
+    let column = restricted_column(predicate)?;
+    let (stats, rows) = column_stats(column)?;
+    let column_only = Statistics::new(
+        rows,
+        EstimateSource::ColumnStats,
+        BTreeMap::from([(column.id(), stats.clone())]),
+    );
+    let kept_rows = (rows as f64 * predicate_selectivity(predicate, &column_only)).round() as u64;
+
+    let restricted = if predicate.downcast_ref::<IsNull>().is_some() {
+        stats.restrict_to_nulls(rows, kept_rows, true)
+    } else if predicate.downcast_ref::<IsNotNull>().is_some() {
+        stats.restrict_to_nulls(rows, kept_rows, false)
+    } else if let Some(values) = in_list_values(predicate) {
+        stats.restrict_to_values(rows, kept_rows, &values)
+    } else {
+        let (left, right, ordering, or_equal) = comparison(predicate)?;
+        let (value, ordering) = match const_datum(right) {
+            Some(value) => (value, ordering),
+            None => (const_datum(left)?, ordering.reverse()),
+        };
+        stats.restrict_to_range(rows, kept_rows, &value, ordering, or_equal)
+    };
+    Some((column.clone(), restricted, kept_rows))
+}
+
+/// Returns the column of a predicate comparing it with constants or testing it for null.
+fn restricted_column(predicate: &dyn ScalarExpression) -> Option<&ColumnVar> {
+    if let Some(is_null) = predicate.downcast_ref::<IsNull>() {
+        return is_null.inner().downcast_ref::<ColumnVar>();
+    }
+    if let Some(is_not_null) = predicate.downcast_ref::<IsNotNull>() {
+        return is_not_null.inner().downcast_ref::<ColumnVar>();
+    }
+    if in_list_values(predicate).is_some() {
+        return predicate_column(predicate);
+    }
+    let (left, right, ..) = comparison(predicate)?;
+    match (left.downcast_ref::<ColumnVar>(), right.downcast_ref::<ColumnVar>()) {
+        (Some(column), None) => const_datum(right).map(|_| column),
+        (None, Some(column)) => const_datum(left).map(|_| column),
+        _ => None,
+    }
+}
+
+/// Estimates the selectivity of the conjunction of the predicates over rows with `stats`.
+///
+/// Equalities on all columns of multi-column statistics are estimated together, the widest
+/// statistics first. The selectivities of the other predicates are multiplied, as if the
+/// predicates were independent.
+pub fn conjunction_selectivity(predicates: &[Rc<dyn ScalarExpression>], stats: &Statistics) -> f64 {
+    // column id -> (position, value) of the predicates `column = constant`
+    let mut equalities = HashMap::new();
+    for (i, predicate) in predicates.iter().enumerate() {
+        if let Some((column, value)) = column_equality(predicate.as_ref()) {
+            equalities.entry(column.id()).or_insert((i, value));
+        }
+    }
+
+    let mut multi_column_stats = stats.multi_column_stats().iter().collect::<Vec<_>>();
+    multi_column_stats.sort_by_key(|(columns, _)| Reverse(columns.len()));
+
+    let mut estimated = vec![false; predicates.len()];
+    let mut selectivity = 1.0;
+    for (columns, multi_column_stats) in multi_column_stats {
+        let matched = columns
+            .iter()
+            .map(|column| equalities.get(&column.id()).filter(|(i, _)| !estimated[*i]).cloned())
+            .collect::<Option<Vec<_>>>();
+        let Some(matched) = matched else {
+            continue;
+        };
+        let values = matched.iter().map(|(_, value)| value.clone()).collect::<Vec<_>>();
+        let Some(combined) = combined_equality_selectivity(multi_column_stats, &values) else {
+            continue;
+        };
+
+        // the combination is never more common than any of its values
+        let individual = matched
+            .iter()
+            .map(|(i, _)| predicate_selectivity(predicates[*i].as_ref(), stats))
+            .fold(1.0, f64::min);
+        selectivity *= combined.min(individual);
+        matched.iter().for_each(|(i, _)| estimated[*i] = true);
+    }
+
+    for (predicate, estimated) in predicates.iter().zip(estimated) {
+        if !estimated {
+            selectivity *= predicate_selectivity(predicate.as_ref(), stats);
+        }
+    }
+    selectivity.clamp(0.0, 1.0)
+}
+
+fn combined_equality_selectivity(multi_column_stats: &MultiColumnStats, values: &[Datum]) -> Option<f64> {
+    if let Some(frequency) = multi_column_stats.mcv_frequency(values) {
+        return Some(frequency);
+    }
+
+    // spread the rows that aren't a most common combination over the other combinations
+    let mcv_count = multi_column_stats.mcvs().len() as u64;
+    let ndv = multi_column_stats.ndv().filter(|ndv| *ndv > mcv_count)?;
+    let mcv_fraction = multi_column_stats.mcvs().iter().map(|mcv| mcv.frequency()).sum::<f64>();
+    Some((1.0 - mcv_fraction).max(0.0) / (ndv - mcv_count) as f64)
+}
+
+/// Returns the column of an equality or IN predicate comparing a column with constants.
+fn predicate_column(predicate: &dyn ScalarExpression) -> Option<&ColumnVar> {
+    if let Some(equal) = predicate.downcast_ref::<Equal>() {
+        match (
+            equal.left().downcast_ref::<ColumnVar>(),
+            equal.right().downcast_ref::<ColumnVar>(),
+        ) {
+            (Some(column), None) | (None, Some(column)) => Some(column),
+            _ => None,
+        }
+    } else if let Some(in_list) = predicate.downcast_ref::<InList>() {
+        in_list.expr().downcast_ref::<ColumnVar>()
+    } else {
+        None
+    }
+}
+
+/// Estimates the fraction of the rows where the operand is null, from the statistics of the
+/// column if it is an analyzed one.
+fn column_null_selectivity(operand: &dyn ScalarExpression, stats: &Statistics) -> f64 {
+    match column_stats_of(operand, stats) {
+        Some(column_stats) => null_selectivity(column_stats, stats.output_row_count()),
+        None => DEFAULT_NULL_SELECTIVITY,
+    }
+}
+
+/// Returns the statistics of the operand if it is a column.
+fn column_stats_of<'a>(operand: &dyn ScalarExpression, stats: &'a Statistics) -> Option<&'a ColumnStats> {
+    stats.column_stats_of(operand.downcast_ref::<ColumnVar>()?)
+}
+
+/// Returns the operands of a comparison and how they compare when it holds, like `(Less, true)`
+/// for `<=`, and `(Equal, false)` for `!=`.
+fn comparison(
+    predicate: &dyn ScalarExpression,
+) -> Option<(&dyn ScalarExpression, &dyn ScalarExpression, Ordering, bool)> {
+    if let Some(cmp) = predicate.downcast_ref::<Equal>() {
+        Some((cmp.left(), cmp.right(), Ordering::Equal, true))
+    } else if let Some(cmp) = predicate.downcast_ref::<NotEqual>() {
+        Some((cmp.left(), cmp.right(), Ordering::Equal, false))
+    } else if let Some(cmp) = predicate.downcast_ref::<LessThan>() {
+        Some((cmp.left(), cmp.right(), Ordering::Less, false))
+    } else if let Some(cmp) = predicate.downcast_ref::<LessThanEqual>() {
+        Some((cmp.left(), cmp.right(), Ordering::Less, true))
+    } else if let Some(cmp) = predicate.downcast_ref::<GreaterThan>() {
+        Some((cmp.left(), cmp.right(), Ordering::Greater, false))
+    } else if let Some(cmp) = predicate.downcast_ref::<GreaterThanEqual>() {
+        Some((cmp.left(), cmp.right(), Ordering::Greater, true))
+    } else {
+        None
+    }
+}
+
+/// Matches the comparison of an analyzed column with a constant, returning the statistics of the
+/// column, the constant and how the column compares with it: `10 < c0` is `c0 > 10`.
+fn column_comparison<'a>(
+    left: &dyn ScalarExpression,
+    right: &dyn ScalarExpression,
+    ordering: Ordering,
+    stats: &'a Statistics,
+) -> Option<(&'a ColumnStats, Datum, Ordering)> {
+    match (column_stats_of(left, stats), column_stats_of(right, stats)) {
+        (Some(column_stats), None) => Some((column_stats, const_datum(right)?, ordering)),
+        (None, Some(column_stats)) => Some((column_stats, const_datum(left)?, ordering.reverse())),
+        _ => None,
+    }
+}
+
+/// Matches `column = constant` and `constant = column`.
+fn column_equality(predicate: &dyn ScalarExpression) -> Option<(&ColumnVar, Datum)> {
+    let equal = predicate.downcast_ref::<Equal>()?;
+    match (
+        equal.left().downcast_ref::<ColumnVar>(),
+        equal.right().downcast_ref::<ColumnVar>(),
+    ) {
+        (Some(column), None) => Some((column, const_datum(equal.right())?)),
+        (None, Some(column)) => Some((column, const_datum(equal.left())?)),
+        _ => None,
+    }
+}
+
+/// Matches `column IN (constants...)`.
+fn in_list_values(predicate: &dyn ScalarExpression) -> Option<Vec<Datum>> {
+    let in_list = predicate.downcast_ref::<InList>()?;
+    in_list.expr().downcast_ref::<ColumnVar>()?;
+    in_list.list().iter().map(|expr| const_datum(expr.as_ref())).collect()
+}
+
+fn const_datum(expr: &dyn ScalarExpression) -> Option<Datum> {
+    expr.downcast_ref::<Const>().map(Datum::from)
+}
+
+fn non_null_fraction(column_stats: &ColumnStats, rows: f64) -> f64 {
+    (1.0 - column_stats.null_count() as f64 / rows).clamp(0.0, 1.0)
+}
+
+fn mcv_fraction(column_stats: &ColumnStats) -> f64 {
+    column_stats.mcvs().iter().map(|mcv| mcv.frequency()).sum()
+}
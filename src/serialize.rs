@@ -0,0 +1,250 @@
+//! Serde adapters for plans and physical properties.
+//!
+//! Operators and properties are trait objects in `cso_core`, so they are converted from and to the
+//! operators defined in this crate. Use the modules with `#[serde(with = "...")]`.
+
+use crate::operator::logical_filter::LogicalFilter;
+use crate::operator::logical_index_scan::LogicalIndexScan;
+use crate::operator::logical_partition_scan::LogicalPartitionScan;
+use crate::operator::logical_project::LogicalProject;
+use crate::operator::logical_scan::LogicalScan;
+use crate::operator::logical_view_scan::LogicalViewScan;
+use crate::operator::physical_filter::PhysicalFilter;
+use crate::operator::physical_index_scan::PhysicalIndexScan;
+use crate::operator::physical_partition_scan::PhysicalPartitionScan;
+use crate::operator::physical_project::PhysicalProject;
+use crate::operator::physical_scan::PhysicalScan;
+use crate::operator::physical_sort::PhysicalSort;
+use crate::operator::{LogicalOperator, PhysicalOperator};
+use crate::property::sort_property::SortProperty;
+use crate::property::{PhysicalProperties, PhysicalProperty};
+use crate::{LogicalPlan, PhysicalPlan};
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+
+#[derive(Serialize, Deserialize)]
+enum LogicalOperatorData {
+    Scan(LogicalScan),
+    Filter(LogicalFilter),
+    Project(LogicalProject),
+    IndexScan(LogicalIndexScan),
+    PartitionScan(LogicalPartitionScan),
+    ViewScan(LogicalViewScan),
+}
+
+impl LogicalOperatorData {
+    fn from_operator(op: &LogicalOperator) -> Result<Self, String> {
+        if let Some(op) = op.downcast_ref::<LogicalScan>() {
+            Ok(Self::Scan(op.clone()))
+        } else if let Some(op) = op.downcast_ref::<LogicalFilter>() {
+            Ok(Self::Filter(op.clone()))
+        } else if let Some(op) = op.downcast_ref::<LogicalProject>() {
+            Ok(Self::Project(op.clone()))
+        } else if let Some(op) = op.downcast_ref::<LogicalIndexScan>() {
+            Ok(Self::IndexScan(op.clone()))
+        } else if let Some(op) = op.downcast_ref::<LogicalPartitionScan>() {
+            Ok(Self::PartitionScan(op.clone()))
+        } else if let Some(op) = op.downcast_ref::<LogicalViewScan>() {
+            Ok(Self::ViewScan(op.clone()))
+        } else {
+            Err(format!("unsupported logical operator: {}", op.name()))
+        }
+    }
+
+    fn into_operator(self) -> Rc<LogicalOperator> {
+        match self {
+            Self::Scan(op) => Rc::new(op),
+            Self::Filter(op) => Rc::new(op),
+            Self::Project(op) => Rc::new(op),
+            Self::IndexScan(op) => Rc::new(op),
+            Self::PartitionScan(op) => Rc::new(op),
+            Self::ViewScan(op) => Rc::new(op),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum PhysicalOperatorData {
+    Scan(PhysicalScan),
+    Filter(PhysicalFilter),
+    Project(PhysicalProject),
+    IndexScan(PhysicalIndexScan),
+    Sort(PhysicalSort),
+    PartitionScan(PhysicalPartitionScan),
+}
+
+impl PhysicalOperatorData {
+    fn from_operator(op: &PhysicalOperator) -> Result<Self, String> {
+        if let Some(op) = op.downcast_ref::<PhysicalScan>() {
+            Ok(Self::Scan(op.clone()))
+        } else if let Some(op) = op.downcast_ref::<PhysicalFilter>() {
+            Ok(Self::Filter(op.clone()))
+        } else if let Some(op) = op.downcast_ref::<PhysicalProject>() {
+            Ok(Self::Project(op.clone()))
+        } else if let Some(op) = op.downcast_ref::<PhysicalIndexScan>() {
+            Ok(Self::IndexScan(op.clone()))
+        } else if let Some(op) = op.downcast_ref::<PhysicalSort>() {
+            Ok(Self::Sort(op.clone()))
+        } else if let Some(op) = op.downcast_ref::<PhysicalPartitionScan>() {
+            Ok(Self::PartitionScan(op.clone()))
+        } else {
+            Err(format!("unsupported physical operator: {}", op.name()))
+        }
+    }
+
+    fn into_operator(self) -> Rc<PhysicalOperator> {
+        match self {
+            Self::Scan(op) => Rc::new(op),
+            Self::Filter(op) => Rc::new(op),
+            Self::Project(op) => Rc::new(op),
+            Self::IndexScan(op) => Rc::new(op),
+            Self::Sort(op) => Rc::new(op),
+            Self::PartitionScan(op) => Rc::new(op),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum PhysicalPropertyData {
+    Sort(SortProperty),
+}
+
+impl PhysicalPropertyData {
+    fn from_property(property: &PhysicalProperty) -> Result<Self, String> {
+        match property.downcast_ref::<SortProperty>() {
+            Some(property) => Ok(Self::Sort(property.clone())),
+            None => Err(format!("unsupported physical property: {:?}", property)),
+        }
+    }
+
+    fn into_property(self) -> Box<PhysicalProperty> {
+        match self {
+            Self::Sort(property) => Box::new(property),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PhysicalPropertiesData(Vec<PhysicalPropertyData>);
+
+impl PhysicalPropertiesData {
+    fn from_properties(properties: &PhysicalProperties) -> Result<Self, String> {
+        let properties = properties
+            .properties()
+            .iter()
+            .map(|property| PhysicalPropertyData::from_property(property.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self(properties))
+    }
+
+    fn into_properties(self) -> Rc<PhysicalProperties> {
+        let properties = self.0.into_iter().map(PhysicalPropertyData::into_property).collect();
+        PhysicalProperties::with_properties(properties)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct LogicalPlanData {
+    operator: LogicalOperatorData,
+    inputs: Vec<LogicalPlanData>,
+    required_properties: Vec<PhysicalPropertiesData>,
+}
+
+impl LogicalPlanData {
+    fn from_plan(plan: &LogicalPlan) -> Result<Self, String> {
+        Ok(Self {
+            operator: LogicalOperatorData::from_operator(plan.operator().as_ref())?,
+            inputs: plan.inputs().iter().map(Self::from_plan).collect::<Result<_, _>>()?,
+            required_properties: plan
+                .required_properties()
+                .iter()
+                .map(PhysicalPropertiesData::from_properties)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn into_plan(self) -> LogicalPlan {
+        LogicalPlan::new(
+            self.operator.into_operator(),
+            self.inputs.into_iter().map(Self::into_plan).collect(),
+            self.required_properties
+                .into_iter()
+                .map(|properties| properties.into_properties().as_ref().clone())
+                .collect(),
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PhysicalPlanData {
+    operator: PhysicalOperatorData,
+    inputs: Vec<PhysicalPlanData>,
+}
+
+impl PhysicalPlanData {
+    fn from_plan(plan: &PhysicalPlan) -> Result<Self, String> {
+        Ok(Self {
+            operator: PhysicalOperatorData::from_operator(plan.operator().as_ref())?,
+            inputs: plan.inputs().iter().map(Self::from_plan).collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn into_plan(self) -> PhysicalPlan {
+        PhysicalPlan::new(
+            self.operator.into_operator(),
+            self.inputs.into_iter().map(Self::into_plan).collect(),
+        )
+    }
+}
+
+pub mod logical_plan {
+    use super::LogicalPlanData;
+    use crate::LogicalPlan;
+    use serde::ser::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(plan: &LogicalPlan, serializer: S) -> Result<S::Ok, S::Error> {
+        LogicalPlanData::from_plan(plan)
+            .map_err(S::Error::custom)?
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<LogicalPlan, D::Error> {
+        LogicalPlanData::deserialize(deserializer).map(LogicalPlanData::into_plan)
+    }
+}
+
+pub mod physical_plan {
+    use super::PhysicalPlanData;
+    use crate::PhysicalPlan;
+    use serde::ser::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(plan: &PhysicalPlan, serializer: S) -> Result<S::Ok, S::Error> {
+        PhysicalPlanData::from_plan(plan)
+            .map_err(S::Error::custom)?
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PhysicalPlan, D::Error> {
+        PhysicalPlanData::deserialize(deserializer).map(PhysicalPlanData::into_plan)
+    }
+}
+
+pub mod physical_properties {
+    use super::PhysicalPropertiesData;
+    use crate::property::PhysicalProperties;
+    use serde::ser::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::rc::Rc;
+
+    pub fn serialize<S: Serializer>(properties: &Rc<PhysicalProperties>, serializer: S) -> Result<S::Ok, S::Error> {
+        PhysicalPropertiesData::from_properties(properties)
+            .map_err(S::Error::custom)?
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Rc<PhysicalProperties>, D::Error> {
+        PhysicalPropertiesData::deserialize(deserializer).map(PhysicalPropertiesData::into_properties)
+    }
+}
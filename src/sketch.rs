@@ -0,0 +1,148 @@
+//! Mergeable sketches of column values.
+
+use crate::datum::Datum;
+use cso_core::metadata::Metadata;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_HLL_PRECISION: u8 = 12;
+pub const MIN_HLL_PRECISION: u8 = 4;
+pub const MAX_HLL_PRECISION: u8 = 16;
+
+/// A HyperLogLog sketch estimating the number of distinct values of a column.
+///
+/// Sketches of the partitions or shards of a table can be merged into the sketch of the table,
+/// as long as they have the same precision. The sketch of `2^precision` registers has a standard
+/// error of about `1.04 / sqrt(2^precision)`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawHyperLogLog")]
+pub struct HyperLogLog {
+    precision: u8,
+    registers: Vec<u8>,
+}
+
+/// A serialized sketch, checked before it is used.
+#[derive(Deserialize)]
+struct RawHyperLogLog {
+    precision: u8,
+    registers: Vec<u8>,
+}
+
+impl TryFrom<RawHyperLogLog> for HyperLogLog {
+    type Error = String;
+
+    fn try_from(raw: RawHyperLogLog) -> Result<Self, Self::Error> {
+        let RawHyperLogLog { precision, registers } = raw;
+        if !(MIN_HLL_PRECISION..=MAX_HLL_PRECISION).contains(&precision) {
+            return Err(format!(
+                "precision of hyperloglog must be in [{MIN_HLL_PRECISION}, {MAX_HLL_PRECISION}], got {precision}"
+            ));
+        }
+        if registers.len() != 1 << precision {
+            return Err(format!(
+                "hyperloglog of precision {precision} must have {} registers, got {}",
+                1 << precision,
+                registers.len()
+            ));
+        }
+        Ok(Self { precision, registers })
+    }
+}
+
+impl HyperLogLog {
+    pub fn new(precision: u8) -> Self {
+        assert!(
+            (MIN_HLL_PRECISION..=MAX_HLL_PRECISION).contains(&precision),
+            "precision of hyperloglog must be in [{MIN_HLL_PRECISION}, {MAX_HLL_PRECISION}]"
+        );
+        Self {
+            precision,
+            registers: vec![0; 1 << precision],
+        }
+    }
+
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    pub fn add(&mut self, value: &Datum) {
+        self.add_hash(hash_datum(value));
+    }
+
+    pub fn add_hash(&mut self, hash: u64) {
+        let index = (hash >> (64 - self.precision)) as usize;
+        // the position of the first 1 bit after the index bits, at most 64 - precision + 1
+        let rank = ((hash << self.precision) | (1 << (self.precision - 1))).leading_zeros() as u8 + 1;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    /// Merges `other` into the sketch, so it describes the values added to either.
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        assert_eq!(
+            self.precision, other.precision,
+            "cannot merge hyperloglogs of different precisions"
+        );
+        for (register, other) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(*other);
+        }
+    }
+
+    /// Returns the estimated number of distinct values.
+    pub fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let sum = self
+            .registers
+            .iter()
+            .map(|register| 2f64.powi(-(*register as i32)))
+            .sum::<f64>();
+        let estimate = alpha * m * m / sum;
+
+        // linear counting is more accurate for small cardinalities
+        let zeros = self.registers.iter().filter(|register| **register == 0).count();
+        if estimate <= 2.5 * m && zeros > 0 {
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+}
+
+#[typetag::serde]
+impl Metadata for HyperLogLog {}
+
+/// A hash of the value, which must not change between releases, since sketches are stored.
+fn hash_datum(value: &Datum) -> u64 {
+    // the type seeds the hash, so equal bits of different types hash differently
+    let typed = |seed: u64, bits: u64| mix(bits ^ mix(seed));
+    match value {
+        Datum::I32(value) => mix(*value as u32 as u64),
+        Datum::Null => typed(1, 0),
+        Datum::Bool(value) => typed(2, *value as u64),
+        Datum::I64(value) => typed(3, *value as u64),
+        Datum::F64(value) => typed(4, value.to_bits()),
+        Datum::Decimal { unscaled, scale } => typed(5, mix(*unscaled as u64) ^ *scale as u64),
+        Datum::String(value) => typed(6, fnv1a(value.as_bytes())),
+        Datum::Date(value) => typed(7, *value as u32 as u64),
+        Datum::Timestamp(value) => typed(8, *value as u64),
+    }
+}
+
+/// The 64-bit FNV-1a hash.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// The finalizer of SplitMix64.
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
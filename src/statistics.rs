@@ -1,9 +1,17 @@
 use crate::datum::Datum;
-use crate::expression::ColumnVar;
+use crate::expression::{
+    And, ColumnVar, Equal, GreaterThan, GreaterThanEqual, InList, IsNotNull, IsNull, LessThan, LessThanEqual, Not,
+    NotEqual, Or, ScalarExpression,
+};
+use crate::partition::PartitionInfo;
+use crate::LogicalPlan;
 use cso_core::metadata::Metadata;
 use cso_core::metadata::Stats;
+use cso_core::ColumnRefSet;
 use serde::{Deserialize, Serialize};
-use std::fmt::Debug;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug};
 use std::rc::Rc;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -26,13 +34,13 @@ impl Bucket {
     }
 
     #[inline]
-    pub fn lower(&self) -> Datum {
-        self.lower
+    pub fn lower(&self) -> &Datum {
+        &self.lower
     }
 
     #[inline]
-    pub fn upper(&self) -> Datum {
-        self.upper
+    pub fn upper(&self) -> &Datum {
+        &self.upper
     }
 
     #[inline]
@@ -44,6 +52,18 @@ impl Bucket {
     pub fn value_count(&self) -> u64 {
         self.value_count
     }
+
+    /// Estimates the fraction of the values of the bucket less than `value`, interpolating
+    /// between the bounds when `value` falls inside the bucket.
+    pub fn fraction_below(&self, value: &Datum) -> f64 {
+        if value <= &self.lower {
+            0.0
+        } else if value > &self.upper {
+            1.0
+        } else {
+            value.interpolate(&self.lower, &self.upper).unwrap_or(0.5)
+        }
+    }
 }
 
 /// A histogram is a representation of the distribution of a column.
@@ -62,6 +82,49 @@ impl Histogram {
     pub fn buckets(&self) -> &[Bucket] {
         self.buckets.as_slice()
     }
+
+    /// Returns the number of values of all buckets.
+    pub fn value_count(&self) -> u64 {
+        self.buckets.iter().map(|bucket| bucket.value_count).sum()
+    }
+
+    /// Estimates the fraction of the values of the histogram less than `value`.
+    pub fn fraction_below(&self, value: &Datum) -> f64 {
+        let total = self.value_count();
+        if total == 0 {
+            return 0.0;
+        }
+        let below = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.value_count as f64 * bucket.fraction_below(value))
+            .sum::<f64>();
+        below / total as f64
+    }
+}
+
+/// A most common value of a column, with the fraction of all rows (nulls included) holding it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Mcv {
+    value: Datum,
+    frequency: f64,
+}
+
+impl Mcv {
+    #[inline]
+    pub const fn new(value: Datum, frequency: f64) -> Self {
+        Self { value, frequency }
+    }
+
+    #[inline]
+    pub fn value(&self) -> &Datum {
+        &self.value
+    }
+
+    #[inline]
+    pub fn frequency(&self) -> f64 {
+        self.frequency
+    }
 }
 
 /// Statistics information of a column
@@ -73,6 +136,12 @@ pub struct ColumnStats {
     max: Datum,                   // Max value of the column
     null_count: u64,              // Count of null values
     histogram: Option<Histogram>, // Histogram of column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ndv: Option<u64>, // Estimated number of distinct non-null values
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    mcvs: Vec<Mcv>, // Most common values, which the histogram doesn't cover
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sketch_mdid: Option<u64>, // Mdid of the distinct value sketch of the column
 }
 
 impl ColumnStats {
@@ -91,19 +160,41 @@ impl ColumnStats {
             max,
             null_count,
             histogram,
+            ndv: None,
+            mcvs: Vec::new(),
+            sketch_mdid: None,
         }
     }
 
+    pub fn with_ndv(mut self, ndv: u64) -> Self {
+        self.ndv = Some(ndv);
+        self
+    }
+
+    pub fn with_mcvs(mut self, mcvs: Vec<Mcv>) -> Self {
+        self.mcvs = mcvs;
+        self
+    }
+
+    pub fn with_sketch_mdid(mut self, sketch_mdid: u64) -> Self {
+        self.sketch_mdid = Some(sketch_mdid);
+        self
+    }
+
+    pub fn col_id(&self) -> usize {
+        self.col_id
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
-    pub fn min(&self) -> Datum {
-        self.min
+    pub fn min(&self) -> &Datum {
+        &self.min
     }
 
-    pub fn max(&self) -> Datum {
-        self.max
+    pub fn max(&self) -> &Datum {
+        &self.max
     }
 
     pub fn null_count(&self) -> u64 {
@@ -113,41 +204,587 @@ impl ColumnStats {
     pub fn histogram(&self) -> &Option<Histogram> {
         &self.histogram
     }
+
+    pub fn ndv(&self) -> Option<u64> {
+        self.ndv
+    }
+
+    /// Returns the number of distinct non-null values, derived from the histogram and the most
+    /// common values if it isn't known.
+    pub fn estimated_ndv(&self) -> Option<u64> {
+        self.ndv.or_else(|| {
+            let histogram_ndv = self
+                .histogram
+                .as_ref()?
+                .buckets()
+                .iter()
+                .map(|bucket| bucket.ndv())
+                .sum::<u64>();
+            Some(histogram_ndv + self.mcvs.len() as u64)
+        })
+    }
+
+    pub fn sketch_mdid(&self) -> Option<u64> {
+        self.sketch_mdid
+    }
+
+    pub fn mcvs(&self) -> &[Mcv] {
+        &self.mcvs
+    }
+
+    /// Returns the frequency of `value` if it is a most common value, comparing numbers of
+    /// different types by value.
+    pub fn mcv_frequency(&self, value: &Datum) -> Option<f64> {
+        self.mcvs
+            .iter()
+            .find(|mcv| mcv.value.cmp_value(value).is_eq())
+            .map(|mcv| mcv.frequency)
+    }
+
+    /// Returns the statistics of the column over `kept_rows` of its `rows` rows, chosen
+    /// independently of the values of the column. More rows than `rows` hold the same values as
+    /// often, but statistics of no rows tell nothing of the values of any other rows.
+    pub fn scale(&self, rows: u64, kept_rows: u64) -> ColumnStats {
+        if kept_rows == rows {
+            return self.clone();
+        }
+        if rows == 0 {
+            return ColumnStats {
+                null_count: 0,
+                histogram: None,
+                ndv: None,
+                mcvs: Vec::new(),
+                sketch_mdid: None,
+                ..self.clone()
+            };
+        }
+        let fraction = kept_rows as f64 / rows as f64;
+        let scale_count = |count: u64| (count as f64 * fraction).round() as u64;
+
+        let mut scaled = self.clone();
+        scaled.null_count = scale_count(self.null_count);
+        if let Some(histogram) = &mut scaled.histogram {
+            for bucket in &mut histogram.buckets {
+                let value_count = scale_count(bucket.value_count);
+                bucket.ndv = distinct_values_kept(bucket.ndv, bucket.value_count, value_count);
+                bucket.value_count = value_count;
+            }
+        }
+        let non_null_rows = rows.saturating_sub(self.null_count);
+        let kept_non_null_rows = kept_rows.saturating_sub(scaled.null_count);
+        scaled.ndv = self
+            .ndv
+            .map(|ndv| distinct_values_kept(ndv, non_null_rows, kept_non_null_rows));
+        scaled
+    }
+
+    /// Returns the statistics of the column over the `kept_rows` of its `rows` rows holding one
+    /// of `values`.
+    pub fn restrict_to_values(&self, rows: u64, kept_rows: u64, values: &[Datum]) -> ColumnStats {
+        let mut values = values.iter().filter(|value| !value.is_null()).collect::<Vec<_>>();
+        values.sort();
+        values.dedup();
+        let (Some(min), Some(max)) = (values.first(), values.last()) else {
+            return self.restrict_to_nothing();
+        };
+
+        let histogram = self.histogram.as_ref().map(|histogram| {
+            let buckets = histogram.buckets.iter().filter_map(|bucket| {
+                let held = values
+                    .iter()
+                    .filter(|value| bucket.lower <= ***value && ***value <= bucket.upper)
+                    .filter(|value| self.mcv_frequency(value).is_none())
+                    .collect::<Vec<_>>();
+                if held.is_empty() || bucket.ndv == 0 {
+                    return None;
+                }
+                let ndv = (held.len() as u64).min(bucket.ndv);
+                let value_count = (bucket.value_count as f64 * ndv as f64 / bucket.ndv as f64).round() as u64;
+                let lower = (*held[0]).clone();
+                let upper = (*held[held.len() - 1]).clone();
+                Some(Bucket::new(lower, upper, ndv, value_count))
+            });
+            Histogram::new(buckets.collect())
+        });
+
+        let mut restricted = ColumnStats {
+            min: (*min).clone().max(self.min.clone()),
+            max: (*max).clone().min(self.max.clone()),
+            null_count: 0,
+            histogram,
+            ndv: self.estimated_ndv().map(|ndv| ndv.min(values.len() as u64)),
+            mcvs: self
+                .mcvs
+                .iter()
+                .filter(|mcv| values.contains(&&mcv.value))
+                .cloned()
+                .collect(),
+            ..self.clone()
+        };
+        restricted.renormalize_mcvs(rows, kept_rows);
+        restricted
+    }
+
+    /// Returns the statistics of the column over the `kept_rows` of its `rows` rows whose values
+    /// compare with `value` as `ordering`, or are equal to it if `or_equal`. `(Equal, false)`
+    /// keeps the values other than `value`.
+    pub fn restrict_to_range(
+        &self,
+        rows: u64,
+        kept_rows: u64,
+        value: &Datum,
+        ordering: Ordering,
+        or_equal: bool,
+    ) -> ColumnStats {
+        if ordering == Ordering::Equal && or_equal {
+            return self.restrict_to_values(rows, kept_rows, std::slice::from_ref(value));
+        }
+        if value.is_null() {
+            return self.restrict_to_nothing();
+        }
+        let satisfies = |other: &Datum| {
+            let cmp = other.cmp_value(value);
+            match ordering {
+                Ordering::Equal => cmp != Ordering::Equal,
+                _ => cmp == ordering || (or_equal && cmp == Ordering::Equal),
+            }
+        };
+
+        let mut restricted = self.clone();
+        restricted.null_count = 0;
+        restricted.mcvs.retain(|mcv| satisfies(&mcv.value));
+        match ordering {
+            Ordering::Less if value < &self.max => restricted.max = value.clone(),
+            Ordering::Greater if value > &self.min => restricted.min = value.clone(),
+            _ => {}
+        }
+
+        let mut kept_fraction = 1.0;
+        if let Some(histogram) = &mut restricted.histogram {
+            let total_ndv = histogram.buckets.iter().map(|bucket| bucket.ndv).sum::<u64>();
+            for bucket in &mut histogram.buckets {
+                let fraction = match ordering {
+                    Ordering::Less => bucket.fraction_below(value),
+                    Ordering::Greater => 1.0 - bucket.fraction_below(value),
+                    // a single value of the bucket is removed
+                    Ordering::Equal if bucket.lower <= *value && *value <= bucket.upper && bucket.ndv > 0 => {
+                        1.0 - 1.0 / bucket.ndv as f64
+                    }
+                    Ordering::Equal => 1.0,
+                };
+                match ordering {
+                    Ordering::Less if value < &bucket.upper => bucket.upper = value.clone(),
+                    Ordering::Greater if value > &bucket.lower => bucket.lower = value.clone(),
+                    _ => {}
+                }
+                bucket.value_count = (bucket.value_count as f64 * fraction).round() as u64;
+                bucket.ndv = (bucket.ndv as f64 * fraction).ceil() as u64;
+            }
+            histogram.buckets.retain(|bucket| bucket.value_count > 0);
+            if total_ndv > 0 {
+                let kept_ndv = histogram.buckets.iter().map(|bucket| bucket.ndv).sum::<u64>();
+                kept_fraction = kept_ndv as f64 / total_ndv as f64;
+            }
+        } else if ordering != Ordering::Equal {
+            if let Some(below) = value.interpolate(&self.min, &self.max) {
+                kept_fraction = if ordering == Ordering::Less { below } else { 1.0 - below };
+            }
+        }
+
+        // the distinct values other than the most common ones are kept as the histogram says
+        let mcv_count = self.mcvs.len() as u64;
+        restricted.ndv = self.ndv.map(|ndv| {
+            let rest = ndv.saturating_sub(mcv_count);
+            let rest = match ordering {
+                Ordering::Equal if self.mcv_frequency(value).is_none() => rest.saturating_sub(1),
+                Ordering::Equal => rest,
+                _ => (rest as f64 * kept_fraction).ceil() as u64,
+            };
+            rest + restricted.mcvs.len() as u64
+        });
+        restricted.renormalize_mcvs(rows, kept_rows);
+        restricted
+    }
+
+    /// Returns the statistics of the column over the `kept_rows` of its `rows` rows that are
+    /// null if `is_null`, and not null otherwise.
+    pub fn restrict_to_nulls(&self, rows: u64, kept_rows: u64, is_null: bool) -> ColumnStats {
+        if is_null {
+            return ColumnStats {
+                null_count: kept_rows,
+                ..self.restrict_to_nothing()
+            };
+        }
+        let mut restricted = ColumnStats {
+            null_count: 0,
+            ..self.clone()
+        };
+        restricted.renormalize_mcvs(rows, kept_rows);
+        restricted
+    }
+
+    /// The statistics of a column without any non-null value.
+    fn restrict_to_nothing(&self) -> ColumnStats {
+        ColumnStats {
+            null_count: 0,
+            histogram: self.histogram.as_ref().map(|_| Histogram::new(Vec::new())),
+            ndv: Some(0),
+            mcvs: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Rescales the frequencies of the most common values, which hold over `rows` rows, to the
+    /// `kept_rows` rows holding all of them.
+    fn renormalize_mcvs(&mut self, rows: u64, kept_rows: u64) {
+        if kept_rows == 0 {
+            self.mcvs.clear();
+            return;
+        }
+        let scale = rows as f64 / kept_rows as f64;
+        for mcv in &mut self.mcvs {
+            mcv.frequency = (mcv.frequency * scale).min(1.0);
+        }
+    }
+
+    /// Combines the statistics of a column over disjoint sets of rows, like the partitions of a
+    /// table, given as `(rows, stats)`.
+    ///
+    /// The histograms are concatenated when the value ranges of the sets don't overlap, and
+    /// dropped otherwise. The number of distinct values is then the sum of those of the sets, or
+    /// else at least the largest of them.
+    pub fn merge(parts: &[(u64, &ColumnStats)]) -> ColumnStats {
+        assert!(!parts.is_empty(), "no column statistics to merge");
+        let total_rows = parts.iter().map(|(rows, _)| *rows).sum::<u64>();
+        let mut merged = ColumnStats {
+            null_count: parts.iter().map(|(_, stats)| stats.null_count).sum(),
+            histogram: None,
+            ndv: Some(0),
+            mcvs: Vec::new(),
+            sketch_mdid: None,
+            ..parts[0].1.clone()
+        };
+
+        // the bounds of sets of nulls are meaningless
+        let mut parts = parts
+            .iter()
+            .filter(|(rows, stats)| *rows > stats.null_count)
+            .collect::<Vec<_>>();
+        if parts.is_empty() {
+            return merged;
+        }
+        parts.sort_by(|(_, l), (_, r)| l.min.cmp(&r.min));
+        let disjoint = parts.windows(2).all(|pair| pair[0].1.max < pair[1].1.min);
+
+        merged.min = parts[0].1.min.clone();
+        merged.max = parts.iter().map(|(_, stats)| &stats.max).max().unwrap().clone();
+        if disjoint {
+            merged.histogram = parts
+                .iter()
+                .map(|(_, stats)| stats.histogram.as_ref().map(|histogram| histogram.buckets.clone()))
+                .collect::<Option<Vec<_>>>()
+                .map(|buckets| Histogram::new(buckets.concat()));
+        }
+
+        for (rows, stats) in &parts {
+            let weight = *rows as f64 / total_rows as f64;
+            for mcv in &stats.mcvs {
+                match merged.mcvs.iter_mut().find(|merged| merged.value == mcv.value) {
+                    Some(merged) => merged.frequency += mcv.frequency * weight,
+                    None => merged.mcvs.push(Mcv::new(mcv.value.clone(), mcv.frequency * weight)),
+                }
+            }
+        }
+        merged
+            .mcvs
+            .sort_by(|l, r| r.frequency.total_cmp(&l.frequency).then_with(|| l.value.cmp(&r.value)));
+
+        let ndvs = parts
+            .iter()
+            .map(|(_, stats)| stats.estimated_ndv())
+            .collect::<Option<Vec<_>>>();
+        merged.ndv = match ndvs {
+            Some(ndvs) if disjoint => Some(ndvs.iter().sum()),
+            Some(ndvs) => ndvs.into_iter().max(),
+            None => None,
+        };
+        merged
+    }
 }
 
 #[typetag::serde]
 impl Metadata for ColumnStats {}
 
+/// Estimates the number of the `ndv` distinct values of `rows` rows left in `kept_rows` of them
+/// chosen at random, assuming every value holds as many rows.
+fn distinct_values_kept(ndv: u64, rows: u64, kept_rows: u64) -> u64 {
+    if kept_rows == 0 || ndv == 0 {
+        return 0;
+    }
+    if kept_rows >= rows {
+        return ndv;
+    }
+    let rows_per_value = rows as f64 / ndv as f64;
+    let missed = (1.0 - kept_rows as f64 / rows as f64).powf(rows_per_value);
+    ((ndv as f64 * (1.0 - missed)).ceil() as u64).clamp(1, kept_rows)
+}
+
+/// A combination of values of several columns, with the fraction of all rows holding it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JointMcv {
+    values: Vec<Datum>,
+    frequency: f64,
+}
+
+impl JointMcv {
+    pub fn new(values: Vec<Datum>, frequency: f64) -> Self {
+        Self { values, frequency }
+    }
+
+    pub fn values(&self) -> &[Datum] {
+        &self.values
+    }
+
+    pub fn frequency(&self) -> f64 {
+        self.frequency
+    }
+}
+
+/// Statistics of a group of correlated columns of a table, such as a city and its zip code,
+/// whose selectivities must not be multiplied.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultiColumnStats {
+    col_ids: Vec<usize>,
+    ndv: Option<u64>, // Number of distinct combinations of non-null values
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    mcvs: Vec<JointMcv>, // Most common combinations, values in the order of `col_ids`
+}
+
+impl MultiColumnStats {
+    pub fn new(col_ids: Vec<usize>, ndv: Option<u64>, mcvs: Vec<JointMcv>) -> Self {
+        assert!(col_ids.len() > 1, "multi-column statistics need at least two columns");
+        debug_assert!(mcvs.iter().all(|mcv| mcv.values.len() == col_ids.len()));
+        Self { col_ids, ndv, mcvs }
+    }
+
+    pub fn col_ids(&self) -> &[usize] {
+        &self.col_ids
+    }
+
+    pub fn ndv(&self) -> Option<u64> {
+        self.ndv
+    }
+
+    pub fn mcvs(&self) -> &[JointMcv] {
+        &self.mcvs
+    }
+
+    /// Returns the frequency of the values, in the order of `col_ids`, if they are a most
+    /// common combination.
+    pub fn mcv_frequency(&self, values: &[Datum]) -> Option<f64> {
+        self.mcvs
+            .iter()
+            .find(|mcv| {
+                mcv.values.len() == values.len()
+                    && mcv
+                        .values
+                        .iter()
+                        .zip(values)
+                        .all(|(mcv_value, value)| mcv_value.cmp_value(value).is_eq())
+            })
+            .map(|mcv| mcv.frequency)
+    }
+
+    /// Returns the statistics of the columns over `kept_rows` of their `rows` rows, chosen
+    /// independently of the values of the columns, like [`ColumnStats::scale`].
+    pub fn scale(&self, rows: u64, kept_rows: u64) -> MultiColumnStats {
+        if kept_rows == rows {
+            return self.clone();
+        }
+        if rows == 0 {
+            return MultiColumnStats {
+                ndv: None,
+                mcvs: Vec::new(),
+                ..self.clone()
+            };
+        }
+        MultiColumnStats {
+            ndv: self.ndv.map(|ndv| distinct_values_kept(ndv, rows, kept_rows)),
+            ..self.clone()
+        }
+    }
+}
+
+#[typetag::serde]
+impl Metadata for MultiColumnStats {}
+
+/// Where an estimated row count comes from, from the least to the most trustworthy.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EstimateSource {
+    /// Some predicate was estimated with a default selectivity, without statistics.
+    DefaultGuess,
+    /// Predicates estimated from statistics were combined as if they were independent.
+    MultiplePredicates,
+    /// A single predicate was estimated from the statistics of its column.
+    ColumnStats,
+    /// The row count of the relation, as analyzed.
+    BaseStats,
+}
+
+impl fmt::Display for EstimateSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = match self {
+            EstimateSource::DefaultGuess => "default guess",
+            EstimateSource::MultiplePredicates => "multiple predicates",
+            EstimateSource::ColumnStats => "column stats",
+            EstimateSource::BaseStats => "base stats",
+        };
+        f.write_str(source)
+    }
+}
+
+/// Derived statistics of the output of an operator.
 #[derive(Clone, Debug)]
 pub struct Statistics {
     output_row_count: u64,
 
-    /// Statistics of columns, column index -> column stat
-    column_stats: Vec<Box<dyn Metadata>>,
+    /// Where the output row count comes from
+    source: EstimateSource,
+
+    /// Statistics of output columns, column id -> column stats
+    column_stats: BTreeMap<u32, ColumnStats>,
+
+    /// Statistics of groups of correlated output columns, with the columns in the order of
+    /// `MultiColumnStats::col_ids`
+    multi_column_stats: Vec<(Vec<ColumnVar>, MultiColumnStats)>,
+
+    /// Average widths of output columns, column id -> width
+    column_widths: BTreeMap<u32, u32>,
 }
 
 impl Statistics {
-    pub const fn new(output_row_count: u64, column_stats: Vec<Box<dyn Metadata>>) -> Self {
+    pub const fn new(output_row_count: u64, source: EstimateSource, column_stats: BTreeMap<u32, ColumnStats>) -> Self {
         Self {
             output_row_count,
+            source,
             column_stats,
+            multi_column_stats: Vec::new(),
+            column_widths: BTreeMap::new(),
         }
     }
 
-    pub fn column_stats(&self) -> &Vec<Box<dyn Metadata>> {
+    pub fn source(&self) -> EstimateSource {
+        self.source
+    }
+
+    pub fn with_column_widths(mut self, column_widths: BTreeMap<u32, u32>) -> Self {
+        self.column_widths = column_widths;
+        self
+    }
+
+    pub fn column_widths(&self) -> &BTreeMap<u32, u32> {
+        &self.column_widths
+    }
+
+    /// Returns the average width of an output row, the sum of the widths of its columns, or 1 if
+    /// none are known.
+    pub fn row_width(&self) -> u32 {
+        self.column_widths.values().sum::<u32>().max(1)
+    }
+
+    pub fn with_output_row_count(mut self, output_row_count: u64) -> Self {
+        self.output_row_count = output_row_count;
+        self
+    }
+
+    pub fn with_multi_column_stats(mut self, multi_column_stats: Vec<(Vec<ColumnVar>, MultiColumnStats)>) -> Self {
+        self.multi_column_stats = multi_column_stats;
+        self
+    }
+
+    pub fn multi_column_stats(&self) -> &[(Vec<ColumnVar>, MultiColumnStats)] {
+        &self.multi_column_stats
+    }
+
+    pub fn column_stats(&self) -> &BTreeMap<u32, ColumnStats> {
         &self.column_stats
     }
+
+    pub fn column_stats_of(&self, column: &ColumnVar) -> Option<&ColumnStats> {
+        self.column_stats.get(&column.id())
+    }
+
+    /// Returns the statistics of `output_row_count` rows holding the values as often as the rows
+    /// of these statistics.
+    pub fn scale(&self, output_row_count: u64) -> Statistics {
+        let column_stats = self
+            .column_stats
+            .iter()
+            .map(|(id, stats)| (*id, stats.scale(self.output_row_count, output_row_count)));
+        let multi_column_stats = self
+            .multi_column_stats
+            .iter()
+            .map(|(group, stats)| (group.clone(), stats.scale(self.output_row_count, output_row_count)));
+        Statistics {
+            output_row_count,
+            column_stats: column_stats.collect(),
+            multi_column_stats: multi_column_stats.collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the statistics of the same rows with only the columns, like the output of a
+    /// projection.
+    pub fn project(&self, columns: &ColumnRefSet) -> Statistics {
+        let column_stats = self
+            .column_stats
+            .iter()
+            .filter(|(id, _)| columns.contains(**id))
+            .map(|(id, stats)| (*id, stats.clone()));
+        let multi_column_stats = self
+            .multi_column_stats
+            .iter()
+            .filter(|(group, _)| group.iter().all(|column| columns.contains(column.id())))
+            .cloned();
+        let column_widths = self
+            .column_widths
+            .iter()
+            .filter(|(id, _)| columns.contains(**id))
+            .map(|(id, width)| (*id, *width));
+        Statistics {
+            output_row_count: self.output_row_count,
+            source: self.source,
+            column_stats: column_stats.collect(),
+            multi_column_stats: multi_column_stats.collect(),
+            column_widths: column_widths.collect(),
+        }
+    }
+
+    /// Returns the number of distinct values of the column, which is never more than the rows.
+    pub fn column_ndv(&self, column: &ColumnVar) -> Option<u64> {
+        let ndv = self.column_stats_of(column)?.estimated_ndv()?;
+        Some(ndv.min(self.output_row_count))
+    }
 }
 
 impl Stats for Statistics {
+    /// Prefers the most trustworthy estimate, then the lowest row count.
     fn should_update(&self, new_stats: &Rc<dyn Stats>) -> bool {
         let new_stats = new_stats.as_ref().as_any().downcast_ref::<Statistics>().unwrap();
-        new_stats.output_row_count < self.output_row_count
+        (new_stats.source, Reverse(new_stats.output_row_count)) > (self.source, Reverse(self.output_row_count))
     }
 
     fn output_row_count(&self) -> u64 {
         self.output_row_count
     }
+
+    fn override_output_row_count(&self, output_row_count: u64) -> Rc<dyn Stats> {
+        Rc::new(self.scale(output_row_count))
+    }
+
+    fn explain(&self) -> String {
+        format!("rows={} from {}", self.output_row_count, self.source)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -156,6 +793,8 @@ pub struct RelationStats {
     rows: u64,
     empty: bool,
     col_stat_mdids: Vec<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    multi_col_stat_mdids: Vec<u64>,
 }
 
 impl RelationStats {
@@ -165,9 +804,15 @@ impl RelationStats {
             rows,
             empty,
             col_stat_mdids,
+            multi_col_stat_mdids: Vec::new(),
         }
     }
 
+    pub fn with_multi_col_stat_mdids(mut self, multi_col_stat_mdids: Vec<u64>) -> Self {
+        self.multi_col_stat_mdids = multi_col_stat_mdids;
+        self
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -183,6 +828,10 @@ impl RelationStats {
     pub fn col_stat_mdids(&self) -> &[u64] {
         &self.col_stat_mdids
     }
+
+    pub fn multi_col_stat_mdids(&self) -> &[u64] {
+        &self.multi_col_stat_mdids
+    }
 }
 
 #[typetag::serde]
@@ -224,8 +873,8 @@ impl ColumnMetadata {
         self.width
     }
 
-    pub fn default(&self) -> Datum {
-        self.default
+    pub fn default(&self) -> &Datum {
+        &self.default
     }
 }
 
@@ -240,12 +889,41 @@ impl IndexInfo {
     }
 }
 
+/// A constraint of a relation. Columns are referred to by their positions in the relation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Constraint {
+    PrimaryKey {
+        col_ids: Vec<usize>,
+    },
+    Unique {
+        col_ids: Vec<usize>,
+    },
+    /// The columns reference the columns of a unique key of the relation `referenced_rel_mdid`.
+    ForeignKey {
+        col_ids: Vec<usize>,
+        referenced_rel_mdid: u64,
+        referenced_col_ids: Vec<usize>,
+    },
+    NotNull {
+        col_id: usize,
+    },
+    /// A predicate over the columns of the relation, which every row satisfies or evaluates to
+    /// null on.
+    Check {
+        predicate: Rc<dyn ScalarExpression>,
+    },
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RelationMetadata {
     name: String,
     column_metadata: Vec<ColumnMetadata>,
     rel_stats_mdid: u64,
     index_info_list: Vec<IndexInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    constraints: Vec<Constraint>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    partition_info: Option<PartitionInfo>,
 }
 
 impl RelationMetadata {
@@ -260,7 +938,59 @@ impl RelationMetadata {
             column_metadata,
             rel_stats_mdid,
             index_info_list,
+            constraints: Vec::new(),
+            partition_info: None,
+        }
+    }
+
+    pub fn with_constraints(mut self, constraints: Vec<Constraint>) -> Self {
+        let column_count = self.column_metadata.len();
+        let mut primary_keys = 0;
+        for constraint in &constraints {
+            let col_ids = match constraint {
+                Constraint::PrimaryKey { col_ids } => {
+                    primary_keys += 1;
+                    col_ids.as_slice()
+                }
+                Constraint::Unique { col_ids } => col_ids.as_slice(),
+                Constraint::ForeignKey {
+                    col_ids,
+                    referenced_col_ids,
+                    ..
+                } => {
+                    assert_eq!(
+                        col_ids.len(),
+                        referenced_col_ids.len(),
+                        "foreign key must reference as many columns as it has"
+                    );
+                    col_ids.as_slice()
+                }
+                Constraint::NotNull { col_id } => std::slice::from_ref(col_id),
+                Constraint::Check { .. } => &[],
+            };
+            assert!(
+                col_ids.iter().all(|col_id| *col_id < column_count),
+                "constraint {constraint:?} refers to a column not in relation {}",
+                self.name
+            );
         }
+        assert!(
+            primary_keys <= 1,
+            "relation {} has more than one primary key",
+            self.name
+        );
+        self.constraints = constraints;
+        self
+    }
+
+    pub fn with_partition_info(mut self, partition_info: PartitionInfo) -> Self {
+        assert!(
+            partition_info.key_col_id() < self.column_metadata.len(),
+            "partition key of relation {} is not a column",
+            self.name
+        );
+        self.partition_info = Some(partition_info);
+        self
     }
 
     pub fn name(&self) -> &str {
@@ -282,6 +1012,75 @@ impl RelationMetadata {
     pub fn index_mdid(&self, id: usize) -> u64 {
         self.index_info_list[id].mdid
     }
+
+    /// Returns how the relation is partitioned, if it is.
+    pub fn partition_info(&self) -> Option<&PartitionInfo> {
+        self.partition_info.as_ref()
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    pub fn primary_key(&self) -> Option<&[usize]> {
+        self.constraints.iter().find_map(|constraint| match constraint {
+            Constraint::PrimaryKey { col_ids } => Some(col_ids.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// Returns the column sets no two rows agree on, the primary key first. Rows may still agree
+    /// on a unique key if one of them has a null in it.
+    pub fn unique_keys(&self) -> Vec<&[usize]> {
+        let mut keys = Vec::from_iter(self.primary_key());
+        keys.extend(self.constraints.iter().filter_map(|constraint| match constraint {
+            Constraint::Unique { col_ids } => Some(col_ids.as_slice()),
+            _ => None,
+        }));
+        keys
+    }
+
+    /// Returns whether the columns contain a unique key, so they are unique in the relation.
+    pub fn is_unique(&self, col_ids: &[usize]) -> bool {
+        self.unique_keys()
+            .iter()
+            .any(|key| key.iter().all(|col_id| col_ids.contains(col_id)))
+    }
+
+    /// Returns whether the column can't be null, being declared not null or part of the primary key.
+    pub fn is_not_null(&self, col_id: usize) -> bool {
+        !self.column_metadata[col_id].nullable()
+            || self.primary_key().is_some_and(|key| key.contains(&col_id))
+            || self
+                .constraints
+                .iter()
+                .any(|constraint| matches!(constraint, Constraint::NotNull { col_id: id } if *id == col_id))
+    }
+
+    /// Returns the foreign keys as `(col_ids, referenced_rel_mdid, referenced_col_ids)`.
+    pub fn foreign_keys(&self) -> Vec<(&[usize], u64, &[usize])> {
+        self.constraints
+            .iter()
+            .filter_map(|constraint| match constraint {
+                Constraint::ForeignKey {
+                    col_ids,
+                    referenced_rel_mdid,
+                    referenced_col_ids,
+                } => Some((col_ids.as_slice(), *referenced_rel_mdid, referenced_col_ids.as_slice())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn check_predicates(&self) -> Vec<&Rc<dyn ScalarExpression>> {
+        self.constraints
+            .iter()
+            .filter_map(|constraint| match constraint {
+                Constraint::Check { predicate } => Some(predicate),
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 #[typetag::serde]
@@ -289,7 +1088,57 @@ impl Metadata for RelationMetadata {}
 
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum IndexType {
+    /// Returns rows in the order of the keys, looking up equalities, ranges and nulls.
     Btree,
+    /// Looks up equalities on all of the keys, returning rows in no particular order.
+    Hash,
+    /// Keeps a bitmap of rows per key value, so any combination of comparisons and nulls can be
+    /// looked up, returning rows in no particular order.
+    Bitmap,
+}
+
+impl IndexType {
+    /// Returns whether the index returns rows in the order of its keys.
+    pub fn is_ordered(self) -> bool {
+        matches!(self, IndexType::Btree)
+    }
+
+    /// Returns whether the index can only be used when every key is looked up by equality.
+    pub fn requires_all_keys(self) -> bool {
+        matches!(self, IndexType::Hash)
+    }
+
+    /// Returns whether the index can look up the rows satisfying the predicate over its keys.
+    pub fn supports(self, predicate: &dyn ScalarExpression) -> bool {
+        if predicate.downcast_ref::<Equal>().is_some() || predicate.downcast_ref::<InList>().is_some() {
+            return true;
+        }
+        if self == IndexType::Hash {
+            return false;
+        }
+
+        if let Some(and) = predicate.downcast_ref::<And>() {
+            return and.expressions().iter().all(|expr| self.supports(expr.as_ref()));
+        }
+        if let Some(or) = predicate.downcast_ref::<Or>() {
+            return or.expressions().iter().all(|expr| self.supports(expr.as_ref()));
+        }
+        let range_or_null = predicate.downcast_ref::<LessThan>().is_some()
+            || predicate.downcast_ref::<LessThanEqual>().is_some()
+            || predicate.downcast_ref::<GreaterThan>().is_some()
+            || predicate.downcast_ref::<GreaterThanEqual>().is_some()
+            || predicate.downcast_ref::<IsNull>().is_some()
+            || predicate.downcast_ref::<IsNotNull>().is_some();
+        if range_or_null || self == IndexType::Btree {
+            return range_or_null;
+        }
+
+        // a bitmap index complements bitmaps as easily
+        match predicate.downcast_ref::<Not>() {
+            Some(not) => self.supports(not.expression()),
+            None => predicate.downcast_ref::<NotEqual>().is_some(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -299,6 +1148,10 @@ pub struct IndexMd {
     index_type: IndexType,
     key_columns: Vec<ColumnVar>,
     included_columns: Vec<ColumnVar>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    key_expressions: Vec<Rc<dyn ScalarExpression>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    predicate: Option<Rc<dyn ScalarExpression>>,
 }
 
 impl IndexMd {
@@ -309,9 +1162,29 @@ impl IndexMd {
             index_type: IndexType::Btree,
             key_columns,
             included_columns,
+            key_expressions: Vec::new(),
+            predicate: None,
         }
     }
 
+    pub fn with_index_type(mut self, index_type: IndexType) -> Self {
+        self.index_type = index_type;
+        self
+    }
+
+    /// Keys the index on expressions over the columns, like `c0 > 10`, after its key columns.
+    pub fn with_key_expressions(mut self, key_expressions: Vec<Rc<dyn ScalarExpression>>) -> Self {
+        self.key_expressions = key_expressions;
+        self
+    }
+
+    /// Makes the index partial, only holding the rows satisfying `predicate`.
+    pub fn with_predicate(mut self, predicate: Rc<dyn ScalarExpression>) -> Self {
+        assert!(predicate.is_boolean_expression());
+        self.predicate = Some(predicate);
+        self
+    }
+
     pub fn mdid(&self) -> u64 {
         self.mdid
     }
@@ -331,7 +1204,67 @@ impl IndexMd {
     pub fn included_columns(&self) -> &[ColumnVar] {
         &self.included_columns
     }
+
+    pub fn key_expressions(&self) -> &[Rc<dyn ScalarExpression>] {
+        &self.key_expressions
+    }
+
+    /// The predicate of a partial index.
+    pub fn predicate(&self) -> Option<&Rc<dyn ScalarExpression>> {
+        self.predicate.as_ref()
+    }
 }
 
 #[typetag::serde]
 impl Metadata for IndexMd {}
+
+/// A named query, whose definition replaces its references when the memo is initialized.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ViewMetadata {
+    name: String,
+    #[serde(with = "crate::serialize::logical_plan")]
+    definition: LogicalPlan,
+    column_names: Vec<String>,
+    output_columns: Vec<ColumnVar>,
+}
+
+impl ViewMetadata {
+    /// `output_columns` are the columns of the definition the view returns, named `column_names`.
+    pub fn new(
+        name: String,
+        definition: LogicalPlan,
+        column_names: Vec<String>,
+        output_columns: Vec<ColumnVar>,
+    ) -> Self {
+        assert_eq!(
+            column_names.len(),
+            output_columns.len(),
+            "view {name} must name every output column"
+        );
+        Self {
+            name,
+            definition,
+            column_names,
+            output_columns,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn definition(&self) -> &LogicalPlan {
+        &self.definition
+    }
+
+    pub fn column_names(&self) -> &[String] {
+        &self.column_names
+    }
+
+    pub fn output_columns(&self) -> &[ColumnVar] {
+        &self.output_columns
+    }
+}
+
+#[typetag::serde]
+impl Metadata for ViewMetadata {}
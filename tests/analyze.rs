@@ -0,0 +1,156 @@
+use cso_demo::analyze::{ColumnStatsBuilder, RelationStatsBuilder};
+use cso_demo::datum::Datum;
+
+fn value(v: i32) -> Option<Datum> {
+    Some(Datum::I32(v))
+}
+
+#[test]
+fn test_column_stats_from_full_values() {
+    let mut builder = ColumnStatsBuilder::new(0, "c0".to_string()).with_bucket_count(4);
+    // 0..100 twice, and 10 nulls
+    builder.add_values((0..200).map(|v| value(v % 100)));
+    builder.add_values((0..10).map(|_| None));
+
+    let stats = builder.build().unwrap();
+    assert_eq!(stats.min(), &Datum::I32(0));
+    assert_eq!(stats.max(), &Datum::I32(99));
+    assert_eq!(stats.null_count(), 10);
+    assert_eq!(stats.ndv(), Some(100));
+
+    let buckets = stats.histogram().as_ref().unwrap().buckets();
+    assert_eq!(buckets.len(), 4);
+    for (i, bucket) in buckets.iter().enumerate() {
+        assert_eq!(bucket.lower(), &Datum::I32(25 * i as i32));
+        assert_eq!(bucket.upper(), &Datum::I32(25 * i as i32 + 24));
+        assert_eq!(bucket.value_count(), 50);
+        assert_eq!(bucket.ndv(), 25);
+    }
+}
+
+#[test]
+fn test_equal_values_stay_in_one_bucket() {
+    let mut builder = ColumnStatsBuilder::new(0, "c0".to_string())
+        .with_bucket_count(3)
+        .with_mcv_count(0);
+    builder.add_values([1, 2, 2, 2, 2, 2, 2, 3, 4].map(value));
+
+    let stats = builder.build().unwrap();
+    let buckets = stats.histogram().as_ref().unwrap().buckets();
+    let bounds = buckets
+        .iter()
+        .map(|bucket| (bucket.lower().clone(), bucket.upper().clone(), bucket.value_count()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        bounds,
+        vec![(Datum::I32(1), Datum::I32(2), 7), (Datum::I32(3), Datum::I32(4), 2)]
+    );
+    let total = buckets.iter().map(|bucket| bucket.value_count()).sum::<u64>();
+    assert_eq!(total, 9);
+}
+
+#[test]
+fn test_most_common_values() {
+    let mut builder = ColumnStatsBuilder::new(0, "c0".to_string()).with_bucket_count(2);
+    // 7 appears 50 times, 3 appears 20 times, the other values of 0..32 once
+    builder.add_values((0..50).map(|_| value(7)));
+    builder.add_values((0..20).map(|_| value(3)));
+    builder.add_values((0..32).filter(|v| *v != 3 && *v != 7).map(value));
+
+    let stats = builder.build().unwrap();
+    let mcvs = stats
+        .mcvs()
+        .iter()
+        .map(|mcv| (mcv.value().clone(), mcv.frequency()))
+        .collect::<Vec<_>>();
+    assert_eq!(mcvs, vec![(Datum::I32(7), 0.5), (Datum::I32(3), 0.2)]);
+    assert_eq!(stats.mcv_frequency(&Datum::I32(3)), Some(0.2));
+    assert_eq!(stats.mcv_frequency(&Datum::I32(4)), None);
+    assert_eq!(stats.ndv(), Some(32));
+
+    // the histogram only holds the other values
+    let buckets = stats.histogram().as_ref().unwrap().buckets();
+    assert_eq!(buckets.iter().map(|bucket| bucket.value_count()).sum::<u64>(), 30);
+    assert_eq!(buckets.iter().map(|bucket| bucket.ndv()).sum::<u64>(), 30);
+
+    let stats = builder.with_mcv_count(1).build().unwrap();
+    assert_eq!(stats.mcvs().len(), 1);
+    assert_eq!(stats.mcvs()[0].value(), &Datum::I32(7));
+}
+
+#[test]
+fn test_column_without_values() {
+    let mut builder = ColumnStatsBuilder::new(0, "c0".to_string());
+    assert!(builder.build().is_none());
+    builder.add(None);
+    assert_eq!(builder.row_count(), 1);
+
+    // every row is null
+    let stats = builder.build_from_sample(100).unwrap();
+    assert_eq!(stats.null_count(), 100);
+    assert_eq!(stats.ndv(), Some(0));
+    assert!(stats.histogram().is_none());
+    assert!(stats.mcvs().is_empty());
+}
+
+#[test]
+fn test_column_stats_from_sample() {
+    // a sample of 1000 rows of 100000, every value unique, 10% nulls
+    let mut builder = ColumnStatsBuilder::new(1, "c1".to_string());
+    builder.add_values((0..1000).map(|v| if v % 10 == 0 { None } else { value(v) }));
+
+    let stats = builder.build_from_sample(100_000).unwrap();
+    assert_eq!(stats.null_count(), 10_000);
+    // all sampled values are unique, so the column is estimated to be unique
+    assert_eq!(stats.ndv(), Some(90_000));
+    let total = stats
+        .histogram()
+        .as_ref()
+        .unwrap()
+        .buckets()
+        .iter()
+        .map(|bucket| bucket.value_count())
+        .sum::<u64>();
+    assert_eq!(total, 90_000);
+
+    // every value repeats, so the sample has likely seen all of them
+    let mut builder = ColumnStatsBuilder::new(1, "c1".to_string());
+    builder.add_values((0..1000).map(|v| value(v % 20)));
+    assert_eq!(builder.build_from_sample(100_000).unwrap().ndv(), Some(20));
+}
+
+#[test]
+fn test_relation_stats() {
+    let mut builder = RelationStatsBuilder::new("t1".to_string(), &["a", "b", "c"]).with_bucket_count(2);
+    for v in 0..10 {
+        builder.add_row(&[value(v), value(v % 2), None]);
+    }
+
+    let (relation_stats, column_stats) = builder.build(&[11, 12, 13]);
+    assert_eq!(relation_stats.name(), "t1");
+    assert_eq!(relation_stats.rows(), 10);
+    assert!(!relation_stats.is_empty());
+    // c has no value, but its statistics tell it is all nulls
+    assert_eq!(relation_stats.col_stat_mdids(), &[11, 12, 13]);
+    let (_, stats) = &column_stats[2];
+    assert_eq!(stats.null_count(), 10);
+    assert_eq!(stats.ndv(), Some(0));
+
+    let (md_id, stats) = &column_stats[1];
+    assert_eq!(*md_id, 12);
+    assert_eq!(stats.col_id(), 1);
+    assert_eq!(stats.name(), "b");
+    assert_eq!(stats.ndv(), Some(2));
+
+    let (relation_stats, column_stats) = builder.with_total_rows(1000).build(&[11, 12, 13]);
+    assert_eq!(relation_stats.rows(), 1000);
+    assert_eq!(column_stats[0].1.ndv(), Some(1000));
+}
+
+#[test]
+fn test_empty_relation() {
+    let builder = RelationStatsBuilder::new("t1".to_string(), &["a"]);
+    let (relation_stats, column_stats) = builder.build(&[1]);
+    assert!(relation_stats.is_empty());
+    assert!(column_stats.is_empty());
+}
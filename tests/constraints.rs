@@ -0,0 +1,96 @@
+use cso_demo::datum::Datum;
+use cso_demo::expression::{ColumnVar, Const, GreaterThan, ScalarExpression};
+use cso_demo::metadata::Metadata;
+use cso_demo::statistics::{ColumnMetadata, Constraint, RelationMetadata};
+use std::rc::Rc;
+
+fn orders() -> RelationMetadata {
+    let columns = ["id", "customer_id", "region", "code", "amount"]
+        .iter()
+        .enumerate()
+        .map(|(i, name)| ColumnMetadata::new(name.to_string(), i as u64, *name != "amount", 4, Datum::I32(0)))
+        .collect();
+    RelationMetadata::new("orders".to_string(), columns, 1, vec![])
+}
+
+fn amount_positive() -> Rc<dyn ScalarExpression> {
+    Rc::new(GreaterThan::new(Box::new(ColumnVar::new(4)), Box::new(Const::Int32(0))))
+}
+
+#[test]
+fn test_constraints() {
+    let relation = orders().with_constraints(vec![
+        Constraint::Unique { col_ids: vec![2, 3] },
+        Constraint::PrimaryKey { col_ids: vec![0] },
+        Constraint::ForeignKey {
+            col_ids: vec![1],
+            referenced_rel_mdid: 7,
+            referenced_col_ids: vec![0],
+        },
+        Constraint::NotNull { col_id: 3 },
+        Constraint::Check {
+            predicate: amount_positive(),
+        },
+    ]);
+
+    assert_eq!(relation.constraints().len(), 5);
+    assert_eq!(relation.primary_key(), Some([0].as_slice()));
+    assert_eq!(relation.unique_keys(), vec![[0].as_slice(), [2, 3].as_slice()]);
+    assert!(relation.is_unique(&[0]));
+    assert!(relation.is_unique(&[1, 2, 3]));
+    assert!(!relation.is_unique(&[1, 2]));
+
+    assert!(relation.is_not_null(0));
+    assert!(relation.is_not_null(3));
+    assert!(relation.is_not_null(4));
+    assert!(!relation.is_not_null(2));
+
+    assert_eq!(relation.foreign_keys(), vec![([1].as_slice(), 7, [0].as_slice())]);
+    let checks = relation.check_predicates();
+    assert_eq!(checks.len(), 1);
+    assert!(checks[0].equal(amount_positive().as_ref()));
+}
+
+#[test]
+fn test_serialize_constraints() {
+    let relation = orders().with_constraints(vec![
+        Constraint::PrimaryKey { col_ids: vec![0] },
+        Constraint::ForeignKey {
+            col_ids: vec![1],
+            referenced_rel_mdid: 7,
+            referenced_col_ids: vec![0],
+        },
+        Constraint::Check {
+            predicate: amount_positive(),
+        },
+    ]);
+    let json = serde_json::to_string(&(Box::new(relation) as Box<dyn Metadata>)).unwrap();
+    let md: Box<dyn Metadata> = serde_json::from_str(&json).unwrap();
+    let relation = md.downcast_ref::<RelationMetadata>().unwrap();
+
+    assert_eq!(relation.primary_key(), Some([0].as_slice()));
+    assert_eq!(relation.foreign_keys(), vec![([1].as_slice(), 7, [0].as_slice())]);
+    assert!(relation.check_predicates()[0].equal(amount_positive().as_ref()));
+
+    // relations without constraints keep their format
+    let json = serde_json::to_string(&orders()).unwrap();
+    assert!(!json.contains("constraints"));
+    let relation: RelationMetadata = serde_json::from_str(&json).unwrap();
+    assert!(relation.constraints().is_empty());
+    assert_eq!(relation.primary_key(), None);
+}
+
+#[test]
+#[should_panic(expected = "relation orders has more than one primary key")]
+fn test_two_primary_keys() {
+    orders().with_constraints(vec![
+        Constraint::PrimaryKey { col_ids: vec![0] },
+        Constraint::PrimaryKey { col_ids: vec![1] },
+    ]);
+}
+
+#[test]
+#[should_panic(expected = "refers to a column not in relation orders")]
+fn test_unknown_column() {
+    orders().with_constraints(vec![Constraint::Unique { col_ids: vec![5] }]);
+}
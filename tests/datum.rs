@@ -0,0 +1,189 @@
+use cso_demo::analyze::ColumnStatsBuilder;
+use cso_demo::datum::Datum;
+use cso_demo::expression::Const;
+use cso_demo::statistics::{Bucket, Histogram};
+use std::collections::HashSet;
+
+#[test]
+fn test_total_order() {
+    let sorted = vec![
+        Datum::Null,
+        Datum::Bool(false),
+        Datum::Bool(true),
+        Datum::F64(f64::NEG_INFINITY),
+        Datum::I64(-3_000_000_000),
+        Datum::I32(-1),
+        Datum::decimal(-5, 1),
+        Datum::I32(1),
+        Datum::I64(1),
+        Datum::decimal(10, 1),
+        Datum::decimal(100, 2),
+        Datum::F64(1.0),
+        Datum::decimal(15, 1),
+        Datum::F64(1.5),
+        Datum::I32(2),
+        Datum::F64(f64::INFINITY),
+        Datum::F64(f64::NAN),
+        Datum::String("".to_string()),
+        Datum::String("a".to_string()),
+        Datum::String("b".to_string()),
+        Datum::Date(-1),
+        Datum::Date(19_000),
+        Datum::Timestamp(0),
+    ];
+
+    let mut shuffled = sorted.clone();
+    shuffled.reverse();
+    shuffled.swap(3, 11);
+    shuffled.sort();
+    assert_eq!(shuffled, sorted);
+
+    // values of different types are never equal
+    assert_ne!(Datum::I32(1), Datum::I64(1));
+    assert_ne!(Datum::decimal(10, 1), Datum::decimal(100, 2));
+    assert!(Datum::I32(1) < Datum::I64(1));
+    assert_eq!(Datum::F64(f64::NAN), Datum::F64(f64::NAN));
+}
+
+#[test]
+fn test_hash_agrees_with_equality() {
+    let values = [
+        Datum::Null,
+        Datum::I32(1),
+        Datum::I64(1),
+        Datum::F64(1.0),
+        Datum::F64(f64::NAN),
+        Datum::decimal(10, 1),
+        Datum::String("1".to_string()),
+        Datum::Date(1),
+        Datum::Timestamp(1),
+    ];
+    let set = values.iter().chain(values.iter()).cloned().collect::<HashSet<_>>();
+    assert_eq!(set.len(), values.len());
+}
+
+#[test]
+fn test_serialization() {
+    // the format of existing catalogs is kept
+    assert_eq!(serde_json::to_string(&Datum::I32(3)).unwrap(), r#"{"I32":3}"#);
+    assert_eq!(serde_json::to_string(&Datum::Null).unwrap(), r#""Null""#);
+
+    let values = vec![
+        Datum::Null,
+        Datum::Bool(true),
+        Datum::I64(i64::MIN),
+        Datum::F64(-0.25),
+        Datum::decimal(12345, 2),
+        Datum::String("it's".to_string()),
+        Datum::Date(19_000),
+        Datum::Timestamp(1_700_000_000_000_000),
+    ];
+    let json = serde_json::to_string(&values).unwrap();
+    assert_eq!(serde_json::from_str::<Vec<Datum>>(&json).unwrap(), values);
+}
+
+#[test]
+fn test_const_conversions() {
+    for (value, datum) in [
+        (Const::Int32(7), Datum::I32(7)),
+        (Const::Int64(1 << 40), Datum::I64(1 << 40)),
+        (Const::Str("x".to_string()), Datum::String("x".to_string())),
+    ] {
+        assert_eq!(Datum::from(&value), datum);
+        assert_eq!(Const::try_from(datum), Ok(value));
+    }
+    assert_eq!(Const::try_from(Datum::Date(1)), Err(Datum::Date(1)));
+}
+
+#[test]
+fn test_analyze_strings() {
+    let mut builder = ColumnStatsBuilder::new(0, "name".to_string()).with_mcv_count(0);
+    for name in ["bob", "alice", "carol", "alice"] {
+        builder.add(Some(Datum::String(name.to_string())));
+    }
+    builder.add(Some(Datum::Null));
+    builder.add(None);
+
+    let stats = builder.build().unwrap();
+    assert_eq!(stats.min(), &Datum::String("alice".to_string()));
+    assert_eq!(stats.max(), &Datum::String("carol".to_string()));
+    assert_eq!(stats.null_count(), 2);
+    assert_eq!(stats.ndv(), Some(3));
+}
+
+fn string(value: &str) -> Datum {
+    Datum::String(value.to_string())
+}
+
+#[test]
+fn test_distance() {
+    assert_eq!(Datum::I32(3).distance(&Datum::I64(10)), Some(7.0));
+    assert_eq!(Datum::decimal(150, 2).distance(&Datum::F64(0.5)), Some(1.0));
+    assert_eq!(Datum::Date(100).distance(&Datum::Date(70)), Some(30.0));
+    assert_eq!(Datum::Timestamp(5).distance(&Datum::Timestamp(1)), Some(4.0));
+
+    assert_eq!(Datum::Date(1).distance(&Datum::I32(1)), None);
+    assert_eq!(string("a").distance(&string("b")), None);
+    assert_eq!(Datum::Null.distance(&Datum::Null), None);
+}
+
+#[test]
+fn test_interpolate() {
+    let (lower, upper) = (Datum::I32(0), Datum::I32(100));
+    assert_eq!(Datum::I32(25).interpolate(&lower, &upper), Some(0.25));
+    assert_eq!(Datum::F64(12.5).interpolate(&lower, &upper), Some(0.125));
+    assert_eq!(Datum::decimal(505, 1).interpolate(&lower, &upper), Some(0.505));
+    // clamped to the range
+    assert_eq!(Datum::I32(-5).interpolate(&lower, &upper), Some(0.0));
+    assert_eq!(Datum::I64(500).interpolate(&lower, &upper), Some(1.0));
+
+    let (lower, upper) = (Datum::Date(19_000), Datum::Date(19_010));
+    assert_eq!(Datum::Date(19_002).interpolate(&lower, &upper), Some(0.2));
+    let (lower, upper) = (Datum::Timestamp(0), Datum::Timestamp(1_000_000));
+    assert_eq!(Datum::Timestamp(750_000).interpolate(&lower, &upper), Some(0.75));
+
+    // values of another kind, or an empty range
+    assert_eq!(Datum::Date(5).interpolate(&Datum::I32(0), &Datum::I32(10)), None);
+    assert_eq!(Datum::I32(5).interpolate(&Datum::I32(10), &Datum::I32(0)), None);
+    assert_eq!(Datum::Null.interpolate(&Datum::Null, &Datum::Null), Some(0.0));
+}
+
+#[test]
+fn test_interpolate_strings() {
+    // the shared prefix "appl" is ignored, so "f" and "w" are placed between "e" and "y"
+    let (lower, upper) = (string("apple"), string("apply"));
+    assert_eq!(string("applf").interpolate(&lower, &upper), Some(0.05));
+    assert_eq!(string("applw").interpolate(&lower, &upper), Some(0.9));
+    let position = string("applwz").interpolate(&lower, &upper).unwrap();
+    assert!(position > 0.9 && position < 0.95, "{position}");
+
+    // positions preserve the order of strings
+    let (lower, upper) = (string("a"), string("z"));
+    let positions = ["b", "bz", "c", "m", "mm", "y"]
+        .iter()
+        .map(|value| string(value).interpolate(&lower, &upper).unwrap())
+        .collect::<Vec<_>>();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]), "{positions:?}");
+    assert_eq!(string("").interpolate(&lower, &upper), Some(0.0));
+}
+
+#[test]
+fn test_partial_bucket_fraction() {
+    let histogram = Histogram::new(vec![
+        Bucket::new(Datum::I32(0), Datum::I32(10), 10, 100),
+        Bucket::new(Datum::I32(10), Datum::I32(110), 50, 300),
+    ]);
+    let buckets = histogram.buckets();
+    assert_eq!(buckets[0].fraction_below(&Datum::I32(0)), 0.0);
+    assert_eq!(buckets[0].fraction_below(&Datum::I32(4)), 0.4);
+    assert_eq!(buckets[0].fraction_below(&Datum::I32(11)), 1.0);
+    // the value of a single-value bucket is not below itself
+    let bucket = Bucket::new(string("a"), string("a"), 1, 10);
+    assert_eq!(bucket.fraction_below(&string("a")), 0.0);
+    assert_eq!(bucket.fraction_below(&string("b")), 1.0);
+
+    assert_eq!(histogram.value_count(), 400);
+    assert_eq!(histogram.fraction_below(&Datum::I32(5)), 0.125);
+    assert_eq!(histogram.fraction_below(&Datum::I32(60)), 0.625);
+    assert_eq!(histogram.fraction_below(&Datum::I32(200)), 1.0);
+}
@@ -0,0 +1,92 @@
+use cso_demo::fuzz::{FuzzCase, PlanGenerator};
+use cso_demo::metadata::{CachedMdProvider, MdAccessor};
+use cso_demo::rule::create_rule_set;
+use cso_demo::{Optimizer, Options, PhysicalPlan};
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+
+// Number of random cases, can be raised with CSO_FUZZ_ITERATIONS for longer runs.
+const DEFAULT_ITERATIONS: u64 = 500;
+
+fn iterations() -> u64 {
+    std::env::var("CSO_FUZZ_ITERATIONS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_ITERATIONS)
+}
+
+fn optimize(case: &FuzzCase) -> PhysicalPlan {
+    let md_provider = Rc::new(CachedMdProvider::new(case.md_cache().clone()));
+    let md_accessor = MdAccessor::new(md_provider);
+    let mut optimizer = Optimizer::new(Options::default());
+    optimizer.optimize(
+        case.plan().clone(),
+        case.required_properties().clone(),
+        md_accessor,
+        create_rule_set(),
+    )
+}
+
+fn check_case(case: &FuzzCase) -> Result<(), String> {
+    let plan = panic::catch_unwind(AssertUnwindSafe(|| optimize(case))).map_err(|err| {
+        let message = err
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .or_else(|| err.downcast_ref::<&str>().copied())
+            .unwrap_or("unknown panic");
+        format!("optimizer panicked: {message}")
+    })?;
+
+    let output_properties = plan.derive_output_properties();
+    if !output_properties.satisfy(case.required_properties()) {
+        return Err(format!(
+            "output properties {:?} do not satisfy required properties {:?}\n{}",
+            output_properties,
+            case.required_properties(),
+            plan.explain()
+        ));
+    }
+
+    let other_plan = optimize(case);
+    if other_plan != plan {
+        return Err(format!(
+            "optimizer is not deterministic\n{}\n{}",
+            plan.explain(),
+            other_plan.explain()
+        ));
+    }
+
+    Ok(())
+}
+
+fn sorted_metadata(case: &FuzzCase) -> Vec<(u64, String)> {
+    let mut metadata = case
+        .md_cache()
+        .iter()
+        .map(|(md_id, md)| (*md_id, format!("{md:?}")))
+        .collect::<Vec<_>>();
+    metadata.sort_by_key(|(md_id, _)| *md_id);
+    metadata
+}
+
+#[test]
+fn test_generator_is_deterministic() {
+    for seed in 0..20 {
+        let case = PlanGenerator::generate_case(seed);
+        let other_case = PlanGenerator::generate_case(seed);
+        assert_eq!(format!("{:?}", case.plan()), format!("{:?}", other_case.plan()));
+        assert_eq!(case.required_properties(), other_case.required_properties());
+        assert_eq!(sorted_metadata(&case), sorted_metadata(&other_case));
+    }
+}
+
+#[test]
+fn test_fuzz_optimizer() {
+    let failures = (0..iterations())
+        .filter_map(|seed| {
+            let case = PlanGenerator::generate_case(seed);
+            check_case(&case).err().map(|err| format!("seed {seed}: {err}"))
+        })
+        .collect::<Vec<_>>();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
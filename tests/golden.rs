@@ -0,0 +1,167 @@
+use cso_demo::datum::Datum;
+use cso_demo::dsl::golden::run_golden_dir;
+use cso_demo::dsl::{
+    format_physical_plan, match_physical_plan, parse_logical_plan, parse_required_properties, Catalog,
+};
+use cso_demo::expression::ColumnVar;
+use cso_demo::metadata::{CachedMdProvider, MdAccessor, MdCache, Metadata};
+use cso_demo::partition::{Partition, PartitionBound, PartitionInfo};
+use cso_demo::rule::create_rule_set;
+use cso_demo::statistics::{
+    Bucket, ColumnMetadata, ColumnStats, Histogram, IndexInfo, IndexMd, RelationMetadata, RelationStats,
+};
+use cso_demo::{Optimizer, Options};
+use std::rc::Rc;
+
+/// Tables:
+/// - t1(c1, c2, c3) with index IDX_1, key columns(c1) included columns(c1, c2, c3)
+/// - x(a, b, c) without index
+/// - sales(day, region, amount) partitioned by day into p1 [, 100), p2 [100, 200) and p3 [200, )
+fn md_cache() -> MdCache {
+    // mdids
+    let t1_stats_id = 1;
+    let t1_md_id = 2;
+    let column_stats_id = 3;
+    let index_md_id = 4;
+    let x_stats_id = 11;
+    let x_md_id = 12;
+
+    let t1_stats = RelationStats::new("t1".to_string(), 9011, false, vec![column_stats_id]);
+    let x_stats = RelationStats::new("x".to_string(), 9011, false, vec![column_stats_id]);
+
+    let index_md = IndexMd::new(
+        index_md_id,
+        "IDX_1".to_string(),
+        vec![ColumnVar::new(0)],
+        vec![ColumnVar::new(0), ColumnVar::new(1), ColumnVar::new(2)],
+    );
+
+    let column_md = |names: [&str; 3]| {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| ColumnMetadata::new(name.to_string(), i as u64, true, 4, Datum::I32(0)))
+            .collect::<Vec<_>>()
+    };
+    let t1_md = RelationMetadata::new(
+        "t1".to_string(),
+        column_md(["c1", "c2", "c3"]),
+        t1_stats_id,
+        vec![IndexInfo::new(index_md_id)],
+    );
+    let x_md = RelationMetadata::new("x".to_string(), column_md(["a", "b", "c"]), x_stats_id, vec![]);
+
+    let sales_stats_id = 21;
+    let sales_md_id = 22;
+    let sales_partitions = [
+        (None, Some(100), 1000),
+        (Some(100), Some(200), 2000),
+        (Some(200), None, 3000),
+    ];
+    let partition_stats = sales_partitions
+        .iter()
+        .enumerate()
+        .map(|(i, (_, _, rows))| RelationStats::new(format!("p{}", i + 1), *rows, false, vec![]))
+        .collect::<Vec<_>>();
+    let partitions = sales_partitions
+        .iter()
+        .enumerate()
+        .map(|(i, (lower, upper, _))| {
+            let bound = PartitionBound::Range {
+                lower: lower.map(Datum::I32),
+                upper: upper.map(Datum::I32),
+            };
+            Partition::new(format!("p{}", i + 1), bound, sales_stats_id + 10 + i as u64)
+        })
+        .collect();
+    let sales_stats = RelationStats::new("sales".to_string(), 6000, false, vec![]);
+    let sales_md = RelationMetadata::new(
+        "sales".to_string(),
+        column_md(["day", "region", "amount"]),
+        sales_stats_id,
+        vec![],
+    )
+    .with_partition_info(PartitionInfo::new(0, partitions));
+
+    let buckets = vec![
+        Bucket::new(Datum::I32(0), Datum::I32(1), 1, 2),
+        Bucket::new(Datum::I32(1), Datum::I32(3), 3, 3),
+    ];
+    let column_stats = ColumnStats::new(
+        1,
+        "c2".to_string(),
+        Datum::I32(0),
+        Datum::I32(3),
+        0,
+        Some(Histogram::new(buckets)),
+    );
+
+    let mut md_cache = MdCache::new();
+    md_cache.insert(t1_stats_id, Box::new(t1_stats) as Box<dyn Metadata>);
+    md_cache.insert(t1_md_id, Box::new(t1_md) as Box<dyn Metadata>);
+    md_cache.insert(column_stats_id, Box::new(column_stats) as Box<dyn Metadata>);
+    md_cache.insert(index_md_id, Box::new(index_md) as Box<dyn Metadata>);
+    md_cache.insert(x_stats_id, Box::new(x_stats) as Box<dyn Metadata>);
+    md_cache.insert(x_md_id, Box::new(x_md) as Box<dyn Metadata>);
+    md_cache.insert(sales_stats_id, Box::new(sales_stats) as Box<dyn Metadata>);
+    md_cache.insert(sales_md_id, Box::new(sales_md) as Box<dyn Metadata>);
+    for (i, stats) in partition_stats.into_iter().enumerate() {
+        md_cache.insert(sales_stats_id + 10 + i as u64, Box::new(stats) as Box<dyn Metadata>);
+    }
+    md_cache
+}
+
+#[test]
+fn test_golden_files() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+    if let Err(diff) = run_golden_dir(dir, &md_cache()) {
+        panic!("golden test failed:\n{diff}");
+    }
+}
+
+#[test]
+fn test_match_physical_plan_reports_diff() {
+    let md_cache = md_cache();
+    let catalog = Catalog::from_md_cache(&md_cache);
+
+    let plan = parse_logical_plan("Filter[IsNull(c1)] <- Scan[x: c0, c1, c2]", &catalog).unwrap();
+    let required_properties = parse_required_properties("").unwrap();
+    let md_accessor = MdAccessor::new(Rc::new(CachedMdProvider::new(md_cache)));
+    let mut optimizer = Optimizer::new(Options::default());
+    let physical_plan = optimizer.optimize(plan, required_properties, md_accessor, create_rule_set());
+
+    assert_eq!(
+        format_physical_plan(&physical_plan, &catalog),
+        "Filter[IsNull(c1)]\n<- Scan[x: c0, c1, c2]"
+    );
+    assert!(match_physical_plan(&physical_plan, "Filter[IsNull(c1)] <- Scan[x: c0,c1,c2]", &catalog).is_ok());
+
+    let diff = match_physical_plan(&physical_plan, "Filter[IsNull(c0)] <- Scan[x: c0, c1, c2]", &catalog).unwrap_err();
+    assert_eq!(
+        diff,
+        "+ Filter[IsNull(c1)]\n+ <- Scan[x: c0, c1, c2]\n- Filter[IsNull(c0)] <- Scan[x: c0, c1, c2]\n"
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    let catalog = Catalog::from_md_cache(&md_cache());
+
+    let err = parse_logical_plan("Scan[unknown: c0]", &catalog).err().unwrap();
+    assert_eq!(err.message(), "unknown table 'unknown'");
+    assert_eq!(err.position(), 5);
+
+    let err = parse_logical_plan("Filter[c0] <- Scan[x: c0]", &catalog).err().unwrap();
+    assert_eq!(err.message(), "expected boolean expression");
+
+    let err = parse_logical_plan("Scan[x: c0] Scan[x: c1]", &catalog).err().unwrap();
+    assert_eq!(err.position(), 12);
+
+    // `<-` within an operator is a comparison with a negative number
+    let plan = parse_logical_plan("Filter[c0<-5] <- Scan[x: c0, c1, c2]", &catalog).unwrap();
+    let expected = parse_logical_plan("Filter[c0 < -5] <- Scan[x: c0, c1, c2]", &catalog).unwrap();
+    assert_eq!(format!("{plan:?}"), format!("{expected:?}"));
+
+    assert!(parse_required_properties("c0 desc nulls last, c1").is_ok());
+    assert!(parse_required_properties("c0 nulls middle").is_err());
+}
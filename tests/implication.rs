@@ -0,0 +1,131 @@
+use cso_demo::expression::{
+    implies, And, ColumnVar, Const, Equal, GreaterThan, GreaterThanEqual, InList, IsNotNull, IsNull, LessThan,
+    LessThanEqual, Not, NotEqual, Or, ScalarExpression,
+};
+use std::rc::Rc;
+use std::slice;
+
+fn column(id: u32) -> Box<dyn ScalarExpression> {
+    Box::new(ColumnVar::new(id))
+}
+
+fn int(value: i32) -> Box<dyn ScalarExpression> {
+    Box::new(Const::Int32(value))
+}
+
+fn assert_implies(premises: &[Rc<dyn ScalarExpression>], conclusion: &dyn ScalarExpression, expected: bool) {
+    assert_eq!(
+        implies(premises, conclusion),
+        expected,
+        "{premises:?} implies {conclusion:?}"
+    );
+}
+
+#[test]
+fn test_identical() {
+    let premise: Rc<dyn ScalarExpression> = Rc::new(Equal::new(column(0), column(1)));
+    assert_implies(slice::from_ref(&premise), premise.as_ref(), true);
+    assert_implies(slice::from_ref(&premise), &Equal::new(column(0), column(2)), false);
+}
+
+#[test]
+fn test_ranges() {
+    let lt_10: Rc<dyn ScalarExpression> = Rc::new(LessThan::new(column(0), int(10)));
+    assert_implies(slice::from_ref(&lt_10), &LessThan::new(column(0), int(10)), true);
+    assert_implies(slice::from_ref(&lt_10), &LessThanEqual::new(column(0), int(10)), true);
+    assert_implies(slice::from_ref(&lt_10), &LessThan::new(column(0), int(20)), true);
+    assert_implies(slice::from_ref(&lt_10), &NotEqual::new(column(0), int(10)), true);
+    assert_implies(slice::from_ref(&lt_10), &LessThan::new(column(0), int(5)), false);
+    assert_implies(slice::from_ref(&lt_10), &GreaterThan::new(column(0), int(0)), false);
+    assert_implies(slice::from_ref(&lt_10), &LessThan::new(column(1), int(20)), false);
+    // the constant on the left
+    assert_implies(slice::from_ref(&lt_10), &GreaterThan::new(int(20), column(0)), true);
+
+    let le_10: Rc<dyn ScalarExpression> = Rc::new(LessThanEqual::new(column(0), int(10)));
+    assert_implies(slice::from_ref(&le_10), &LessThan::new(column(0), int(10)), false);
+    assert_implies(
+        slice::from_ref(&le_10),
+        &LessThan::new(column(0), Box::new(Const::Int64(11))),
+        true,
+    );
+    assert_implies(slice::from_ref(&le_10), &NotEqual::new(column(0), int(10)), false);
+
+    let ge_10: Rc<dyn ScalarExpression> = Rc::new(GreaterThanEqual::new(column(0), int(10)));
+    assert_implies(slice::from_ref(&ge_10), &GreaterThan::new(column(0), int(9)), true);
+    assert_implies(slice::from_ref(&ge_10), &GreaterThan::new(column(0), int(10)), false);
+    assert_implies(
+        slice::from_ref(&ge_10),
+        &Not::new(Box::new(Equal::new(column(0), int(5)))),
+        true,
+    );
+}
+
+#[test]
+fn test_values() {
+    let eq_5: Rc<dyn ScalarExpression> = Rc::new(Equal::new(column(0), int(5)));
+    assert_implies(slice::from_ref(&eq_5), &LessThan::new(column(0), int(10)), true);
+    assert_implies(
+        slice::from_ref(&eq_5),
+        &InList::new(column(0), vec![int(1), int(5)]),
+        true,
+    );
+    assert_implies(slice::from_ref(&eq_5), &NotEqual::new(column(0), int(5)), false);
+
+    let in_list: Rc<dyn ScalarExpression> = Rc::new(InList::new(column(0), vec![int(1), int(5)]));
+    assert_implies(slice::from_ref(&in_list), &LessThanEqual::new(column(0), int(5)), true);
+    assert_implies(slice::from_ref(&in_list), &GreaterThan::new(column(0), int(1)), false);
+}
+
+#[test]
+fn test_nulls() {
+    let is_null: Rc<dyn ScalarExpression> = Rc::new(IsNull::new(column(0)));
+    assert_implies(slice::from_ref(&is_null), &IsNull::new(column(0)), true);
+    assert_implies(slice::from_ref(&is_null), &IsNotNull::new(column(0)), false);
+
+    // comparisons never hold on null
+    let gt_5: Rc<dyn ScalarExpression> = Rc::new(GreaterThan::new(column(0), int(5)));
+    assert_implies(slice::from_ref(&gt_5), &IsNotNull::new(column(0)), true);
+    assert_implies(
+        slice::from_ref(&gt_5),
+        &Not::new(Box::new(IsNull::new(column(0)))),
+        true,
+    );
+    let not_null: Rc<dyn ScalarExpression> = Rc::new(IsNotNull::new(column(0)));
+    assert_implies(slice::from_ref(&not_null), &GreaterThan::new(column(0), int(5)), false);
+}
+
+#[test]
+fn test_and_or() {
+    let premises: Vec<Rc<dyn ScalarExpression>> = vec![
+        Rc::new(And::new(vec![
+            Rc::new(GreaterThan::new(column(0), int(5))),
+            Rc::new(Equal::new(column(1), int(1))),
+        ])),
+        Rc::new(IsNull::new(column(2))),
+    ];
+    let conclusion = And::new(vec![
+        Rc::new(GreaterThan::new(column(0), int(0))),
+        Rc::new(IsNull::new(column(2))),
+    ]);
+    assert_implies(&premises, &conclusion, true);
+    let conclusion = And::new(vec![
+        Rc::new(GreaterThan::new(column(0), int(0))),
+        Rc::new(IsNull::new(column(3))),
+    ]);
+    assert_implies(&premises, &conclusion, false);
+
+    // any disjunct of the conclusion
+    let conclusion = Or::new(vec![
+        Box::new(IsNull::new(column(3))),
+        Box::new(Equal::new(column(1), int(1))),
+    ]);
+    assert_implies(&premises, &conclusion, true);
+
+    // every disjunct of a premise
+    let premise: Rc<dyn ScalarExpression> = Rc::new(Or::new(vec![
+        Box::new(Equal::new(column(0), int(1))),
+        Box::new(GreaterThan::new(column(0), int(5))),
+    ]));
+    assert_implies(slice::from_ref(&premise), &GreaterThan::new(column(0), int(0)), true);
+    assert_implies(slice::from_ref(&premise), &GreaterThan::new(column(0), int(2)), false);
+}
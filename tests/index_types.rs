@@ -0,0 +1,362 @@
+use cso_core::operator::LogicalOperator;
+use cso_demo::datum::Datum;
+use cso_demo::dsl::{match_physical_plan, parse_logical_plan, parse_required_properties, Catalog};
+use cso_demo::expression::{
+    And, ColumnVar, Const, Equal, GreaterThan, InList, IsNull, LessThan, Not, NotEqual, Or, ScalarExpression,
+};
+use cso_demo::metadata::{CachedMdProvider, MdAccessor, MdAccessorExt, MdCache, Metadata};
+use cso_demo::operator::logical_filter::LogicalFilter;
+use cso_demo::operator::logical_index_scan::LogicalIndexScan;
+use cso_demo::operator::logical_scan::{LogicalScan, TableDesc};
+use cso_demo::rule::create_rule_set;
+use cso_demo::statistics::{ColumnMetadata, IndexInfo, IndexMd, IndexType, RelationMetadata, RelationStats};
+use cso_demo::{Demo, Optimizer, Options, PhysicalPlan};
+use std::rc::Rc;
+
+fn column(id: u32) -> Box<dyn ScalarExpression> {
+    Box::new(ColumnVar::new(id))
+}
+
+fn int(value: i32) -> Box<dyn ScalarExpression> {
+    Box::new(Const::Int32(value))
+}
+
+fn over_ten() -> Rc<dyn ScalarExpression> {
+    Rc::new(GreaterThan::new(column(2), int(10)))
+}
+
+/// Tables of columns (c0, c1, c2), each with one index including all columns:
+/// - h with a hash index H on c0
+/// - hh with a hash index HH on (c0, c1)
+/// - b with a bitmap index B on c1
+/// - e with a btree index E on the expression `c2 > 10`
+/// - p with a partial btree index P on c0 of the rows where `c2 IS NULL`
+/// - r with a partial btree index R on c0 of the rows where `c2 > 10`
+/// - n with a btree index N on c0 including no other column
+fn md_cache() -> MdCache {
+    let all_columns = || vec![ColumnVar::new(0), ColumnVar::new(1), ColumnVar::new(2)];
+    let indexes = [
+        (
+            "h",
+            IndexMd::new(0, "H".to_string(), vec![ColumnVar::new(0)], all_columns()).with_index_type(IndexType::Hash),
+        ),
+        (
+            "hh",
+            IndexMd::new(
+                0,
+                "HH".to_string(),
+                vec![ColumnVar::new(0), ColumnVar::new(1)],
+                all_columns(),
+            )
+            .with_index_type(IndexType::Hash),
+        ),
+        (
+            "b",
+            IndexMd::new(0, "B".to_string(), vec![ColumnVar::new(1)], all_columns()).with_index_type(IndexType::Bitmap),
+        ),
+        (
+            "e",
+            IndexMd::new(0, "E".to_string(), vec![], all_columns()).with_key_expressions(vec![over_ten()]),
+        ),
+        (
+            "p",
+            IndexMd::new(0, "P".to_string(), vec![ColumnVar::new(0)], all_columns())
+                .with_predicate(Rc::new(IsNull::new(column(2)))),
+        ),
+        (
+            "r",
+            IndexMd::new(0, "R".to_string(), vec![ColumnVar::new(0)], all_columns()).with_predicate(over_ten()),
+        ),
+        (
+            "n",
+            IndexMd::new(0, "N".to_string(), vec![ColumnVar::new(0)], vec![ColumnVar::new(0)]),
+        ),
+    ];
+
+    let mut md_cache = MdCache::new();
+    for (i, (name, index_md)) in indexes.into_iter().enumerate() {
+        let (relation_md_id, stats_md_id, index_md_id) = (i as u64 * 10 + 1, i as u64 * 10 + 2, i as u64 * 10 + 3);
+        let columns = ["c0", "c1", "c2"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| ColumnMetadata::new(name.to_string(), i as u64, true, 4, Datum::I32(0)))
+            .collect();
+        let relation_md = RelationMetadata::new(
+            name.to_string(),
+            columns,
+            stats_md_id,
+            vec![IndexInfo::new(index_md_id)],
+        );
+        let stats = RelationStats::new(name.to_string(), 10_000, false, vec![]);
+        md_cache.insert(relation_md_id, Box::new(relation_md) as Box<dyn Metadata>);
+        md_cache.insert(stats_md_id, Box::new(stats) as Box<dyn Metadata>);
+        md_cache.insert(index_md_id, Box::new(index_md) as Box<dyn Metadata>);
+    }
+    md_cache
+}
+
+fn assert_plan(plan: &str, order: &str, expected: &str) {
+    assert_plan_with_options(Options::default(), plan, order, expected);
+}
+
+fn assert_plan_with_options(options: Options, plan: &str, order: &str, expected: &str) -> PhysicalPlan {
+    let md_cache = md_cache();
+    let catalog = Catalog::from_md_cache(&md_cache);
+    let plan = parse_logical_plan(plan, &catalog).unwrap();
+    let required_properties = parse_required_properties(order).unwrap();
+    let md_accessor = MdAccessor::new(Rc::new(CachedMdProvider::new(md_cache)));
+    let mut optimizer = Optimizer::new(options);
+    let physical_plan = optimizer.optimize(plan, required_properties, md_accessor, create_rule_set());
+    if let Err(diff) = match_physical_plan(&physical_plan, expected, &catalog) {
+        panic!("unexpected plan:\n{diff}");
+    }
+    physical_plan
+}
+
+#[test]
+fn test_supported_predicates() {
+    let eq = Equal::new(column(0), int(1));
+    let lt = LessThan::new(column(0), int(1));
+    let ne = NotEqual::new(column(0), int(1));
+    let in_list = InList::new(column(0), vec![int(1), int(2)]);
+    let is_null = IsNull::new(column(0));
+    let not_null = Not::new(Box::new(is_null.clone()));
+    let or = Or::new(vec![Box::new(eq.clone()), Box::new(lt.clone())]);
+
+    let supported = |index_type: IndexType| {
+        let predicates: [&dyn ScalarExpression; 7] = [&eq, &in_list, &lt, &is_null, &or, &ne, &not_null];
+        predicates.map(|predicate| index_type.supports(predicate))
+    };
+    assert_eq!(
+        supported(IndexType::Btree),
+        [true, true, true, true, true, false, false]
+    );
+    assert_eq!(
+        supported(IndexType::Hash),
+        [true, true, false, false, false, false, false]
+    );
+    assert_eq!(supported(IndexType::Bitmap), [true; 7]);
+
+    assert!(IndexType::Btree.is_ordered());
+    assert!(!IndexType::Hash.is_ordered() && !IndexType::Bitmap.is_ordered());
+}
+
+#[test]
+fn test_hash_index() {
+    assert_plan(
+        "Filter[In(c0, 1, 2)] <- Scan[h: c0, c1, c2]",
+        "",
+        "IndexScan[h.H: c0, c1, c2; And(In(c0, 1, 2))]",
+    );
+    // a hash index returns rows in no order
+    assert_plan(
+        "Filter[c0 = 1] <- Scan[h: c0, c1, c2]",
+        "c0",
+        "Sort[c0] <- IndexScan[h.H: c0, c1, c2; And(c0 = 1)]",
+    );
+    // nor looks up ranges
+    assert_plan(
+        "Filter[c0 < 1] <- Scan[h: c0, c1, c2]",
+        "",
+        "Filter[c0 < 1] <- Scan[h: c0, c1, c2]",
+    );
+}
+
+#[test]
+fn test_hash_index_needs_all_keys() {
+    assert_plan(
+        "Filter[c0 = 1] <- Scan[hh: c0, c1, c2]",
+        "",
+        "Filter[c0 = 1] <- Scan[hh: c0, c1, c2]",
+    );
+    assert_plan(
+        "Filter[And(c0 = 1, c1 = 2, c2 = 3)] <- Scan[hh: c0, c1, c2]",
+        "",
+        "Filter[And(c2 = 3)] <- IndexScan[hh.HH: c0, c1, c2; And(c0 = 1, c1 = 2)]",
+    );
+}
+
+#[test]
+fn test_bitmap_index() {
+    assert_plan(
+        "Filter[Not(c1 = 1)] <- Scan[b: c0, c1, c2]",
+        "",
+        "IndexScan[b.B: c0, c1, c2; And(Not(c1 = 1))]",
+    );
+    assert_plan(
+        "Filter[c1 != 1] <- Scan[b: c0, c1, c2]",
+        "c1",
+        "Sort[c1] <- IndexScan[b.B: c0, c1, c2; And(c1 != 1)]",
+    );
+}
+
+#[test]
+fn test_expression_index() {
+    assert_plan(
+        "Filter[c2 > 10] <- Scan[e: c0, c1, c2]",
+        "",
+        "IndexScan[e.E: c0, c1, c2; And(c2 > 10)]",
+    );
+    // the key expression compared with a constant
+    assert_plan(
+        "Filter[And((c2 > 10) = 1, c0 = 1)] <- Scan[e: c0, c1, c2]",
+        "",
+        "Filter[And(c0 = 1)] <- IndexScan[e.E: c0, c1, c2; And((c2 > 10) = 1)]",
+    );
+    assert_plan(
+        "Filter[c2 > 5] <- Scan[e: c0, c1, c2]",
+        "",
+        "Filter[c2 > 5] <- Scan[e: c0, c1, c2]",
+    );
+}
+
+#[test]
+fn test_partial_index() {
+    // the index holds every row the filter needs, and they all satisfy `c2 IS NULL`
+    assert_plan(
+        "Filter[And(c0 = 1, IsNull(c2))] <- Scan[p: c0, c1, c2]",
+        "",
+        "IndexScan[p.P: c0, c1, c2; And(c0 = 1)]",
+    );
+    assert_plan(
+        "Filter[c0 = 1] <- Scan[p: c0, c1, c2]",
+        "",
+        "Filter[c0 = 1] <- Scan[p: c0, c1, c2]",
+    );
+    assert_plan(
+        "Filter[And(c0 = 1, c2 > 20)] <- Scan[r: c0, c1, c2]",
+        "",
+        "Filter[And(c2 > 20)] <- IndexScan[r.R: c0, c1, c2; And(c0 = 1)]",
+    );
+    assert_plan(
+        "Filter[And(c0 = 1, 10 < c2)] <- Scan[r: c0, c1, c2]",
+        "",
+        "IndexScan[r.R: c0, c1, c2; And(c0 = 1)]",
+    );
+    assert_plan(
+        "Filter[And(c0 = 1, c2 > 5)] <- Scan[r: c0, c1, c2]",
+        "",
+        "Filter[And(c0 = 1, c2 > 5)] <- Scan[r: c0, c1, c2]",
+    );
+}
+
+#[test]
+fn test_index_scan_statistics() {
+    let md_cache = md_cache();
+    let catalog = Catalog::from_md_cache(&md_cache);
+    let md_accessor = MdAccessor::new(Rc::new(CachedMdProvider::new(md_cache)));
+    let derive = |table: &str, predicate: Rc<dyn ScalarExpression>| {
+        let relation_md_id = catalog.table_md_id(table).unwrap();
+        let index_md = md_accessor
+            .retrieve_index(md_accessor.retrieve_relation(relation_md_id).unwrap().index_mdid(0))
+            .unwrap();
+        let output_columns = vec![ColumnVar::new(0), ColumnVar::new(1), ColumnVar::new(2)];
+        let index_scan = LogicalIndexScan::new(TableDesc::new(relation_md_id), &index_md, output_columns, predicate);
+        LogicalOperator::<Demo>::derive_statistics(&index_scan, &md_accessor, &[]).output_row_count()
+    };
+
+    let c0_is_1 = || Rc::new(And::new(vec![Rc::new(Equal::new(column(0), int(1)))]));
+    assert_eq!(derive("h", c0_is_1()), 50);
+    // only the rows of the partial index where `c2 > 10` are looked up
+    assert_eq!(derive("r", c0_is_1()), 17);
+
+    // a lookup implying the predicate of the partial index finds as many rows as the filter
+    let filter_rows = |table: &str, predicate: Rc<dyn ScalarExpression>| {
+        let relation_md_id = catalog.table_md_id(table).unwrap();
+        let output_columns = vec![ColumnVar::new(0), ColumnVar::new(1), ColumnVar::new(2)];
+        let scan = LogicalScan::new(TableDesc::new(relation_md_id), output_columns);
+        let scan_stats = LogicalOperator::<Demo>::derive_statistics(&scan, &md_accessor, &[]);
+        let filter = LogicalFilter::new(predicate);
+        LogicalOperator::<Demo>::derive_statistics(&filter, &md_accessor, &[scan_stats]).output_row_count()
+    };
+    let over_twenty = || -> Rc<dyn ScalarExpression> {
+        Rc::new(And::new(vec![
+            Rc::new(Equal::new(column(0), int(1))),
+            Rc::new(GreaterThan::new(column(2), int(20))),
+        ]))
+    };
+    assert_eq!(derive("r", over_twenty()), filter_rows("r", over_twenty()));
+    assert_eq!(derive("r", over_twenty()), 17);
+    let c2_is_null = || -> Rc<dyn ScalarExpression> {
+        Rc::new(And::new(vec![
+            Rc::new(Equal::new(column(0), int(1))),
+            Rc::new(IsNull::new(column(2))),
+        ]))
+    };
+    assert_eq!(derive("p", c2_is_null()), filter_rows("p", c2_is_null()));
+}
+
+#[test]
+fn test_non_covering_index() {
+    // few rows are fetched from the table
+    assert_plan(
+        "Filter[c0 = 1] <- Scan[n: c0, c1, c2]",
+        "",
+        "IndexScan[n.N: c0, c1, c2; And(c0 = 1)]",
+    );
+    // fetching almost every row costs more than scanning the table
+    assert_plan(
+        "Filter[IsNotNull(c0)] <- Scan[n: c0, c1, c2]",
+        "",
+        "Filter[IsNotNull(c0)] <- Scan[n: c0, c1, c2]",
+    );
+    // unless the index includes the columns
+    assert_plan(
+        "Filter[IsNotNull(c0)] <- Scan[n: c0]",
+        "",
+        "IndexScan[n.N: c0; And(IsNotNull(c0))]",
+    );
+}
+
+#[test]
+fn test_row_count_overrides() {
+    let catalog = Catalog::from_md_cache(&md_cache());
+    let n = catalog.table_md_id("n").unwrap();
+
+    // a table this large is cheaper to scan than to fetch a row at a time
+    let large_table = Options::default().with_relation_row_count(n, 100_000_000);
+    let plan = assert_plan_with_options(
+        large_table.clone(),
+        "Filter[c0 = 1] <- Scan[n: c0, c1, c2]",
+        "",
+        "Filter[c0 = 1] <- Scan[n: c0, c1, c2]",
+    );
+    let explain = plan.explain();
+    assert!(
+        explain.lines().last().unwrap().contains("[rows=100000000 "),
+        "{explain}"
+    );
+
+    // the filter, and the index scan of its group, are assumed to return few rows
+    let few_rows = Options::default().with_operator_row_count(vec![], 10);
+    let plan = assert_plan_with_options(
+        few_rows,
+        "Filter[IsNotNull(c0)] <- Scan[n: c0, c1, c2]",
+        "",
+        "IndexScan[n.N: c0, c1, c2; And(IsNotNull(c0))]",
+    );
+    assert!(plan.explain().contains("[rows=10 "));
+
+    // the overrides are kept in minidumps
+    let json = serde_json::to_string(&large_table).unwrap();
+    let options: Options = serde_json::from_str(&json).unwrap();
+    assert_eq!(options.relation_row_counts(), &[(n, 100_000_000)]);
+    let options: Options = serde_json::from_str("{}").unwrap();
+    assert!(options.relation_row_counts().is_empty());
+}
+
+#[test]
+fn test_serialize_index_md() {
+    let index_md = IndexMd::new(1, "E".to_string(), vec![ColumnVar::new(0)], vec![])
+        .with_index_type(IndexType::Bitmap)
+        .with_key_expressions(vec![over_ten()])
+        .with_predicate(over_ten());
+    let json = serde_json::to_string(&(Box::new(index_md) as Box<dyn Metadata>)).unwrap();
+    let md: Box<dyn Metadata> = serde_json::from_str(&json).unwrap();
+    let index_md = md.downcast_ref::<IndexMd>().unwrap();
+
+    assert_eq!(index_md.index_type(), IndexType::Bitmap);
+    assert_eq!(index_md.key_columns(), &[ColumnVar::new(0)]);
+    assert!(index_md.key_expressions()[0].as_ref() == over_ten().as_ref());
+    assert!(index_md.predicate().unwrap().as_ref() == over_ten().as_ref());
+}
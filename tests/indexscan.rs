@@ -188,13 +188,6 @@ fn expected_physical_plan_without_index() -> PhysicalPlan {
     let filter = PhysicalFilter::new(Rc::new(predicate));
     let filter = PhysicalPlan::new(Rc::new(filter), vec![scan]);
 
-    let project = vec![
-        Rc::new(ColumnVar::new(1)) as Rc<dyn ScalarExpression>,
-        Rc::new(ColumnVar::new(2)) as Rc<dyn ScalarExpression>,
-    ];
-    let project = PhysicalProject::new(project);
-    let project = PhysicalPlan::new(Rc::new(project), vec![filter]);
-
     let order = OrderSpec {
         order_desc: vec![Ordering {
             key: ColumnVar::new(0),
@@ -203,13 +196,20 @@ fn expected_physical_plan_without_index() -> PhysicalPlan {
         }],
     };
     let sort = PhysicalSort::new(order);
-    PhysicalPlan::new(Rc::new(sort), vec![project])
+    let sort = PhysicalPlan::new(Rc::new(sort), vec![filter]);
+
+    let project = vec![
+        Rc::new(ColumnVar::new(1)) as Rc<dyn ScalarExpression>,
+        Rc::new(ColumnVar::new(2)) as Rc<dyn ScalarExpression>,
+    ];
+    let project = PhysicalProject::new(project);
+    PhysicalPlan::new(Rc::new(project), vec![sort])
 }
 
 // can not cover filter
 // sql: select c2, c3 from t1 where c2 is null order by c1;
 // idx: key columns(c1) included columns(c1, c2, c3)
-// Sort(c1) -> project(c2, c3) -> filter(c2) -> Scan
+// project(c2, c3) -> Sort(c1) -> filter(c2) -> Scan, as the project drops c1
 #[test]
 fn test_sort_project_index_scan_not_matched() {
     let mut optimizer = Optimizer::new(Options::default());
@@ -0,0 +1,108 @@
+use cso_core::metadata::MdProvider as _;
+use cso_demo::dsl::{match_physical_plan, parse_logical_plan, parse_required_properties, Catalog};
+use cso_demo::metadata::{JsonDirMdProvider, MdAccessor, MdError, MdProvider};
+use cso_demo::rule::create_rule_set;
+use cso_demo::statistics::{RelationMetadata, RelationStats};
+use cso_demo::{Optimizer, Options};
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+// tests/catalog holds t1 (mdid 2) with index IDX_1 and x (mdid 12) without index.
+fn catalog_dir() -> PathBuf {
+    PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/catalog"))
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cso-json-md-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_optimize_with_json_catalog() {
+    let mut catalog = Catalog::new();
+    catalog.add_table("t1", 2);
+
+    let plan = parse_logical_plan(
+        "Project[c1, c2] <- Filter[IsNull(c0)] <- Scan[t1: c0, c1, c2]",
+        &catalog,
+    )
+    .unwrap();
+    let required_properties = parse_required_properties("c0").unwrap();
+
+    let md_provider: Rc<MdProvider> = Rc::new(JsonDirMdProvider::new(catalog_dir()));
+    let mut optimizer = Optimizer::new(Options::default());
+    let physical_plan = optimizer.optimize(
+        plan,
+        required_properties,
+        MdAccessor::new(md_provider),
+        create_rule_set(),
+    );
+
+    let expected = "Project[c1, c2] <- IndexScan[t1.IDX_1: c0, c1, c2; And(IsNull(c0))]";
+    assert_eq!(match_physical_plan(&physical_plan, expected, &catalog), Ok(()));
+}
+
+#[test]
+fn test_metadata_is_loaded_lazily() {
+    let dir = temp_dir("lazy");
+    let md_provider = JsonDirMdProvider::new(&dir);
+
+    let err = md_provider.retrieve_metadata(&1).err().unwrap();
+    assert!(matches!(err, MdError::NotFound { .. }));
+    assert_eq!(err.to_string(), "metadata 1 not found");
+
+    fs::copy(catalog_dir().join("1.json"), dir.join("1.json")).unwrap();
+    let md = md_provider.retrieve_metadata(&1).unwrap();
+    assert_eq!(md.downcast_ref::<RelationStats>().unwrap().name(), "t1");
+
+    // loaded metadata is cached
+    fs::remove_file(dir.join("1.json")).unwrap();
+    assert!(md_provider.retrieve_metadata(&1).is_ok());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_malformed_metadata() {
+    let dir = temp_dir("malformed");
+    fs::write(dir.join("1.json"), r#"{"type": "RelationStats", "name": "t1"}"#).unwrap();
+    fs::write(dir.join("2.json"), r#"{"type": "Unknown"}"#).unwrap();
+    let md_provider = JsonDirMdProvider::new(&dir);
+
+    let err = md_provider.retrieve_metadata(&1).err().unwrap();
+    assert_eq!(err.md_id(), "1");
+    let message = err.to_string();
+    assert!(message.starts_with("malformed metadata 1 in "), "{message}");
+    assert!(message.contains("1.json"), "{message}");
+    assert!(message.contains("missing field `rows`"), "{message}");
+
+    let err = md_provider.retrieve_metadata(&2).err().unwrap();
+    assert!(err.to_string().contains("unknown variant `Unknown`"), "{err}");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_write_md_cache() {
+    let md_provider = JsonDirMdProvider::new(catalog_dir());
+    let mut md_cache = cso_demo::metadata::MdCache::new();
+    for md_id in [1, 2, 3, 4] {
+        md_cache.insert(md_id, md_provider.retrieve_metadata(&md_id).unwrap());
+    }
+
+    let dir = temp_dir("write");
+    JsonDirMdProvider::write_md_cache(&dir, &md_cache).unwrap();
+    for md_id in [1, 2, 3, 4] {
+        let expected = fs::read_to_string(catalog_dir().join(format!("{md_id}.json"))).unwrap();
+        let actual = fs::read_to_string(dir.join(format!("{md_id}.json"))).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    let md = JsonDirMdProvider::new(&dir).retrieve_metadata(&2).unwrap();
+    assert_eq!(md.downcast_ref::<RelationMetadata>().unwrap().index_count(), 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,81 @@
+use cso_demo::metadata::{JsonDirMdProvider, MdAccessor, MdAccessorExt, MdError, SharedMdCache};
+use cso_demo::statistics::{ColumnStats, RelationMetadata};
+use std::rc::Rc;
+use std::sync::Arc;
+
+// tests/catalog holds t1 (mdid 2) with index IDX_1 and x (mdid 12) without index.
+fn md_accessor() -> MdAccessor {
+    let md_provider = JsonDirMdProvider::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/catalog"));
+    MdAccessor::new(Rc::new(md_provider))
+}
+
+#[test]
+fn test_retrieve_typed_metadata() {
+    let md_accessor = md_accessor();
+
+    let relation = md_accessor.retrieve_relation(2).unwrap();
+    assert_eq!(relation.name(), "t1");
+    let index = md_accessor.retrieve_index(4).unwrap();
+    assert_eq!(index.index_name(), "IDX_1");
+    let column_stats = md_accessor.retrieve_metadata_as::<ColumnStats>(&3).unwrap();
+    assert_eq!(column_stats.name(), "c2");
+}
+
+#[test]
+fn test_retrieve_unexpected_type() {
+    let md_accessor = md_accessor();
+
+    let err = md_accessor.retrieve_relation(3).unwrap_err();
+    assert!(matches!(err, MdError::UnexpectedType { .. }));
+    assert_eq!(err.md_id(), "3");
+    assert_eq!(err.to_string(), "metadata 3 is ColumnStats, expected RelationMetadata");
+
+    let err = md_accessor.retrieve_index(99).unwrap_err();
+    assert_eq!(err.to_string(), "metadata 99 not found");
+}
+
+#[test]
+fn test_relation_by_name() {
+    let md_accessor = md_accessor();
+
+    let (md_id, relation) = md_accessor.relation_by_name("x").unwrap();
+    assert_eq!(md_id, 12);
+    assert_eq!(relation.name(), "x");
+
+    // relation stats are named after the relation too, but are not relations
+    let (md_id, _) = md_accessor.relation_by_name("t1").unwrap();
+    assert_eq!(md_id, 2);
+
+    let err = md_accessor.relation_by_name("unknown").unwrap_err();
+    assert_eq!(err.to_string(), "RelationMetadata named \"unknown\" not found");
+}
+
+#[test]
+fn test_relation_by_name_through_shared_cache() {
+    let md_provider = JsonDirMdProvider::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/catalog"));
+    let shared_cache = Arc::new(SharedMdCache::new(Arc::new(md_provider), 16));
+    let md_accessor = MdAccessor::new(Rc::new(shared_cache.snapshot()));
+
+    let (md_id, _) = md_accessor.relation_by_name("t1").unwrap();
+    assert_eq!(md_id, 2);
+    assert!(shared_cache.contains(&2));
+}
+
+#[test]
+fn test_relation_lookups() {
+    let md_accessor = md_accessor();
+    let relation: RelationMetadata = md_accessor.retrieve_relation(2).unwrap();
+
+    assert_eq!(md_accessor.relation_stats(&relation).unwrap().rows(), 9011);
+
+    let indexes = md_accessor.relation_indexes(&relation).unwrap();
+    assert_eq!(indexes.len(), 1);
+    assert_eq!(indexes[0].mdid(), 4);
+
+    let column_stats = md_accessor.column_stats(&relation, 1).unwrap().unwrap();
+    assert_eq!(column_stats.name(), "c2");
+    assert!(md_accessor.column_stats(&relation, 0).unwrap().is_none());
+
+    let (_, x) = md_accessor.relation_by_name("x").unwrap();
+    assert!(md_accessor.relation_indexes(&x).unwrap().is_empty());
+}
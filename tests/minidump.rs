@@ -0,0 +1,248 @@
+use cso_core::cost::Cost;
+use cso_core::metadata::Stats;
+use cso_demo::datum::Datum;
+use cso_demo::expression::ScalarExpression;
+use cso_demo::expression::{ColumnVar, IsNull};
+use cso_demo::metadata::{CachedMdProvider, MdAccessor};
+use cso_demo::metadata::{MdCache, Metadata};
+use cso_demo::minidump::Minidump;
+use cso_demo::operator::logical_filter::LogicalFilter;
+use cso_demo::operator::logical_project::LogicalProject;
+use cso_demo::operator::logical_scan::{LogicalScan, TableDesc};
+use cso_demo::operator::physical_scan::PhysicalScan;
+use cso_demo::operator::physical_sort::{OrderSpec, Ordering};
+use cso_demo::operator::{OperatorId, PhysicalOperator};
+use cso_demo::property::sort_property::SortProperty;
+use cso_demo::property::PhysicalProperties;
+use cso_demo::rule::create_rule_set;
+use cso_demo::statistics::{
+    Bucket, ColumnMetadata, ColumnStats, Histogram, IndexInfo, IndexMd, RelationMetadata, RelationStats,
+};
+use cso_demo::{Demo, LogicalPlan, Optimizer, Options, PhysicalPlan};
+use std::fmt;
+use std::rc::Rc;
+
+fn logical_plan() -> LogicalPlan {
+    let table_desc = TableDesc::new(2);
+    let output_columns = vec![ColumnVar::new(0), ColumnVar::new(1), ColumnVar::new(2)];
+    let scan = LogicalScan::new(table_desc, output_columns);
+    let scan = LogicalPlan::new(Rc::new(scan), vec![], vec![]);
+
+    let predicate = IsNull::new(Box::new(ColumnVar::new(0)));
+    let filter = LogicalFilter::new(Rc::new(predicate));
+    let filter = LogicalPlan::new(Rc::new(filter), vec![scan], vec![]);
+
+    let project = vec![
+        Rc::new(ColumnVar::new(1)) as Rc<dyn ScalarExpression>,
+        Rc::new(ColumnVar::new(2)) as Rc<dyn ScalarExpression>,
+    ];
+    let project = LogicalProject::new(project);
+    LogicalPlan::new(Rc::new(project), vec![filter], vec![])
+}
+
+fn required_properties() -> Rc<PhysicalProperties> {
+    let order = OrderSpec {
+        order_desc: vec![Ordering::new(0)],
+    };
+    PhysicalProperties::with_property(Box::new(SortProperty::with_order(order)))
+}
+
+fn md_cache() -> MdCache {
+    // mdids
+    let relation_stats_id = 1;
+    let relation_md_id = 2;
+    let column_stats_id = 3;
+    let index_md_id = 4;
+    let unused_relation_stats_id = 5;
+
+    let relation_stats = RelationStats::new("t1".to_string(), 9011, false, vec![column_stats_id]);
+
+    let index_md = IndexMd::new(
+        index_md_id,
+        "IDX_1".to_string(),
+        vec![ColumnVar::new(0)],
+        vec![ColumnVar::new(0), ColumnVar::new(1), ColumnVar::new(2)],
+    );
+
+    let column_md = vec![
+        ColumnMetadata::new("c1".to_string(), 1, true, 4, Datum::I32(0)),
+        ColumnMetadata::new("c2".to_string(), 2, true, 4, Datum::I32(0)),
+        ColumnMetadata::new("c3".to_string(), 3, false, 4, Datum::I32(0)),
+    ];
+    let relation_md = RelationMetadata::new(
+        "t1".to_string(),
+        column_md,
+        relation_stats_id,
+        vec![IndexInfo::new(index_md_id)],
+    );
+
+    let buckets = vec![
+        Bucket::new(Datum::I32(0), Datum::I32(1), 1, 2),
+        Bucket::new(Datum::I32(1), Datum::I32(3), 3, 3),
+    ];
+    let column_stats = ColumnStats::new(
+        1,
+        "c2".to_string(),
+        Datum::I32(0),
+        Datum::I32(3),
+        0,
+        Some(Histogram::new(buckets)),
+    );
+
+    let unused_relation_stats = RelationStats::new("t2".to_string(), 10, false, vec![]);
+
+    let mut md_cache = MdCache::new();
+    md_cache.insert(relation_stats_id, Box::new(relation_stats) as Box<dyn Metadata>);
+    md_cache.insert(relation_md_id, Box::new(relation_md) as Box<dyn Metadata>);
+    md_cache.insert(column_stats_id, Box::new(column_stats) as Box<dyn Metadata>);
+    md_cache.insert(index_md_id, Box::new(index_md) as Box<dyn Metadata>);
+    md_cache.insert(
+        unused_relation_stats_id,
+        Box::new(unused_relation_stats) as Box<dyn Metadata>,
+    );
+    md_cache
+}
+
+fn capture() -> Minidump {
+    let md_provider = Rc::new(CachedMdProvider::new(md_cache()));
+    Minidump::capture(
+        Options::default(),
+        logical_plan(),
+        required_properties(),
+        md_provider,
+        create_rule_set(),
+    )
+}
+
+#[test]
+fn test_minidump_captures_accessed_metadata() {
+    let minidump = capture();
+
+    let metadata = minidump.metadata();
+    for md_id in [1, 2, 3, 4] {
+        assert!(metadata.get(&md_id).is_some(), "metadata {md_id} should be captured");
+    }
+    assert!(metadata.get(&5).is_none());
+}
+
+#[test]
+fn test_minidump_replay_from_file() {
+    let minidump = capture();
+
+    let path = std::env::temp_dir().join(format!("cso-minidump-{}.json", std::process::id()));
+    minidump.write_to_file(&path).unwrap();
+    let replayed = Minidump::read_from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(replayed.result() == minidump.result());
+    let plan = replayed.replay().unwrap();
+    assert_eq!(replayed.diff(&plan), None);
+}
+
+#[test]
+fn test_minidump_option() {
+    let path = std::env::temp_dir().join(format!("cso-minidump-option-{}.json", std::process::id()));
+    let md_provider = Rc::new(CachedMdProvider::new(md_cache()));
+    let mut optimizer = Optimizer::new(Options::default().with_minidump_path(&path));
+    let plan = optimizer.optimize(
+        logical_plan(),
+        required_properties(),
+        MdAccessor::new(md_provider),
+        create_rule_set(),
+    );
+    assert!(optimizer.minidump_error().is_none());
+
+    let minidump = Minidump::read_from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(minidump.diff(&plan), None);
+    assert!(minidump.metadata().get(&5).is_none());
+    assert_eq!(minidump.diff(&minidump.replay().unwrap()), None);
+
+    // the minidump can't be written into a missing directory, which doesn't fail the optimization
+    let mut optimizer = Optimizer::new(Options::default().with_minidump_path(path.join("missing")));
+    let md_provider = Rc::new(CachedMdProvider::new(md_cache()));
+    let other_plan = optimizer.optimize(
+        logical_plan(),
+        required_properties(),
+        MdAccessor::new(md_provider),
+        create_rule_set(),
+    );
+    assert!(optimizer.minidump_error().is_some());
+    assert!(other_plan == plan);
+}
+
+#[test]
+fn test_replay_unknown_rule() {
+    // a minidump of a build with the scan implementation as a transformation
+    let mut json = serde_json::to_value(capture()).unwrap();
+    json["transform_rules"] = serde_json::json!(["ScanImplementation"]);
+    let minidump: Minidump = serde_json::from_value(json).unwrap();
+
+    assert_eq!(minidump.replay().err().unwrap(), "unknown rule: ScanImplementation");
+}
+
+#[test]
+fn test_minidump_diff() {
+    let minidump = capture();
+
+    let scan = PhysicalScan::new(
+        TableDesc::new(2),
+        vec![ColumnVar::new(0), ColumnVar::new(1), ColumnVar::new(2)],
+    );
+    let plan = PhysicalPlan::new(Rc::new(scan), vec![]);
+
+    let diff = minidump.diff(&plan).expect("plans should differ");
+    assert!(diff.lines().any(|line| line.starts_with("+ PhysicalScan")));
+    assert!(diff.lines().any(|line| line.starts_with("- PhysicalProject")));
+}
+
+/// Behaves and prints like the operator it wraps, but equals no other operator.
+#[derive(Clone)]
+struct Disguised(Rc<PhysicalOperator>);
+
+impl fmt::Debug for Disguised {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl cso_core::operator::PhysicalOperator<Demo> for Disguised {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn operator_id(&self) -> &OperatorId {
+        self.0.operator_id()
+    }
+
+    fn derive_output_properties(&self, child_props: &[Rc<PhysicalProperties>]) -> Rc<PhysicalProperties> {
+        self.0.derive_output_properties(child_props)
+    }
+
+    fn required_properties(&self, input_prop: Rc<PhysicalProperties>) -> Vec<Vec<Rc<PhysicalProperties>>> {
+        self.0.required_properties(input_prop)
+    }
+
+    fn compute_cost(&self, stats: Option<&dyn Stats>) -> Cost {
+        self.0.compute_cost(stats)
+    }
+
+    fn equal(&self, _other: &PhysicalOperator) -> bool {
+        false
+    }
+}
+
+#[test]
+fn test_minidump_diff_of_plans_printing_the_same() {
+    let minidump = capture();
+
+    let result = minidump.result();
+    let mut plan = PhysicalPlan::new(Rc::new(Disguised(result.operator().clone())), result.inputs().to_vec());
+    if let Some(statistics) = result.statistics() {
+        plan = plan.with_statistics(statistics.clone());
+    }
+    assert_eq!(plan.explain(), result.explain());
+
+    let diff = minidump.diff(&plan).expect("plans should differ");
+    assert!(!diff.is_empty());
+}
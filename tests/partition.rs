@@ -0,0 +1,327 @@
+use cso_core::operator::LogicalOperator;
+use cso_demo::datum::Datum;
+use cso_demo::expression::{
+    ColumnVar, Const, Equal, GreaterThan, GreaterThanEqual, InList, IsNotNull, IsNull, LessThan, LessThanEqual, Or,
+    ScalarExpression,
+};
+use cso_demo::metadata::{CachedMdProvider, MdAccessor, MdCache, Metadata, Stats};
+use cso_demo::operator::logical_partition_scan::{LogicalPartitionScan, PartitionDesc};
+use cso_demo::operator::logical_scan::TableDesc;
+use cso_demo::partition::{Partition, PartitionBound, PartitionInfo};
+use cso_demo::sketch::{HyperLogLog, DEFAULT_HLL_PRECISION};
+use cso_demo::statistics::{
+    Bucket, ColumnMetadata, ColumnStats, Histogram, Mcv, RelationMetadata, RelationStats, Statistics,
+};
+use cso_demo::Demo;
+use std::rc::Rc;
+
+fn key() -> Box<dyn ScalarExpression> {
+    Box::new(ColumnVar::new(0))
+}
+
+fn int(value: i32) -> Box<dyn ScalarExpression> {
+    Box::new(Const::Int32(value))
+}
+
+fn range(lower: Option<i32>, upper: Option<i32>) -> PartitionBound {
+    PartitionBound::Range {
+        lower: lower.map(Datum::I32),
+        upper: upper.map(Datum::I32),
+    }
+}
+
+fn partitions(bounds: Vec<PartitionBound>) -> PartitionInfo {
+    let partitions = bounds
+        .into_iter()
+        .enumerate()
+        .map(|(i, bound)| Partition::new(format!("p{i}"), bound, 100 + i as u64))
+        .collect();
+    PartitionInfo::new(0, partitions)
+}
+
+fn prune(info: &PartitionInfo, predicates: Vec<Box<dyn ScalarExpression>>) -> Vec<usize> {
+    let predicates = predicates.into_iter().map(Rc::from).collect::<Vec<_>>();
+    info.prune(&ColumnVar::new(0), &predicates)
+}
+
+#[test]
+fn test_prune_range_partitions() {
+    let info = partitions(vec![
+        range(None, Some(100)),
+        range(Some(100), Some(200)),
+        range(Some(200), None),
+    ]);
+
+    assert_eq!(prune(&info, vec![]), vec![0, 1, 2]);
+    assert_eq!(prune(&info, vec![Box::new(Equal::new(key(), int(100)))]), vec![1]);
+    assert_eq!(prune(&info, vec![Box::new(Equal::new(int(99), key()))]), vec![0]);
+    assert_eq!(prune(&info, vec![Box::new(LessThan::new(key(), int(100)))]), vec![0]);
+    assert_eq!(
+        prune(&info, vec![Box::new(LessThanEqual::new(key(), int(100)))]),
+        vec![0, 1]
+    );
+    assert_eq!(
+        prune(&info, vec![Box::new(GreaterThanEqual::new(key(), int(200)))]),
+        vec![2]
+    );
+    // `150 < key` is `key > 150`
+    assert_eq!(prune(&info, vec![Box::new(LessThan::new(int(150), key()))]), vec![1, 2]);
+    assert_eq!(
+        prune(
+            &info,
+            vec![
+                Box::new(GreaterThan::new(key(), int(50))),
+                Box::new(LessThan::new(key(), int(150))),
+            ]
+        ),
+        vec![0, 1]
+    );
+    assert_eq!(
+        prune(
+            &info,
+            vec![Box::new(Or::new(vec![
+                Box::new(LessThan::new(key(), int(10))),
+                Box::new(Equal::new(key(), int(250))),
+            ]))]
+        ),
+        vec![0, 2]
+    );
+    assert_eq!(
+        prune(&info, vec![Box::new(InList::new(key(), vec![int(5), int(7)]))]),
+        vec![0]
+    );
+
+    // no partition holds nulls or rows contradicting the predicates
+    assert!(prune(&info, vec![Box::new(IsNull::new(key()))]).is_empty());
+    assert!(prune(
+        &info,
+        vec![
+            Box::new(LessThan::new(key(), int(100))),
+            Box::new(GreaterThan::new(key(), int(200))),
+        ]
+    )
+    .is_empty());
+
+    // predicates on other columns don't prune
+    let other = || Box::new(ColumnVar::new(1)) as Box<dyn ScalarExpression>;
+    assert_eq!(prune(&info, vec![Box::new(Equal::new(other(), int(1)))]), vec![0, 1, 2]);
+    assert_eq!(prune(&info, vec![Box::new(Equal::new(key(), other()))]), vec![0, 1, 2]);
+}
+
+#[test]
+fn test_prune_list_partitions() {
+    let info = partitions(vec![
+        PartitionBound::List {
+            values: vec![Datum::I32(1), Datum::I32(2)],
+        },
+        PartitionBound::List {
+            values: vec![Datum::I32(3), Datum::Null],
+        },
+        PartitionBound::Default,
+    ]);
+
+    assert_eq!(prune(&info, vec![Box::new(Equal::new(key(), int(2)))]), vec![0, 2]);
+    assert_eq!(
+        prune(&info, vec![Box::new(GreaterThan::new(key(), int(2)))]),
+        vec![1, 2]
+    );
+    assert_eq!(
+        prune(&info, vec![Box::new(LessThanEqual::new(key(), int(1)))]),
+        vec![0, 2]
+    );
+    assert_eq!(prune(&info, vec![Box::new(IsNull::new(key()))]), vec![1, 2]);
+    assert_eq!(prune(&info, vec![Box::new(IsNotNull::new(key()))]), vec![0, 1, 2]);
+}
+
+#[test]
+fn test_prune_bigint_partitions_by_integer() {
+    // the key is a bigint, compared with integer constants
+    let info = partitions(vec![
+        PartitionBound::List {
+            values: vec![Datum::I64(5)],
+        },
+        PartitionBound::Range {
+            lower: Some(Datum::I64(10)),
+            upper: Some(Datum::I64(20)),
+        },
+        PartitionBound::Range {
+            lower: Some(Datum::I64(20)),
+            upper: None,
+        },
+    ]);
+
+    assert_eq!(prune(&info, vec![Box::new(Equal::new(key(), int(5)))]), vec![0]);
+    assert_eq!(prune(&info, vec![Box::new(Equal::new(key(), int(10)))]), vec![1]);
+    assert_eq!(prune(&info, vec![Box::new(Equal::new(key(), int(20)))]), vec![2]);
+    assert_eq!(
+        prune(&info, vec![Box::new(LessThanEqual::new(key(), int(10)))]),
+        vec![0, 1]
+    );
+    assert_eq!(
+        prune(&info, vec![Box::new(GreaterThanEqual::new(key(), int(5)))]),
+        vec![0, 1, 2]
+    );
+    assert_eq!(
+        prune(&info, vec![Box::new(InList::new(key(), vec![int(5), int(25)]))]),
+        vec![0, 2]
+    );
+}
+
+#[test]
+#[should_panic(expected = "a table has at most one default partition")]
+fn test_two_default_partitions() {
+    partitions(vec![PartitionBound::Default, PartitionBound::Default]);
+}
+
+fn column_stats(min: i32, max: i32, null_count: u64, buckets: Vec<Bucket>) -> ColumnStats {
+    ColumnStats::new(
+        0,
+        "c0".to_string(),
+        Datum::I32(min),
+        Datum::I32(max),
+        null_count,
+        Some(Histogram::new(buckets)),
+    )
+}
+
+#[test]
+fn test_merge_column_stats() {
+    let p0 = column_stats(0, 99, 10, vec![Bucket::new(Datum::I32(0), Datum::I32(99), 50, 80)])
+        .with_mcvs(vec![Mcv::new(Datum::I32(7), 0.1)]);
+    let p1 = column_stats(
+        100,
+        199,
+        0,
+        vec![Bucket::new(Datum::I32(100), Datum::I32(199), 30, 300)],
+    );
+    let merged = ColumnStats::merge(&[(300, &p1), (100, &p0)]);
+
+    assert_eq!(merged.min(), &Datum::I32(0));
+    assert_eq!(merged.max(), &Datum::I32(199));
+    assert_eq!(merged.null_count(), 10);
+    let buckets = merged.histogram().as_ref().unwrap().buckets();
+    assert_eq!(buckets.len(), 2);
+    assert_eq!(buckets[0].lower(), &Datum::I32(0));
+    // the most common value of the first partition holds a tenth of its 100 rows
+    assert_eq!(merged.mcvs().len(), 1);
+    assert_eq!(merged.mcvs()[0].frequency(), 0.025);
+    assert_eq!(merged.ndv(), Some(81));
+
+    // overlapping partitions
+    let p2 = column_stats(50, 150, 0, vec![Bucket::new(Datum::I32(50), Datum::I32(150), 60, 100)]);
+    let merged = ColumnStats::merge(&[(100, &p0), (100, &p2)]);
+    assert!(merged.histogram().is_none());
+    assert_eq!(merged.ndv(), Some(60));
+    assert_eq!(merged.max(), &Datum::I32(150));
+
+    // empty partitions only count their nulls
+    let empty = column_stats(0, 0, 5, vec![]);
+    let merged = ColumnStats::merge(&[(5, &empty), (300, &p1)]);
+    assert_eq!(merged.min(), &Datum::I32(100));
+    assert_eq!(merged.null_count(), 5);
+}
+
+/// Table `sales(day, amount)` in three partitions by day, of 100, 200 and 300 rows.
+fn md_accessor() -> MdAccessor {
+    let mut md_cache = MdCache::new();
+    let mut partition_list = vec![];
+    for (i, rows) in [100u64, 200, 300].into_iter().enumerate() {
+        let lower = i as i32 * 100;
+        let stats_mdid = 10 + i as u64 * 10;
+        let (day_mdid, amount_mdid, sketch_mdid) = (stats_mdid + 1, stats_mdid + 2, stats_mdid + 3);
+
+        let mut sketch = HyperLogLog::new(DEFAULT_HLL_PRECISION);
+        (0..rows as i32).for_each(|amount| sketch.add(&Datum::I32(amount)));
+        let day = column_stats(
+            lower,
+            lower + 99,
+            0,
+            vec![Bucket::new(Datum::I32(lower), Datum::I32(lower + 99), 100, rows)],
+        )
+        .with_ndv(100);
+        let amount = ColumnStats::new(1, "amount".to_string(), Datum::I32(0), Datum::I32(1000), 0, None)
+            .with_sketch_mdid(sketch_mdid);
+        let stats = RelationStats::new(format!("p{i}"), rows, false, vec![day_mdid, amount_mdid]);
+
+        md_cache.insert(stats_mdid, Box::new(stats) as Box<dyn Metadata>);
+        md_cache.insert(day_mdid, Box::new(day) as Box<dyn Metadata>);
+        md_cache.insert(amount_mdid, Box::new(amount) as Box<dyn Metadata>);
+        md_cache.insert(sketch_mdid, Box::new(sketch) as Box<dyn Metadata>);
+        partition_list.push(Partition::new(
+            format!("p{i}"),
+            range(Some(lower), Some(lower + 100)),
+            stats_mdid,
+        ));
+    }
+
+    let columns = vec![
+        ColumnMetadata::new("day".to_string(), 0, false, 4, Datum::I32(0)),
+        ColumnMetadata::new("amount".to_string(), 1, true, 4, Datum::I32(0)),
+    ];
+    let relation = RelationMetadata::new("sales".to_string(), columns, 2, vec![])
+        .with_partition_info(PartitionInfo::new(0, partition_list));
+    md_cache.insert(1, Box::new(relation) as Box<dyn Metadata>);
+    md_cache.insert(2, Box::new(RelationStats::new("sales".to_string(), 600, false, vec![])));
+    MdAccessor::new(Rc::new(CachedMdProvider::new(md_cache)))
+}
+
+fn partition_scan_stats(md_accessor: &MdAccessor, partitions: &[usize]) -> Statistics {
+    let relation = md_accessor.retrieve_metadata_as::<RelationMetadata>(&1).unwrap();
+    let partition_info = relation.partition_info().unwrap();
+    let partitions = partitions
+        .iter()
+        .map(|i| PartitionDesc::from(&partition_info.partitions()[*i]))
+        .collect();
+    let scan = LogicalPartitionScan::new(
+        TableDesc::new(1),
+        vec![ColumnVar::new(0), ColumnVar::new(1)],
+        partitions,
+    );
+    let stats = LogicalOperator::<Demo>::derive_statistics(&scan, md_accessor, &[]);
+    stats.as_any().downcast_ref::<Statistics>().unwrap().clone()
+}
+
+fn assert_within(estimate: u64, expected: u64) {
+    let error = (estimate as f64 - expected as f64).abs() / expected as f64;
+    assert!(error <= 0.05, "estimate {estimate} of {expected} is off by {error}");
+}
+
+#[test]
+fn test_partition_scan_statistics() {
+    let md_accessor = md_accessor();
+
+    let stats = partition_scan_stats(&md_accessor, &[1]);
+    assert_eq!(stats.output_row_count(), 200);
+    assert_eq!(
+        stats.column_stats_of(&ColumnVar::new(0)).unwrap().min(),
+        &Datum::I32(100)
+    );
+    assert_within(stats.column_ndv(&ColumnVar::new(1)).unwrap(), 200);
+
+    let stats = partition_scan_stats(&md_accessor, &[0, 2]);
+    assert_eq!(stats.output_row_count(), 400);
+    let day = stats.column_stats_of(&ColumnVar::new(0)).unwrap();
+    assert_eq!((day.min(), day.max()), (&Datum::I32(0), &Datum::I32(299)));
+    assert_eq!(day.histogram().as_ref().unwrap().value_count(), 400);
+    assert_eq!(stats.column_ndv(&ColumnVar::new(0)), Some(200));
+    // the amounts of the partitions overlap, which the merged sketches account for
+    assert_within(stats.column_ndv(&ColumnVar::new(1)).unwrap(), 300);
+
+    let stats = partition_scan_stats(&md_accessor, &[]);
+    assert_eq!(stats.output_row_count(), 0);
+    assert!(stats.column_stats().is_empty());
+}
+
+#[test]
+fn test_serialize_partition_info() {
+    let md_accessor = md_accessor();
+    let relation = md_accessor.retrieve_metadata_as::<RelationMetadata>(&1).unwrap();
+    let json = serde_json::to_string(&(Box::new(relation) as Box<dyn Metadata>)).unwrap();
+    let md: Box<dyn Metadata> = serde_json::from_str(&json).unwrap();
+
+    let partition_info = md.downcast_ref::<RelationMetadata>().unwrap().partition_info().unwrap();
+    assert_eq!(partition_info.key_col_id(), 0);
+    assert_eq!(partition_info.partitions().len(), 3);
+    assert_eq!(partition_info.partitions()[2].bound(), &range(Some(200), Some(300)));
+    assert_eq!(partition_info.partitions()[2].rel_stats_mdid(), 30);
+}
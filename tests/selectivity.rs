@@ -0,0 +1,532 @@
+use cso_core::operator::LogicalOperator;
+use cso_demo::datum::Datum;
+use cso_demo::expression::{
+    And, ColumnVar, Const, Equal, GreaterThan, GreaterThanEqual, InList, IsNotNull, IsNull, LessThan, Not, NotEqual,
+    Or, ScalarExpression,
+};
+use cso_demo::metadata::{CachedMdProvider, MdAccessor, MdCache, Metadata, Stats};
+use cso_demo::operator::logical_filter::{derive_filter_stats, LogicalFilter};
+use cso_demo::operator::logical_scan::{LogicalScan, TableDesc};
+use cso_demo::selectivity::{
+    column_predicate_selectivity, conjunction_selectivity, equality_selectivity, in_list_selectivity,
+    predicate_selectivity, DEFAULT_EQUALITY_SELECTIVITY, DEFAULT_NULL_SELECTIVITY, DEFAULT_RANGE_SELECTIVITY,
+};
+use cso_demo::statistics::{
+    Bucket, ColumnMetadata, ColumnStats, EstimateSource, Histogram, JointMcv, Mcv, MultiColumnStats, RelationMetadata,
+    RelationStats, Statistics,
+};
+use cso_demo::Demo;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+const ROWS: u64 = 1000;
+
+/// 1000 rows: 100 nulls, 1 in 40% and 2 in 10% of the rows, the other 450 rows hold 90 values in
+/// [0, 100) spread over two buckets.
+fn skewed_stats() -> ColumnStats {
+    let buckets = vec![
+        Bucket::new(Datum::I32(0), Datum::I32(49), 45, 360),
+        Bucket::new(Datum::I32(50), Datum::I32(99), 45, 90),
+    ];
+    ColumnStats::new(
+        0,
+        "c0".to_string(),
+        Datum::I32(0),
+        Datum::I32(99),
+        100,
+        Some(Histogram::new(buckets)),
+    )
+    .with_ndv(92)
+    .with_mcvs(vec![Mcv::new(Datum::I32(1), 0.4), Mcv::new(Datum::I32(2), 0.1)])
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+}
+
+#[test]
+fn test_equality_selectivity() {
+    let stats = skewed_stats();
+
+    // most common values
+    assert_close(equality_selectivity(&stats, ROWS, &Datum::I32(1)), 0.4);
+    assert_close(equality_selectivity(&stats, ROWS, &Datum::I32(2)), 0.1);
+    // histogram buckets
+    assert_close(equality_selectivity(&stats, ROWS, &Datum::I32(10)), 0.008);
+    assert_close(equality_selectivity(&stats, ROWS, &Datum::I32(60)), 0.002);
+    // out of range
+    assert_close(equality_selectivity(&stats, ROWS, &Datum::I32(100)), 0.0);
+    assert_close(equality_selectivity(&stats, 0, &Datum::I32(1)), 0.0);
+}
+
+#[test]
+fn test_equality_selectivity_without_histogram() {
+    let stats = ColumnStats::new(0, "c0".to_string(), Datum::I32(0), Datum::I32(99), 100, None)
+        .with_mcvs(vec![Mcv::new(Datum::I32(1), 0.4)]);
+    assert_close(
+        equality_selectivity(&stats, ROWS, &Datum::I32(5)),
+        DEFAULT_EQUALITY_SELECTIVITY,
+    );
+
+    // the rows left by nulls and most common values are spread over the other distinct values
+    let stats = stats.with_ndv(51);
+    assert_close(equality_selectivity(&stats, ROWS, &Datum::I32(5)), 0.5 / 50.0);
+}
+
+#[test]
+fn test_equality_selectivity_of_bigint_by_integer() {
+    // a bigint column compared with integer constants at its bounds
+    let buckets = vec![
+        Bucket::new(Datum::I64(5), Datum::I64(49), 45, 450),
+        Bucket::new(Datum::I64(50), Datum::I64(99), 50, 450),
+    ];
+    let stats = ColumnStats::new(
+        0,
+        "c0".to_string(),
+        Datum::I64(5),
+        Datum::I64(99),
+        100,
+        Some(Histogram::new(buckets)),
+    );
+    assert_close(equality_selectivity(&stats, ROWS, &Datum::I32(5)), 0.01);
+    assert_close(equality_selectivity(&stats, ROWS, &Datum::I32(99)), 0.009);
+    assert_close(equality_selectivity(&stats, ROWS, &Datum::I32(4)), 0.0);
+
+    let values = [Datum::I32(5), Datum::I64(5)];
+    assert_close(in_list_selectivity(&stats, ROWS, &values), 0.01);
+}
+
+#[test]
+fn test_mcv_frequency_by_value() {
+    let stats = ColumnStats::new(0, "c0".to_string(), Datum::I64(0), Datum::I64(99), 0, None)
+        .with_mcvs(vec![Mcv::new(Datum::I64(7), 0.3)]);
+    assert_eq!(stats.mcv_frequency(&Datum::I32(7)), Some(0.3));
+    assert_close(equality_selectivity(&stats, ROWS, &Datum::I32(7)), 0.3);
+
+    let multi_column_stats = MultiColumnStats::new(
+        vec![0, 1],
+        None,
+        vec![JointMcv::new(vec![Datum::I64(1), Datum::I32(7)], 0.1)],
+    );
+    assert_eq!(
+        multi_column_stats.mcv_frequency(&[Datum::I32(1), Datum::I64(7)]),
+        Some(0.1)
+    );
+    assert_eq!(multi_column_stats.mcv_frequency(&[Datum::I32(1)]), None);
+}
+
+#[test]
+fn test_in_list_selectivity() {
+    let stats = skewed_stats();
+
+    let values = [Datum::I32(1), Datum::I32(10), Datum::I32(1), Datum::I32(200)];
+    assert_close(in_list_selectivity(&stats, ROWS, &values), 0.408);
+
+    // never more than the non-null rows
+    let values = (0..100).map(Datum::I32).collect::<Vec<_>>();
+    assert_close(in_list_selectivity(&stats, ROWS, &values), 0.9);
+}
+
+#[test]
+fn test_column_predicate_selectivity() {
+    let stats = skewed_stats();
+    let c0 = ColumnVar::new(0);
+    let c1 = ColumnVar::new(1);
+    let int = |v: i32| Box::new(Const::Int32(v)) as Box<dyn ScalarExpression>;
+
+    let equal = Equal::new(int(2), Box::new(c0.clone()));
+    assert_close(column_predicate_selectivity(&equal, &c0, &stats, ROWS).unwrap(), 0.1);
+    assert!(column_predicate_selectivity(&equal, &c1, &stats, ROWS).is_none());
+
+    let in_list = InList::new(Box::new(c0.clone()), vec![int(1), int(2), int(60)]);
+    assert_close(
+        column_predicate_selectivity(&in_list, &c0, &stats, ROWS).unwrap(),
+        0.502,
+    );
+
+    let in_list = InList::new(Box::new(c0.clone()), vec![int(1), Box::new(c1)]);
+    assert!(column_predicate_selectivity(&in_list, &c0, &stats, ROWS).is_none());
+
+    let is_null = IsNull::new(Box::new(c0.clone()));
+    assert!(column_predicate_selectivity(&is_null, &c0, &stats, ROWS).is_none());
+}
+
+fn column(id: u32) -> Box<dyn ScalarExpression> {
+    Box::new(ColumnVar::new(id))
+}
+
+fn int(value: i32) -> Box<dyn ScalarExpression> {
+    Box::new(Const::Int32(value))
+}
+
+#[test]
+fn test_range_selectivity() {
+    let stats = Statistics::new(ROWS, EstimateSource::BaseStats, BTreeMap::from([(0, skewed_stats())]));
+    let selectivity = |predicate: &dyn ScalarExpression| predicate_selectivity(predicate, &stats);
+
+    // both most common values and the first bucket
+    assert_close(selectivity(&LessThan::new(column(0), int(50))), 0.5 + 0.4 * 0.8);
+    assert_close(selectivity(&GreaterThanEqual::new(column(0), int(50))), 0.4 * 0.2);
+    // `50 < c0` leaves out the rows equal to 50
+    assert_close(selectivity(&LessThan::new(int(50), column(0))), 0.4 * 0.2 - 0.002);
+    // part of the first bucket
+    assert_close(
+        selectivity(&GreaterThan::new(column(0), int(1))),
+        0.1 + 0.4 * (1.0 - 0.8 / 49.0),
+    );
+    assert_close(selectivity(&GreaterThan::new(column(0), int(100))), 0.0);
+
+    // the values are spread evenly between min and max without a histogram
+    let column_stats = ColumnStats::new(0, "c0".to_string(), Datum::I32(0), Datum::I32(99), 100, None);
+    let stats = Statistics::new(ROWS, EstimateSource::BaseStats, BTreeMap::from([(0, column_stats)]));
+    assert_close(
+        predicate_selectivity(&LessThan::new(column(0), int(25)), &stats),
+        0.9 * 25.0 / 99.0,
+    );
+}
+
+#[test]
+fn test_predicate_selectivity() {
+    let stats = Statistics::new(ROWS, EstimateSource::BaseStats, BTreeMap::from([(0, skewed_stats())]));
+    let selectivity = |predicate: &dyn ScalarExpression| predicate_selectivity(predicate, &stats);
+
+    // neither null nor 1
+    assert_close(selectivity(&NotEqual::new(column(0), int(1))), 0.5);
+    assert_close(selectivity(&IsNull::new(column(0))), 0.1);
+    assert_close(selectivity(&IsNotNull::new(column(0))), 0.9);
+    assert_close(selectivity(&Not::new(Box::new(IsNull::new(column(0))))), 0.9);
+    assert_close(
+        selectivity(&Or::new(vec![
+            Box::new(Equal::new(column(0), int(1))),
+            Box::new(Equal::new(column(0), int(2))),
+        ])),
+        1.0 - 0.6 * 0.9,
+    );
+    let lt_50: Rc<dyn ScalarExpression> = Rc::new(LessThan::new(column(0), int(50)));
+    let ne_1: Rc<dyn ScalarExpression> = Rc::new(NotEqual::new(column(0), int(1)));
+    assert_close(selectivity(&And::new(vec![lt_50, ne_1])), 0.82 * 0.5);
+
+    // defaults without statistics
+    assert_close(
+        selectivity(&LessThan::new(column(1), int(50))),
+        DEFAULT_RANGE_SELECTIVITY,
+    );
+    assert_close(selectivity(&IsNull::new(column(1))), DEFAULT_NULL_SELECTIVITY);
+    assert_close(
+        selectivity(&NotEqual::new(column(1), int(1))),
+        1.0 - DEFAULT_EQUALITY_SELECTIVITY,
+    );
+    assert_close(
+        selectivity(&Equal::new(column(0), column(1))),
+        DEFAULT_EQUALITY_SELECTIVITY,
+    );
+    assert_close(
+        selectivity(&Not::new(Box::new(Equal::new(column(0), column(1))))),
+        1.0 - DEFAULT_EQUALITY_SELECTIVITY,
+    );
+    assert_close(
+        selectivity(&Not::new(Box::new(LessThan::new(column(0), column(1))))),
+        1.0 - DEFAULT_RANGE_SELECTIVITY,
+    );
+    // null tests of other expressions than columns
+    assert_close(selectivity(&IsNull::new(int(1))), DEFAULT_NULL_SELECTIVITY);
+    assert_close(selectivity(&IsNotNull::new(int(1))), 1.0 - DEFAULT_NULL_SELECTIVITY);
+
+    // predicates that can't be estimated, like comparing a column with a list of columns, don't
+    // filter any row, nor do their negations
+    let opaque = || Box::new(InList::new(column(0), vec![column(1), column(2)]));
+    assert_close(selectivity(&*opaque()), 1.0);
+    assert_close(selectivity(&Not::new(opaque())), 1.0);
+    let or = || Or::new(vec![Box::new(Equal::new(column(0), int(1))), opaque()]);
+    assert_close(selectivity(&or()), 1.0);
+    assert_close(selectivity(&Not::new(Box::new(or()))), 1.0);
+}
+
+#[test]
+fn test_mcvs_serialization() {
+    let stats = skewed_stats();
+    let json = serde_json::to_string(&stats).unwrap();
+    let stats = serde_json::from_str::<ColumnStats>(&json).unwrap();
+    assert_eq!(stats.mcv_frequency(&Datum::I32(1)), Some(0.4));
+
+    // statistics written before most common values existed have none
+    let stats = ColumnStats::new(0, "c0".to_string(), Datum::I32(0), Datum::I32(9), 0, None);
+    let json = serde_json::to_string(&stats).unwrap();
+    assert!(!json.contains("mcvs"));
+    assert!(serde_json::from_str::<ColumnStats>(&json).unwrap().mcvs().is_empty());
+}
+
+/// 10000 rows of (city, zip, other), where the zip code determines the city: 100 cities, 1000 zip
+/// codes, and zip code 7 of city 1 is a tenth of the rows, city 1 has 15% of the rows.
+fn address_stats() -> Statistics {
+    let column_stats = |col_id: usize, name: &str, ndv: u64, mcvs: Vec<Mcv>| {
+        let stats = ColumnStats::new(col_id, name.to_string(), Datum::I32(0), Datum::I32(999), 0, None);
+        (col_id as u32, stats.with_ndv(ndv).with_mcvs(mcvs))
+    };
+    let multi_column_stats = MultiColumnStats::new(
+        vec![0, 1],
+        Some(1000),
+        vec![JointMcv::new(vec![Datum::I32(1), Datum::I32(7)], 0.1)],
+    );
+    Statistics::new(
+        10_000,
+        EstimateSource::BaseStats,
+        BTreeMap::from([
+            column_stats(0, "city", 100, vec![Mcv::new(Datum::I32(1), 0.15)]),
+            column_stats(1, "zip", 1000, vec![Mcv::new(Datum::I32(7), 0.1)]),
+            column_stats(2, "other", 10, vec![]),
+        ]),
+    )
+    .with_multi_column_stats(vec![(vec![ColumnVar::new(0), ColumnVar::new(1)], multi_column_stats)])
+}
+
+fn equal(col_id: u32, value: i32) -> Rc<dyn ScalarExpression> {
+    Rc::new(Equal::new(
+        Box::new(ColumnVar::new(col_id)),
+        Box::new(Const::Int32(value)),
+    ))
+}
+
+#[test]
+fn test_conjunction_selectivity_with_multi_column_stats() {
+    let stats = address_stats();
+
+    // a single column doesn't use the multi-column statistics
+    let city = 0.85 / 99.0;
+    let zip = 0.9 / 999.0;
+    assert_close(conjunction_selectivity(&[equal(0, 2)], &stats), city);
+
+    // the zip code determines the city, so the city doesn't filter any more rows
+    assert_close(
+        conjunction_selectivity(&[equal(0, 2), equal(1, 30)], &stats),
+        0.9 / 999.0,
+    );
+    assert_close(conjunction_selectivity(&[equal(1, 7), equal(0, 1)], &stats), 0.1);
+    // the other column is independent
+    assert_close(
+        conjunction_selectivity(&[equal(0, 2), equal(2, 5), equal(1, 30)], &stats),
+        zip * 0.1,
+    );
+
+    // without multi-column statistics the selectivities are multiplied
+    let stats = stats.with_multi_column_stats(vec![]);
+    assert_close(
+        conjunction_selectivity(&[equal(0, 2), equal(1, 30)], &stats),
+        city * zip,
+    );
+}
+
+#[test]
+fn test_filter_statistics() {
+    let stats: Rc<dyn Stats> = Rc::new(address_stats());
+    let md_accessor = MdAccessor::new(Rc::new(CachedMdProvider::new(MdCache::new())));
+    let derive = |predicate: Rc<dyn ScalarExpression>| {
+        let filter = LogicalFilter::new(predicate);
+        LogicalOperator::<Demo>::derive_statistics(&filter, &md_accessor, std::slice::from_ref(&stats))
+            .output_row_count()
+    };
+
+    let city_and_zip = Rc::new(And::new(vec![equal(0, 1), equal(1, 7)]));
+    assert_eq!(derive(city_and_zip), 1000);
+    assert_eq!(derive(equal(2, 5)), 1000);
+    // the city is never null
+    assert_eq!(derive(Rc::new(IsNotNull::new(Box::new(ColumnVar::new(0))))), 10_000);
+    assert_eq!(derive(Rc::new(IsNull::new(Box::new(ColumnVar::new(0))))), 1);
+    // never less than a row
+    let nowhere = Rc::new(And::new(vec![equal(0, 2), equal(2, 5), equal(1, 30), equal(3, 1)]));
+    assert_eq!(derive(nowhere), 1);
+}
+
+#[test]
+fn test_filter_multi_column_statistics() {
+    let stats = address_stats();
+
+    // the joint values of city and zip code are kept, over fewer rows, by a filter on the other
+    // column
+    let filtered = derive_filter_stats(&stats, &[equal(2, 5)]);
+    assert_eq!(filtered.output_row_count(), 1000);
+    let (columns, city_and_zip) = &filtered.multi_column_stats()[0];
+    assert_eq!(columns, &[ColumnVar::new(0), ColumnVar::new(1)]);
+    assert!(city_and_zip.ndv().unwrap() < 1000);
+    assert_eq!(city_and_zip.mcv_frequency(&[Datum::I32(1), Datum::I32(7)]), Some(0.1));
+
+    // but not by a filter on the city, after which zip code 7 may be all or none of the rows
+    let filtered = derive_filter_stats(&stats, &[equal(0, 1)]);
+    assert!(filtered.multi_column_stats().is_empty());
+}
+
+#[test]
+fn test_filter_column_statistics() {
+    let other = ColumnStats::new(1, "c1".to_string(), Datum::I32(0), Datum::I32(999), 200, None).with_ndv(800);
+    let stats = Statistics::new(
+        ROWS,
+        EstimateSource::BaseStats,
+        BTreeMap::from([(0, skewed_stats()), (1, other)]),
+    );
+    let c0 = ColumnVar::new(0);
+    let c1 = ColumnVar::new(1);
+
+    // the range is clipped to the bound, dropping the nulls and the most common values below it
+    let filtered = derive_filter_stats(&stats, &[Rc::new(GreaterThan::new(column(0), int(50)))]);
+    assert_eq!(filtered.output_row_count(), 78);
+    let c0_stats = filtered.column_stats_of(&c0).unwrap();
+    assert_eq!(c0_stats.min(), &Datum::I32(50));
+    assert_eq!(c0_stats.max(), &Datum::I32(99));
+    assert_eq!(c0_stats.null_count(), 0);
+    assert!(c0_stats.mcvs().is_empty());
+    let buckets = c0_stats.histogram().as_ref().unwrap().buckets();
+    assert_eq!(buckets.len(), 1);
+    assert_eq!(buckets[0].lower(), &Datum::I32(50));
+    assert_eq!(c0_stats.ndv(), Some(45));
+    // the other columns are scaled down
+    let c1_stats = filtered.column_stats_of(&c1).unwrap();
+    assert_eq!(c1_stats.null_count(), 16);
+    assert!(c1_stats.ndv().unwrap() <= 78);
+
+    // predicates on the same column restrict it one after the other
+    let filtered = derive_filter_stats(
+        &stats,
+        &[
+            Rc::new(GreaterThanEqual::new(column(0), int(10))),
+            Rc::new(LessThan::new(column(0), int(20))),
+        ],
+    );
+    let c0_stats = filtered.column_stats_of(&c0).unwrap();
+    assert_eq!(c0_stats.min(), &Datum::I32(10));
+    assert_eq!(c0_stats.max(), &Datum::I32(20));
+    assert_eq!(c0_stats.ndv(), Some(10));
+
+    // a most common value holds all rows
+    let filtered = derive_filter_stats(&stats, &[equal(0, 1)]);
+    assert_eq!(filtered.output_row_count(), 400);
+    let c0_stats = filtered.column_stats_of(&c0).unwrap();
+    assert_eq!((c0_stats.min(), c0_stats.max()), (&Datum::I32(1), &Datum::I32(1)));
+    assert_close(c0_stats.mcv_frequency(&Datum::I32(1)).unwrap(), 1.0);
+    assert_eq!(filtered.column_ndv(&c0), Some(1));
+    assert_eq!(filtered.column_stats_of(&c1).unwrap().null_count(), 80);
+
+    let filtered = derive_filter_stats(&stats, &[Rc::new(IsNull::new(column(0)))]);
+    assert_eq!(filtered.output_row_count(), 100);
+    let c0_stats = filtered.column_stats_of(&c0).unwrap();
+    assert_eq!(c0_stats.null_count(), 100);
+    assert_eq!(filtered.column_ndv(&c0), Some(0));
+}
+
+#[test]
+fn test_filter_estimate_source() {
+    let stats = address_stats();
+    let source = |predicates: &[Rc<dyn ScalarExpression>]| derive_filter_stats(&stats, predicates).source();
+    assert_eq!(source(&[]), EstimateSource::BaseStats);
+    assert_eq!(source(&[equal(0, 1)]), EstimateSource::ColumnStats);
+    assert_eq!(source(&[equal(0, 1), equal(2, 5)]), EstimateSource::MultiplePredicates);
+    // column 3 has no statistics
+    assert_eq!(source(&[equal(0, 1), equal(3, 5)]), EstimateSource::DefaultGuess);
+    // never more trustworthy than the input
+    let guessed = derive_filter_stats(&stats, &[equal(3, 5)]);
+    assert_eq!(
+        derive_filter_stats(&guessed, &[equal(0, 1)]).source(),
+        EstimateSource::DefaultGuess
+    );
+}
+
+#[test]
+fn test_should_update_prefers_trustworthy_estimates() {
+    let stats = |rows: u64, source: EstimateSource| -> Rc<dyn Stats> {
+        Rc::new(Statistics::new(rows, source, BTreeMap::new()))
+    };
+    let column_stats = stats(100, EstimateSource::ColumnStats);
+
+    // a guess is ignored even if it has fewer rows
+    assert!(!column_stats.should_update(&stats(10, EstimateSource::DefaultGuess)));
+    assert!(column_stats.should_update(&stats(1000, EstimateSource::BaseStats)));
+    // the lowest row count among estimates as trustworthy
+    assert!(column_stats.should_update(&stats(10, EstimateSource::ColumnStats)));
+    assert!(!column_stats.should_update(&stats(100, EstimateSource::ColumnStats)));
+    assert!(!column_stats.should_update(&stats(1000, EstimateSource::ColumnStats)));
+}
+
+#[test]
+fn test_override_statistics_of_no_rows() {
+    let stats = Statistics::new(0, EstimateSource::BaseStats, BTreeMap::from([(0, skewed_stats())]));
+    let stats = stats.override_output_row_count(1000);
+    let stats = stats.as_any().downcast_ref::<Statistics>().unwrap();
+
+    // nothing is known of the values of the rows
+    let column_stats = stats.column_stats_of(&ColumnVar::new(0)).unwrap();
+    assert_eq!(column_stats.null_count(), 0);
+    assert!(column_stats.histogram().is_none());
+    assert_eq!(column_stats.ndv(), None);
+    assert!(column_stats.mcvs().is_empty());
+    assert_close(
+        predicate_selectivity(&Equal::new(column(0), int(1)), stats),
+        DEFAULT_EQUALITY_SELECTIVITY,
+    );
+}
+
+#[test]
+fn test_multi_column_stats_serialization() {
+    let relation_stats = RelationStats::new("t".to_string(), 10, false, vec![2]).with_multi_col_stat_mdids(vec![3]);
+    let md = Box::new(relation_stats) as Box<dyn Metadata>;
+    let md: Box<dyn Metadata> = serde_json::from_str(&serde_json::to_string(&md).unwrap()).unwrap();
+    assert_eq!(md.downcast_ref::<RelationStats>().unwrap().multi_col_stat_mdids(), &[3]);
+
+    let stats = address_stats().multi_column_stats()[0].1.clone();
+    let md = Box::new(stats) as Box<dyn Metadata>;
+    let md: Box<dyn Metadata> = serde_json::from_str(&serde_json::to_string(&md).unwrap()).unwrap();
+    let stats = md.downcast_ref::<MultiColumnStats>().unwrap();
+    assert_eq!(stats.col_ids(), &[0, 1]);
+    assert_eq!(stats.mcv_frequency(&[Datum::I32(1), Datum::I32(7)]), Some(0.1));
+}
+
+#[test]
+fn test_scan_statistics_keyed_by_column() {
+    let address = address_stats();
+    let mut md_cache = MdCache::new();
+    let column_md = ["city", "zip", "other"]
+        .iter()
+        .enumerate()
+        .map(|(i, name)| ColumnMetadata::new(name.to_string(), i as u64, true, 4 * (i as u32 + 1), Datum::I32(0)))
+        .collect();
+    md_cache.insert(
+        1,
+        Box::new(RelationMetadata::new("t".to_string(), column_md, 2, vec![])),
+    );
+    md_cache.insert(
+        2,
+        Box::new(
+            RelationStats::new("t".to_string(), 10_000, false, vec![10, 11, 12]).with_multi_col_stat_mdids(vec![13]),
+        ),
+    );
+    for (md_id, column_stats) in (10..).zip(address.column_stats().values()) {
+        md_cache.insert(md_id, Box::new(column_stats.clone()));
+    }
+    md_cache.insert(13, Box::new(address.multi_column_stats()[0].1.clone()));
+    let md_accessor = MdAccessor::new(Rc::new(CachedMdProvider::new(md_cache)));
+
+    // the scan names city, zip and other c5, c6 and c7
+    let table_desc = TableDesc::new(1).with_column_ids(vec![5, 6, 7]);
+    let scan_stats = |columns: &[u32]| {
+        let columns = columns.iter().map(|id| ColumnVar::new(*id)).collect();
+        let scan = LogicalScan::new(table_desc.clone(), columns);
+        let stats = LogicalOperator::<Demo>::derive_statistics(&scan, &md_accessor, &[]);
+        stats.as_any().downcast_ref::<Statistics>().unwrap().clone()
+    };
+
+    let stats = scan_stats(&[5, 6]);
+    assert_eq!(stats.column_stats().keys().copied().collect::<Vec<_>>(), vec![5, 6]);
+    assert_eq!(stats.column_stats_of(&ColumnVar::new(6)).unwrap().name(), "zip");
+    assert_eq!(
+        stats.multi_column_stats()[0].0,
+        vec![ColumnVar::new(5), ColumnVar::new(6)]
+    );
+    assert_close(conjunction_selectivity(&[equal(5, 1), equal(6, 7)], &stats), 0.1);
+    assert_eq!(stats.row_width(), 12);
+    // a filter keeps the widths of the columns
+    assert_eq!(derive_filter_stats(&stats, &[equal(5, 1)]).row_width(), 12);
+
+    // the multi-column statistics need all their columns
+    let stats = scan_stats(&[6, 7]);
+    assert_eq!(stats.column_stats().keys().copied().collect::<Vec<_>>(), vec![6, 7]);
+    assert!(stats.multi_column_stats().is_empty());
+    assert_eq!(stats.row_width(), 20);
+}
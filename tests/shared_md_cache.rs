@@ -0,0 +1,328 @@
+use cso_core::metadata::MdProvider as _;
+use cso_demo::dsl::{match_physical_plan, parse_logical_plan, parse_required_properties, Catalog};
+use cso_demo::metadata::{JsonDirMdProvider, MdAccessor, MdError, Metadata, SharedMdCache};
+use cso_demo::rule::create_rule_set;
+use cso_demo::statistics::RelationStats;
+use cso_demo::{Demo, Optimizer, Options};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::thread;
+
+/// A mutable in-memory provider of relation statistics counting its retrievals.
+struct CountingMdProvider {
+    rows: Mutex<HashMap<u64, u64>>,
+    retrievals: AtomicUsize,
+}
+
+impl CountingMdProvider {
+    fn new() -> Self {
+        Self {
+            rows: Mutex::new((1..=5).map(|md_id| (md_id, 100)).collect()),
+            retrievals: AtomicUsize::new(0),
+        }
+    }
+
+    fn update(&self, md_id: u64, rows: u64) {
+        self.rows.lock().unwrap().insert(md_id, rows);
+    }
+
+    fn retrievals(&self) -> usize {
+        self.retrievals.load(Ordering::SeqCst)
+    }
+}
+
+impl cso_core::metadata::MdProvider<Demo> for CountingMdProvider {
+    fn retrieve_metadata(&self, md_id: &u64) -> Result<Box<dyn Metadata>, MdError> {
+        self.retrievals.fetch_add(1, Ordering::SeqCst);
+        match self.rows.lock().unwrap().get(md_id) {
+            Some(rows) => Ok(relation_stats(*md_id, *rows)),
+            None => Err(MdError::NotFound {
+                md_id: md_id.to_string(),
+            }),
+        }
+    }
+}
+
+fn relation_stats(md_id: u64, rows: u64) -> Box<dyn Metadata> {
+    Box::new(RelationStats::new(format!("t{md_id}"), rows, false, vec![]))
+}
+
+fn rows(md: Box<dyn Metadata>) -> u64 {
+    md.downcast_ref::<RelationStats>().unwrap().rows()
+}
+
+#[test]
+fn test_cache_is_shared_by_optimizations() {
+    let md_provider = Arc::new(CountingMdProvider::new());
+    let shared_cache = Arc::new(SharedMdCache::new(md_provider.clone(), 16));
+
+    for _ in 0..3 {
+        let md_accessor = MdAccessor::new(Rc::new(shared_cache.snapshot()));
+        assert_eq!(rows(md_accessor.retrieve_metadata(&1).unwrap()), 100);
+        assert_eq!(rows(md_accessor.retrieve_metadata(&2).unwrap()), 100);
+    }
+    assert_eq!(md_provider.retrievals(), 2);
+    assert_eq!(shared_cache.len(), 2);
+
+    let err = shared_cache.retrieve_metadata(&42).err().unwrap();
+    assert_eq!(err.to_string(), "metadata 42 not found");
+    assert!(!shared_cache.contains(&42));
+}
+
+#[test]
+fn test_capacity_eviction() {
+    let md_provider = Arc::new(CountingMdProvider::new());
+    let shared_cache = SharedMdCache::new(md_provider.clone(), 2);
+
+    shared_cache.retrieve_metadata(&1).unwrap();
+    shared_cache.retrieve_metadata(&2).unwrap();
+    // 1 becomes the most recently used entry, so 2 is evicted
+    shared_cache.retrieve_metadata(&1).unwrap();
+    shared_cache.retrieve_metadata(&3).unwrap();
+
+    assert_eq!(shared_cache.len(), 2);
+    assert!(shared_cache.contains(&1));
+    assert!(!shared_cache.contains(&2));
+    assert!(shared_cache.contains(&3));
+    assert_eq!(md_provider.retrievals(), 3);
+
+    shared_cache.retrieve_metadata(&2).unwrap();
+    assert!(!shared_cache.contains(&1));
+    assert_eq!(md_provider.retrievals(), 4);
+}
+
+#[test]
+fn test_invalidation_bumps_version() {
+    let md_provider = Arc::new(CountingMdProvider::new());
+    let shared_cache = SharedMdCache::new(md_provider.clone(), 16);
+
+    let (_, version) = shared_cache.retrieve_versioned(&1).unwrap();
+    assert_eq!(shared_cache.version(&1), Some(version));
+
+    md_provider.update(1, 500);
+    assert_eq!(rows(shared_cache.retrieve_metadata(&1).unwrap()), 100);
+
+    assert!(shared_cache.invalidate(&1));
+    assert!(!shared_cache.invalidate(&1));
+    assert_eq!(shared_cache.version(&1), None);
+
+    let (md, new_version) = shared_cache.retrieve_versioned(&1).unwrap();
+    assert_eq!(rows(md), 500);
+    assert!(new_version > version);
+
+    shared_cache.invalidate_all();
+    assert!(shared_cache.is_empty());
+}
+
+#[test]
+fn test_optimization_sees_consistent_snapshot() {
+    let md_provider = Arc::new(CountingMdProvider::new());
+    let shared_cache = Arc::new(SharedMdCache::new(md_provider.clone(), 16));
+
+    let md_accessor = MdAccessor::new(Rc::new(shared_cache.snapshot()));
+    assert_eq!(rows(md_accessor.retrieve_metadata(&1).unwrap()), 100);
+
+    md_provider.update(1, 500);
+    shared_cache.invalidate(&1);
+
+    // the running optimization keeps the metadata it has seen, new ones see the update
+    assert_eq!(rows(md_accessor.retrieve_metadata(&1).unwrap()), 100);
+    let new_md_accessor = MdAccessor::new(Rc::new(shared_cache.snapshot()));
+    assert_eq!(rows(new_md_accessor.retrieve_metadata(&1).unwrap()), 500);
+}
+
+#[test]
+fn test_snapshot_sees_metadata_invalidated_after_it() {
+    let md_provider = Arc::new(CountingMdProvider::new());
+    let shared_cache = Arc::new(SharedMdCache::new(md_provider.clone(), 16));
+    shared_cache.retrieve_metadata(&2).unwrap();
+
+    let md_accessor = MdAccessor::new(Rc::new(shared_cache.snapshot()));
+    assert_eq!(rows(md_accessor.retrieve_metadata(&1).unwrap()), 100);
+
+    // 2 changes while the optimization runs, before it reads 2
+    md_provider.update(2, 500);
+    assert!(shared_cache.invalidate(&2));
+    assert_eq!(rows(shared_cache.retrieve_metadata(&2).unwrap()), 500);
+
+    // the optimization sees 2 as of when it started, consistent with 1
+    assert_eq!(rows(md_accessor.retrieve_metadata(&2).unwrap()), 100);
+    let new_md_accessor = MdAccessor::new(Rc::new(shared_cache.snapshot()));
+    assert_eq!(rows(new_md_accessor.retrieve_metadata(&2).unwrap()), 500);
+
+    // reading 1 isn't affected by the invalidation of 2
+    assert_eq!(rows(new_md_accessor.retrieve_metadata(&1).unwrap()), 100);
+    assert_eq!(md_provider.retrievals(), 3);
+}
+
+#[test]
+fn test_superseded_metadata_is_dropped_with_last_snapshot() {
+    let md_provider = Arc::new(CountingMdProvider::new());
+    let shared_cache = Arc::new(SharedMdCache::new(md_provider.clone(), 16));
+    shared_cache.retrieve_metadata(&1).unwrap();
+
+    let snapshot = shared_cache.snapshot();
+    md_provider.update(1, 500);
+    shared_cache.invalidate(&1);
+    assert_eq!(rows(snapshot.retrieve_metadata(&1).unwrap()), 100);
+    drop(snapshot);
+
+    // no snapshot can see the old metadata anymore
+    let snapshot = shared_cache.snapshot();
+    md_provider.update(1, 700);
+    assert_eq!(rows(snapshot.retrieve_metadata(&1).unwrap()), 700);
+}
+
+#[test]
+fn test_cache_is_shared_between_threads() {
+    let md_provider = Arc::new(CountingMdProvider::new());
+    let shared_cache = Arc::new(SharedMdCache::new(md_provider.clone(), 16));
+
+    let threads = (0..4)
+        .map(|_| {
+            let shared_cache = shared_cache.clone();
+            thread::spawn(move || {
+                let md_accessor = MdAccessor::new(Rc::new(shared_cache.snapshot()));
+                (1..=5)
+                    .map(|md_id| rows(md_accessor.retrieve_metadata(&md_id).unwrap()))
+                    .sum::<u64>()
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(), 500);
+    }
+    assert_eq!(shared_cache.len(), 5);
+}
+
+static DESERIALIZATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Metadata counting how often it is deserialized.
+#[derive(Clone, Debug, Serialize)]
+struct CountedMd(u64);
+
+impl<'de> Deserialize<'de> for CountedMd {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        DESERIALIZATIONS.fetch_add(1, Ordering::SeqCst);
+        u64::deserialize(deserializer).map(CountedMd)
+    }
+}
+
+#[typetag::serde]
+impl Metadata for CountedMd {}
+
+/// Metadata which fails to serialize.
+#[derive(Clone, Debug, Deserialize)]
+struct UnserializableMd;
+
+impl Serialize for UnserializableMd {
+    fn serialize<S: Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("not serializable"))
+    }
+}
+
+#[typetag::serde]
+impl Metadata for UnserializableMd {}
+
+struct OtherMdProvider;
+
+impl cso_core::metadata::MdProvider<Demo> for OtherMdProvider {
+    fn retrieve_metadata(&self, md_id: &u64) -> Result<Box<dyn Metadata>, MdError> {
+        match md_id {
+            1 => Ok(Box::new(CountedMd(1))),
+            _ => Ok(Box::new(UnserializableMd)),
+        }
+    }
+}
+
+#[test]
+fn test_metadata_is_deserialized_once_per_thread() {
+    let shared_cache = Arc::new(SharedMdCache::new(Arc::new(OtherMdProvider), 16));
+
+    // the fetching thread keeps the metadata it fetched
+    for _ in 0..3 {
+        shared_cache.retrieve_metadata(&1).unwrap();
+    }
+    assert_eq!(DESERIALIZATIONS.load(Ordering::SeqCst), 0);
+
+    let other_cache = shared_cache.clone();
+    thread::spawn(move || {
+        for _ in 0..3 {
+            let md = other_cache.snapshot().retrieve_metadata(&1).unwrap();
+            assert_eq!(md.downcast_ref::<CountedMd>().unwrap().0, 1);
+        }
+    })
+    .join()
+    .unwrap();
+    assert_eq!(DESERIALIZATIONS.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_uncacheable_metadata() {
+    let shared_cache = SharedMdCache::new(Arc::new(OtherMdProvider), 16);
+    let err = shared_cache.retrieve_metadata(&2).err().unwrap();
+    assert_eq!(err.to_string(), "failed to cache metadata 2: not serializable");
+    assert!(!shared_cache.contains(&2));
+}
+
+/// Retrieves the statistics of relation 1 through the shared cache while retrieving others.
+struct ReentrantMdProvider {
+    shared_cache: OnceLock<Weak<SharedMdCache>>,
+}
+
+impl cso_core::metadata::MdProvider<Demo> for ReentrantMdProvider {
+    fn retrieve_metadata(&self, md_id: &u64) -> Result<Box<dyn Metadata>, MdError> {
+        if *md_id == 1 {
+            return Ok(relation_stats(1, 100));
+        }
+        let shared_cache = self.shared_cache.get().and_then(Weak::upgrade).unwrap();
+        let rows = rows(shared_cache.retrieve_metadata(&1)?);
+        Ok(relation_stats(*md_id, rows * *md_id))
+    }
+}
+
+#[test]
+fn test_provider_retrieves_through_cache() {
+    let md_provider = Arc::new(ReentrantMdProvider {
+        shared_cache: OnceLock::new(),
+    });
+    let shared_cache = Arc::new(SharedMdCache::new(md_provider.clone(), 16));
+    md_provider.shared_cache.set(Arc::downgrade(&shared_cache)).unwrap();
+
+    assert_eq!(rows(shared_cache.retrieve_metadata(&3).unwrap()), 300);
+    assert!(shared_cache.contains(&1));
+}
+
+#[test]
+fn test_optimize_with_shared_cache() {
+    let json_provider = Arc::new(JsonDirMdProvider::new(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/catalog"
+    )));
+    let shared_cache = Arc::new(SharedMdCache::new(json_provider, 16));
+
+    let mut catalog = Catalog::new();
+    catalog.add_table("t1", 2);
+    let expected = "Project[c1, c2] <- IndexScan[t1.IDX_1: c0, c1, c2; And(IsNull(c0))]";
+
+    for _ in 0..2 {
+        let plan = parse_logical_plan(
+            "Project[c1, c2] <- Filter[IsNull(c0)] <- Scan[t1: c0, c1, c2]",
+            &catalog,
+        )
+        .unwrap();
+        let required_properties = parse_required_properties("c0").unwrap();
+        let mut optimizer = Optimizer::new(Options::default());
+        let physical_plan = optimizer.optimize(
+            plan,
+            required_properties,
+            MdAccessor::new(Rc::new(shared_cache.snapshot())),
+            create_rule_set(),
+        );
+        assert_eq!(match_physical_plan(&physical_plan, expected, &catalog), Ok(()));
+    }
+    assert_eq!(shared_cache.len(), 4);
+}
@@ -0,0 +1,129 @@
+use cso_core::operator::LogicalOperator;
+use cso_demo::analyze::ColumnStatsBuilder;
+use cso_demo::datum::Datum;
+use cso_demo::expression::ColumnVar;
+use cso_demo::metadata::{CachedMdProvider, MdAccessor, MdCache, Metadata};
+use cso_demo::operator::logical_scan::{LogicalScan, TableDesc};
+use cso_demo::sketch::{HyperLogLog, DEFAULT_HLL_PRECISION};
+use cso_demo::statistics::{ColumnMetadata, ColumnStats, RelationMetadata, RelationStats, Statistics};
+use cso_demo::Demo;
+use std::rc::Rc;
+
+fn sketch_of(values: impl Iterator<Item = i32>) -> HyperLogLog {
+    let mut sketch = HyperLogLog::new(DEFAULT_HLL_PRECISION);
+    values.for_each(|value| sketch.add(&Datum::I32(value)));
+    sketch
+}
+
+fn assert_within(estimate: u64, expected: u64, error: f64) {
+    let actual_error = (estimate as f64 - expected as f64).abs() / expected as f64;
+    assert!(
+        actual_error <= error,
+        "estimate {estimate} of {expected} is off by {actual_error}"
+    );
+}
+
+#[test]
+fn test_estimate() {
+    assert_eq!(HyperLogLog::new(DEFAULT_HLL_PRECISION).estimate(), 0);
+    assert_eq!(sketch_of(0..10).estimate(), 10);
+    // duplicates don't count
+    assert_eq!(sketch_of((0..1000).map(|v| v % 10)).estimate(), 10);
+
+    assert_within(sketch_of(0..1000).estimate(), 1000, 0.02);
+    assert_within(sketch_of(0..100_000).estimate(), 100_000, 0.05);
+    assert_within(sketch_of(-500_000..500_000).estimate(), 1_000_000, 0.05);
+}
+
+#[test]
+fn test_merge() {
+    // two shards sharing half of their values
+    let mut shard1 = sketch_of(0..20_000);
+    let shard2 = sketch_of(10_000..30_000);
+    shard1.merge(&shard2);
+
+    assert_eq!(shard1, sketch_of(0..30_000));
+    assert_within(shard1.estimate(), 30_000, 0.05);
+}
+
+#[test]
+#[should_panic(expected = "cannot merge hyperloglogs of different precisions")]
+fn test_merge_different_precisions() {
+    HyperLogLog::new(10).merge(&HyperLogLog::new(12));
+}
+
+#[test]
+fn test_sketch_from_partitions() {
+    let mut partitions = (0..4)
+        .map(|_| ColumnStatsBuilder::new(0, "c0".to_string()))
+        .collect::<Vec<_>>();
+    for v in 0..40_000 {
+        partitions[v as usize % 4].add(Some(Datum::I32(v / 2)));
+    }
+
+    let mut sketch = HyperLogLog::new(DEFAULT_HLL_PRECISION);
+    for partition in &partitions {
+        sketch.merge(&partition.build_sketch(DEFAULT_HLL_PRECISION));
+    }
+    assert_within(sketch.estimate(), 20_000, 0.05);
+
+    let md = Box::new(sketch.clone()) as Box<dyn Metadata>;
+    let json = serde_json::to_string(&md).unwrap();
+    let md: Box<dyn Metadata> = serde_json::from_str(&json).unwrap();
+    assert_eq!(md.downcast_ref::<HyperLogLog>(), Some(&sketch));
+}
+
+#[test]
+fn test_deserialize_malformed_sketch() {
+    let json = format!(r#"{{"precision": 4, "registers": {:?}}}"#, vec![0; 16]);
+    assert_eq!(serde_json::from_str::<HyperLogLog>(&json).unwrap(), HyperLogLog::new(4));
+
+    let json = r#"{"precision": 4, "registers": [0, 0, 0]}"#;
+    let err = serde_json::from_str::<HyperLogLog>(json).err().unwrap();
+    assert!(
+        err.to_string()
+            .starts_with("hyperloglog of precision 4 must have 16 registers, got 3"),
+        "{err}"
+    );
+
+    let json = r#"{"type": "HyperLogLog", "precision": 0, "registers": [0]}"#;
+    assert!(serde_json::from_str::<Box<dyn Metadata>>(json).is_err());
+}
+
+fn derive_scan_stats(md_cache: MdCache) -> Statistics {
+    let md_accessor = MdAccessor::new(Rc::new(CachedMdProvider::new(md_cache)));
+    let table_desc = TableDesc::new(2).with_column_ids(vec![10, 11]);
+    let scan = LogicalScan::new(table_desc, vec![ColumnVar::new(10), ColumnVar::new(11)]);
+    let stats = LogicalOperator::<Demo>::derive_statistics(&scan, &md_accessor, &[]);
+    stats.as_any().downcast_ref::<Statistics>().unwrap().clone()
+}
+
+/// Table t(a, b) of 1000 rows, a has 50 distinct values according to its sketch, b has none.
+/// The scan names them c10 and c11.
+fn md_cache() -> MdCache {
+    let columns = ["a", "b"]
+        .iter()
+        .enumerate()
+        .map(|(i, name)| ColumnMetadata::new(name.to_string(), i as u64, true, 4, Datum::I32(0)))
+        .collect();
+    let relation_md = RelationMetadata::new("t".to_string(), columns, 1, vec![]);
+    let relation_stats = RelationStats::new("t".to_string(), 1000, false, vec![3]);
+    let column_stats = ColumnStats::new(0, "a".to_string(), Datum::I32(0), Datum::I32(49), 0, None).with_sketch_mdid(4);
+
+    let mut md_cache = MdCache::new();
+    md_cache.insert(1, Box::new(relation_stats));
+    md_cache.insert(2, Box::new(relation_md));
+    md_cache.insert(3, Box::new(column_stats));
+    md_cache.insert(4, Box::new(sketch_of(0..50)));
+    md_cache
+}
+
+#[test]
+fn test_scan_stats_ndv_from_sketch() {
+    let stats = derive_scan_stats(md_cache());
+    let (a, b) = (ColumnVar::new(10), ColumnVar::new(11));
+    assert_eq!(stats.column_stats_of(&a).unwrap().ndv(), Some(50));
+    assert_eq!(stats.column_ndv(&a), Some(50));
+    assert_eq!(stats.column_ndv(&b), None);
+    assert!(stats.column_stats_of(&ColumnVar::new(0)).is_none());
+}
@@ -1,3 +1,5 @@
+use cso_core::metadata::Stats;
+use cso_core::operator::{LogicalOperator, PhysicalOperator};
 use cso_demo::datum::Datum;
 use cso_demo::expression::ScalarExpression;
 use cso_demo::expression::{ColumnVar, IsNull};
@@ -14,20 +16,23 @@ use cso_demo::operator::physical_sort::{OrderSpec, Ordering, PhysicalSort};
 use cso_demo::property::sort_property::SortProperty;
 use cso_demo::property::PhysicalProperties;
 use cso_demo::rule::create_rule_set;
-use cso_demo::statistics::{Bucket, ColumnMetadata, ColumnStats, Histogram, RelationMetadata, RelationStats};
-use cso_demo::{LogicalPlan, Optimizer, Options, PhysicalPlan};
+use cso_demo::statistics::{
+    Bucket, ColumnMetadata, ColumnStats, EstimateSource, Histogram, RelationMetadata, RelationStats, Statistics,
+};
+use cso_demo::{Demo, LogicalPlan, Optimizer, Options, PhysicalPlan};
+use std::collections::BTreeMap;
 use std::rc::Rc;
 
-/// Table: x(a, b, c)
-/// Sql: select b, c from x where a is null order by c;
-/// Plan:
-///     Sort(c)
-///         |
-///     Project(b, c)
-///         |
-///     Filter(a is null)
-///         |
-///     Scan(a, b, c)
+// Table: x(a, b, c)
+// Sql: select b, c from x where a is null order by c;
+// Plan:
+//     Sort(c)
+//         |
+//     Project(b, c)
+//         |
+//     Filter(a is null)
+//         |
+//     Scan(a, b, c)
 
 fn logical_scan() -> LogicalPlan {
     let mdid = 2;
@@ -142,4 +147,54 @@ fn test_sort_project_filter_scan() {
 
     let physical_plan = optimizer.optimize(project, required_properties, md_accessor, rule_set);
     assert_eq!(physical_plan, expected_physical_plan());
+
+    // explain shows where each estimate comes from, `a` having no statistics
+    let explain = physical_plan.explain();
+    let lines = explain.lines().collect::<Vec<_>>();
+    assert!(lines[0].ends_with("[rows=45 from default guess]"), "{explain}");
+    assert!(lines[3].ends_with("[rows=9011 from base stats]"), "{explain}");
+}
+
+#[test]
+fn test_row_width_cost() {
+    let narrow = Statistics::new(10_000, EstimateSource::BaseStats, BTreeMap::new())
+        .with_column_widths(BTreeMap::from([(0, 4)]));
+    let wide = narrow
+        .clone()
+        .with_column_widths(BTreeMap::from([(0, 4), (1, 4), (2, 4)]));
+
+    // sorting wider rows moves three times as many bytes
+    let sort = PhysicalSort::new(OrderSpec {
+        order_desc: vec![Ordering::new(0)],
+    });
+    let sort_cost = |stats: &Statistics| PhysicalOperator::<Demo>::compute_cost(&sort, Some(stats)).value();
+    assert!((sort_cost(&wide) / sort_cost(&narrow) - 3.0).abs() < 1e-9);
+
+    let scan = PhysicalScan::new(TableDesc::new(2), vec![ColumnVar::new(0)]);
+    let scan_cost = |stats: &Statistics| PhysicalOperator::<Demo>::compute_cost(&scan, Some(stats)).value();
+    assert!(scan_cost(&wide) > scan_cost(&narrow));
+}
+
+#[test]
+fn test_project_narrows_statistics() {
+    let md_accessor = metadata_accessor();
+    let scan = logical_scan();
+    let scan_stats = scan.operator().derive_statistics(&md_accessor, &[]);
+    let project = LogicalProject::new(vec![Rc::new(ColumnVar::new(1)) as Rc<dyn ScalarExpression>]);
+    let project_stats =
+        LogicalOperator::<Demo>::derive_statistics(&project, &md_accessor, std::slice::from_ref(&scan_stats));
+
+    let scan_stats = scan_stats.as_any().downcast_ref::<Statistics>().unwrap();
+    let project_stats = project_stats.as_any().downcast_ref::<Statistics>().unwrap();
+    assert_eq!(project_stats.output_row_count(), scan_stats.output_row_count());
+    assert_eq!(project_stats.row_width(), 4);
+    assert_eq!(scan_stats.row_width(), 12);
+    assert_eq!(project_stats.column_stats().keys().collect::<Vec<_>>(), vec![&1]);
+
+    // sorting the projected rows moves a third of the bytes of sorting the scanned ones
+    let sort = PhysicalSort::new(OrderSpec {
+        order_desc: vec![Ordering::new(1)],
+    });
+    let sort_cost = |stats: &Statistics| PhysicalOperator::<Demo>::compute_cost(&sort, Some(stats)).value();
+    assert!((sort_cost(scan_stats) / sort_cost(project_stats) - 3.0).abs() < 1e-9);
 }
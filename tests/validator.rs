@@ -0,0 +1,116 @@
+use cso_core::cost::Cost;
+use cso_core::metadata::Stats;
+use cso_core::validator::validate_plan;
+use cso_demo::expression::{ColumnVar, IsNull, ScalarExpression};
+use cso_demo::operator::logical_scan::TableDesc;
+use cso_demo::operator::physical_filter::PhysicalFilter;
+use cso_demo::operator::physical_project::PhysicalProject;
+use cso_demo::operator::physical_scan::PhysicalScan;
+use cso_demo::operator::physical_sort::{OrderSpec, Ordering, PhysicalSort};
+use cso_demo::operator::{OperatorId, PhysicalOperator};
+use cso_demo::property::sort_property::SortProperty;
+use cso_demo::property::PhysicalProperties;
+use cso_demo::{Demo, PhysicalPlan};
+use std::rc::Rc;
+
+fn sorted_on(id: u32) -> Rc<PhysicalProperties> {
+    let order = OrderSpec {
+        order_desc: vec![Ordering::new(id)],
+    };
+    PhysicalProperties::with_property(Box::new(SortProperty::with_order(order)))
+}
+
+fn scan() -> PhysicalPlan {
+    let scan = PhysicalScan::new(TableDesc::new(1), vec![ColumnVar::new(0), ColumnVar::new(1)]);
+    PhysicalPlan::new(Rc::new(scan), vec![])
+}
+
+fn filter(id: u32, input: PhysicalPlan) -> PhysicalPlan {
+    let filter = PhysicalFilter::new(Rc::new(IsNull::new(Box::new(ColumnVar::new(id)))));
+    PhysicalPlan::new(Rc::new(filter), vec![input])
+}
+
+fn sort(id: u32, input: PhysicalPlan) -> PhysicalPlan {
+    let sort = PhysicalSort::new(OrderSpec {
+        order_desc: vec![Ordering::new(id)],
+    });
+    PhysicalPlan::new(Rc::new(sort), vec![input])
+}
+
+/// An operator streaming over input sorted on c0, like a merge join would.
+#[derive(Clone, Debug)]
+struct SortedInputOperator;
+
+impl cso_core::operator::PhysicalOperator<Demo> for SortedInputOperator {
+    fn name(&self) -> &str {
+        "sorted input"
+    }
+
+    fn operator_id(&self) -> &OperatorId {
+        &OperatorId::PhysicalFilter
+    }
+
+    fn derive_output_properties(&self, child_props: &[Rc<PhysicalProperties>]) -> Rc<PhysicalProperties> {
+        child_props[0].clone()
+    }
+
+    fn required_properties(&self, _input_prop: Rc<PhysicalProperties>) -> Vec<Vec<Rc<PhysicalProperties>>> {
+        vec![vec![sorted_on(0)]]
+    }
+
+    fn compute_cost(&self, _stats: Option<&dyn Stats>) -> Cost {
+        Cost::new(0.0)
+    }
+
+    fn equal(&self, other: &PhysicalOperator) -> bool {
+        other.downcast_ref::<SortedInputOperator>().is_some()
+    }
+}
+
+#[test]
+fn test_valid_plan() {
+    let plan = sort(1, filter(0, scan()));
+    assert_eq!(validate_plan(&plan, &sorted_on(1)), Ok(()));
+    assert_eq!(validate_plan(&plan, &Rc::new(PhysicalProperties::new())), Ok(()));
+
+    let project = PhysicalProject::new(vec![Rc::new(ColumnVar::new(1)) as Rc<dyn ScalarExpression>]);
+    let plan = PhysicalPlan::new(Rc::new(project), vec![scan()]);
+    assert_eq!(validate_plan(&plan, &Rc::new(PhysicalProperties::new())), Ok(()));
+}
+
+#[test]
+fn test_missing_columns() {
+    let plan = sort(1, filter(5, scan()));
+    let err = validate_plan(&plan, &sorted_on(1)).unwrap_err();
+    assert_eq!(err, "physical filter references columns [5] not provided by its inputs");
+
+    let plan = sort(7, scan());
+    let err = validate_plan(&plan, &sorted_on(7)).unwrap_err();
+    assert_eq!(err, "physical sort references columns [7] not provided by its inputs");
+}
+
+#[test]
+fn test_column_dropped_by_project() {
+    // Sort[c0] <- Project[c1] <- Scan[c0, c1]
+    let project = PhysicalProject::new(vec![Rc::new(ColumnVar::new(1)) as Rc<dyn ScalarExpression>]);
+    let plan = sort(0, PhysicalPlan::new(Rc::new(project), vec![scan()]));
+    let err = validate_plan(&plan, &sorted_on(0)).unwrap_err();
+    assert_eq!(err, "physical sort references columns [0] not provided by its inputs");
+}
+
+#[test]
+fn test_root_properties_not_satisfied() {
+    let plan = filter(0, scan());
+    let err = validate_plan(&plan, &sorted_on(0)).unwrap_err();
+    assert!(err.starts_with("physical filter delivers []"), "{err}");
+}
+
+#[test]
+fn test_input_properties_not_delivered() {
+    let plan = PhysicalPlan::new(Rc::new(SortedInputOperator), vec![sort(1, scan())]);
+    let err = validate_plan(&plan, &Rc::new(PhysicalProperties::new())).unwrap_err();
+    assert!(err.starts_with("inputs of sorted input deliver"), "{err}");
+
+    let plan = PhysicalPlan::new(Rc::new(SortedInputOperator), vec![sort(0, scan())]);
+    assert_eq!(validate_plan(&plan, &Rc::new(PhysicalProperties::new())), Ok(()));
+}
@@ -0,0 +1,164 @@
+use cso_demo::datum::Datum;
+use cso_demo::dsl::{match_physical_plan, parse_logical_plan, parse_required_properties, Catalog};
+use cso_demo::expression::ColumnVar;
+use cso_demo::metadata::{CachedMdProvider, MdAccessor, MdCache, Metadata};
+use cso_demo::operator::logical_partition_scan::{LogicalPartitionScan, PartitionDesc};
+use cso_demo::operator::logical_scan::TableDesc;
+use cso_demo::operator::logical_view_scan::LogicalViewScan;
+use cso_demo::rule::create_rule_set;
+use cso_demo::statistics::{ColumnMetadata, RelationMetadata, RelationStats, ViewMetadata};
+use cso_demo::{LogicalPlan, Optimizer, Options};
+use std::rc::Rc;
+
+/// A table t of columns (c0, c1, c2), with the views
+/// - v(a, b) as `Project[c0, c1] <- Filter[c2 > 10] <- Scan[t: c0, c1, c2]`
+/// - w(a, b) as `Filter[c1 = 2] <- View[v: c0, c1]`
+fn md_cache() -> MdCache {
+    let mut md_cache = MdCache::new();
+    let columns = ["c0", "c1", "c2"]
+        .iter()
+        .enumerate()
+        .map(|(i, name)| ColumnMetadata::new(name.to_string(), i as u64, true, 4, Datum::I32(0)))
+        .collect();
+    let relation_md = RelationMetadata::new("t".to_string(), columns, 2, vec![]);
+    let stats = RelationStats::new("t".to_string(), 1000, false, vec![]);
+    md_cache.insert(1, Box::new(relation_md) as Box<dyn Metadata>);
+    md_cache.insert(2, Box::new(stats) as Box<dyn Metadata>);
+
+    let views = [
+        ("v", "Project[c0, c1] <- Filter[c2 > 10] <- Scan[t: c0, c1, c2]"),
+        ("w", "Filter[c1 = 2] <- View[v: c0, c1]"),
+    ];
+    for (i, (name, definition)) in views.into_iter().enumerate() {
+        let definition = parse_logical_plan(definition, &Catalog::from_md_cache(&md_cache)).unwrap();
+        let view_md = ViewMetadata::new(
+            name.to_string(),
+            definition,
+            vec!["a".to_string(), "b".to_string()],
+            vec![ColumnVar::new(0), ColumnVar::new(1)],
+        );
+        md_cache.insert(3 + i as u64, Box::new(view_md) as Box<dyn Metadata>);
+    }
+    md_cache
+}
+
+fn assert_plan(plan: &str, expected: &str) {
+    let md_cache = md_cache();
+    let catalog = Catalog::from_md_cache(&md_cache);
+    let plan = parse_logical_plan(plan, &catalog).unwrap();
+    let required_properties = parse_required_properties("").unwrap();
+    let md_accessor = MdAccessor::new(Rc::new(CachedMdProvider::new(md_cache)));
+    let mut optimizer = Optimizer::new(Options::default());
+    let physical_plan = optimizer.optimize(plan, required_properties, md_accessor, create_rule_set());
+    if let Err(diff) = match_physical_plan(&physical_plan, expected, &catalog) {
+        panic!("unexpected plan:\n{diff}");
+    }
+}
+
+fn expand(plan: &str, next_column_id: &mut u32) -> LogicalPlan {
+    expand_with(md_cache(), plan, next_column_id)
+}
+
+fn expand_with(md_cache: MdCache, plan: &str, next_column_id: &mut u32) -> LogicalPlan {
+    let plan = parse_logical_plan(plan, &Catalog::from_md_cache(&md_cache)).unwrap();
+    let md_accessor = MdAccessor::new(Rc::new(CachedMdProvider::new(md_cache)));
+    plan.expand(&md_accessor, next_column_id)
+}
+
+/// Adds the view of columns (a, b), as c0 and c1 in the definition, with the mdid.
+fn add_view(md_cache: &mut MdCache, md_id: u64, name: &str, definition: LogicalPlan) {
+    let view_md = ViewMetadata::new(
+        name.to_string(),
+        definition,
+        vec!["a".to_string(), "b".to_string()],
+        vec![ColumnVar::new(0), ColumnVar::new(1)],
+    );
+    md_cache.insert(md_id, Box::new(view_md) as Box<dyn Metadata>);
+}
+
+fn view_scan(md_id: u64) -> LogicalPlan {
+    let view_scan = LogicalViewScan::new(md_id, vec![ColumnVar::new(0), ColumnVar::new(1)]);
+    LogicalPlan::new(Rc::new(view_scan), vec![], vec![])
+}
+
+#[test]
+fn test_expand_view() {
+    assert_plan(
+        "Filter[c10 = 1] <- View[v: c10, c11]",
+        "Filter[c10 = 1] <- Project[c10, c11] <- Filter[c12 > 10] <- Scan[t: c10, c11, c12]",
+    );
+}
+
+#[test]
+fn test_expand_nested_view() {
+    assert_plan(
+        "View[w: c5, c6]",
+        "Filter[c6 = 2] <- Project[c5, c6] <- Filter[c7 > 10] <- Scan[t: c5, c6, c7]",
+    );
+}
+
+#[test]
+fn test_references_have_own_columns() {
+    let mut next_column_id = 22;
+    let first = expand("View[v: c10, c11]", &mut next_column_id);
+    let second = expand("View[v: c20, c21]", &mut next_column_id);
+    assert_eq!(next_column_id, 24);
+
+    assert_eq!(first.derive_column_ids().iter().collect::<Vec<_>>(), vec![10, 11, 22]);
+    assert_eq!(second.derive_column_ids().iter().collect::<Vec<_>>(), vec![20, 21, 23]);
+}
+
+#[test]
+#[should_panic(expected = "view x is defined in terms of itself")]
+fn test_view_cycle() {
+    // x reads y, which reads x
+    let mut md_cache = md_cache();
+    add_view(&mut md_cache, 5, "x", view_scan(6));
+    add_view(&mut md_cache, 6, "y", view_scan(5));
+    expand_with(md_cache, "View[x: c10, c11]", &mut 12);
+}
+
+#[test]
+fn test_expand_view_of_partitions() {
+    let mut md_cache = md_cache();
+    let partition_scan = LogicalPartitionScan::new(
+        TableDesc::new(1),
+        vec![ColumnVar::new(0), ColumnVar::new(1), ColumnVar::new(2)],
+        vec![PartitionDesc::new("p0".to_string(), 2)],
+    );
+    add_view(
+        &mut md_cache,
+        5,
+        "x",
+        LogicalPlan::new(Rc::new(partition_scan), vec![], vec![]),
+    );
+
+    let mut next_column_id = 12;
+    let plan = expand_with(md_cache, "View[x: c10, c11]", &mut next_column_id);
+    let partition_scan = plan.operator().downcast_ref::<LogicalPartitionScan>().unwrap();
+    assert_eq!(partition_scan.table_desc().column(2), ColumnVar::new(12));
+    assert_eq!(
+        partition_scan.output_columns(),
+        &[ColumnVar::new(10), ColumnVar::new(11), ColumnVar::new(12)]
+    );
+    assert_eq!(partition_scan.partitions()[0].name(), "p0");
+    assert_eq!(next_column_id, 13);
+}
+
+#[test]
+fn test_serialize_view_md() {
+    let md_cache = md_cache();
+    let view_md = md_cache.iter().find(|(md_id, _)| **md_id == 3).unwrap().1;
+    let json = serde_json::to_string(view_md).unwrap();
+    let md: Box<dyn Metadata> = serde_json::from_str(&json).unwrap();
+    let view_md = md.downcast_ref::<ViewMetadata>().unwrap();
+
+    assert_eq!(view_md.name(), "v");
+    assert_eq!(view_md.column_names(), &["a".to_string(), "b".to_string()]);
+    assert_eq!(view_md.output_columns(), &[ColumnVar::new(0), ColumnVar::new(1)]);
+    assert_eq!(view_md.definition().inputs().len(), 1);
+    assert_eq!(
+        view_md.definition().derive_column_ids().iter().collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+}